@@ -6,6 +6,7 @@ use crate::options::print_usage;
 use crate::tempfile::Tempfile;
 use crate::vm;
 use getopts::Options;
+use std::path::PathBuf;
 
 const USAGE: &str = "Usage: inko run [OPTIONS] [FILE]
 
@@ -75,6 +76,7 @@ pub fn run(arguments: &[String]) -> Result<i32, Error> {
             matches.opt_strs("i"),
             matches.opt_str("f"),
             &matches.free,
+            None,
         )
     } else {
         Err(Error::generic(
@@ -91,12 +93,12 @@ fn run_file(
     arguments: &[String],
 ) -> Result<i32, Error> {
     let status = if input.ends_with(BYTECODE_IMAGE_EXT) {
-        vm::start(input, arguments)
+        vm::start(input, arguments, None)?
     } else {
         let image = Tempfile::new(BYTECODE_IMAGE_EXT)?;
 
         compile(input, image.path(), include, format)?;
-        vm::start(image.path(), arguments)
+        vm::start(image.path(), arguments, None)?
     };
 
     Ok(status)
@@ -104,12 +106,14 @@ fn run_file(
 
 /// Runs Inko source code that is provided directly, instead of through a file.
 ///
-/// This method is public so the "test" command can reuse it.
+/// This method is public so the "test" command can reuse it. When a coverage
+/// file is given, the line coverage of the program is written to this file.
 pub fn run_eval(
     source: &str,
     include: Vec<String>,
     format: Option<String>,
     arguments: &[String],
+    coverage_file: Option<PathBuf>,
 ) -> Result<i32, Error> {
     let mut input = Tempfile::new(SOURCE_FILE_EXT)?;
     let image = Tempfile::new(BYTECODE_IMAGE_EXT)?;
@@ -118,7 +122,7 @@ pub fn run_eval(
     input.flush();
 
     compile(input.path(), image.path(), include, format)?;
-    Ok(vm::start(image.path(), arguments))
+    vm::start(image.path(), arguments, coverage_file)
 }
 
 /// Compiles the source code in the given input path, producing a bytecode image
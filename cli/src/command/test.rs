@@ -21,6 +21,14 @@ Examples:
 
     inko test           # Runs all unit tests in ./tests/test
     inko test -d foo    # Runs all unit tests in ./foo/test
+    inko test -c        # Runs all unit tests and writes ./lcov.info
+
+Coverage:
+
+When using the -c/--coverage option, the lines of code executed by the unit
+tests are recorded. Once the tests finish, this coverage is written to a file
+in the LCOV format. This file defaults to lcov.info in the current working
+directory, and can be changed using the --coverage-file option.
 
 Output formats:
 
@@ -34,6 +42,10 @@ const DEFAULT_TESTS_ROOT: &str = "tests";
 /// The name of the directory that contains unit tests.
 const TEST_DIRECTORY: &str = "test";
 
+/// The default file (relative to the current working directory) to write line
+/// coverage to.
+const DEFAULT_COVERAGE_FILE: &str = "lcov.info";
+
 /// Compiles and runs Inko unit tests.
 pub fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
@@ -53,6 +65,19 @@ pub fn run(arguments: &[String]) -> Result<i32, Error> {
         "FORMAT",
     );
 
+    options.optflag(
+        "c",
+        "coverage",
+        "Records the lines of code executed by the unit tests",
+    );
+
+    options.optopt(
+        "",
+        "coverage-file",
+        "The file to write line coverage to, in the LCOV format",
+        "FILE",
+    );
+
     let matches = options.parse(arguments)?;
 
     if matches.opt_present("h") {
@@ -79,12 +104,22 @@ pub fn run(arguments: &[String]) -> Result<i32, Error> {
 
     let modules = test_modules(&root_dir)?;
     let source = generate_source(modules);
+    let coverage_file = if matches.opt_present("c") {
+        let file = matches
+            .opt_str("coverage-file")
+            .unwrap_or_else(|| DEFAULT_COVERAGE_FILE.to_string());
+
+        Some(cwd.join(file))
+    } else {
+        None
+    };
 
     run::run_eval(
         &source,
         vec![root_dir.to_string_lossy().to_string()],
         matches.opt_str("f"),
         &matches.free,
+        coverage_file,
     )
 }
 
@@ -1,15 +1,25 @@
 //! Functions for interacting with the Inko VM.
+use crate::error::Error;
 use libinko::config::Config;
 use libinko::vm::machine::Machine;
 use libinko::vm::state::State;
+use std::path::PathBuf;
 
-pub fn start(path: &str, arguments: &[String]) -> i32 {
+pub fn start(
+    path: &str,
+    arguments: &[String],
+    coverage_file: Option<PathBuf>,
+) -> Result<i32, Error> {
     let mut config = Config::new();
 
-    config.populate_from_env();
+    config.populate_from_env()?;
+
+    if coverage_file.is_some() {
+        config.coverage_file = coverage_file;
+    }
 
     let machine = Machine::new(State::with_rc(config, arguments));
 
     machine.start(path);
-    machine.state.current_exit_status()
+    Ok(machine.state.current_exit_status())
 }
@@ -184,6 +184,80 @@ module Inkoc
         GetAttributeInSelf
         MoveResult
         FilePath
+        SocketWriteVectored
+        SocketSendFile
+        SocketPeerCredentials
+        SocketSendHandles
+        SocketReceiveHandles
+        SignalSubscribe
+        SignalUnsubscribe
+        ProcessSetPriority
+        ProcessPriority
+        TimerStart
+        TimerCancel
+        ProcessTerminate
+        ProcessTerminationReason
+        ProcessList
+        ProcessInfo
+        ProcessSetReductionBudget
+        GetLocalAttribute
+        IntegerCompareGoto
+        IntegerAddLiteral
+        FloatSqrt
+        FloatPow
+        FloatExp
+        FloatLn
+        FloatLog2
+        FloatLog10
+        FloatSin
+        FloatCos
+        FloatTan
+        FloatAsin
+        FloatAcos
+        FloatAtan
+        FloatAtan2
+        FloatFma
+        IntegerPow
+        IntegerGcd
+        IntegerSqrt
+        DecimalFromString
+        DecimalToString
+        DecimalAdd
+        DecimalSub
+        DecimalMul
+        DecimalDiv
+        DecimalRound
+        DecimalScale
+        DecimalSmaller
+        DecimalGreater
+        DecimalEquals
+        DecimalGreaterOrEqual
+        DecimalSmallerOrEqual
+        ByteArraySlice
+        ByteArrayCopyRange
+        ByteArrayAppend
+        ByteArrayFill
+        ByteArrayFind
+        ByteArrayReadInteger
+        ByteArrayWriteInteger
+        ByteArrayReadFloat
+        ByteArrayWriteFloat
+        StringFind
+        StringFindLast
+        StringStartsWith
+        StringEndsWith
+        StringSplit
+        StringReplace
+        StringTrim
+        StringTrimStart
+        StringTrimEnd
+        StringCharacters
+        StringGraphemes
+        RegexCompile
+        RegexMatch
+        RegexFindAll
+        RegexSplit
+        RegexReplace
       ]
         .each_with_index
         .each_with_object({}) { |(value, index), hash| hash[value] = index }
@@ -54,6 +54,8 @@ module Inkoc
     WRITE_ONLY_FILE_TYPE = 'WriteOnlyFile'
     READ_WRITE_FILE_TYPE = 'ReadWriteFile'
     HASHER_TYPE = 'DefaultHasher'
+    DECIMAL_TYPE = 'Decimal'
+    REGEX_TYPE = 'Regex'
     SELF_TYPE = 'Self'
     NEVER_TYPE = 'Never'
     MODULES_ATTRIBUTE = 'Modules'
@@ -1170,6 +1170,70 @@ module Inkoc
         raw_binary_instruction(:StringByte, node, body)
       end
 
+      def on_raw_string_find(node, body)
+        raw_ternary_instruction(:StringFind, node, body)
+      end
+
+      def on_raw_string_find_last(node, body)
+        raw_ternary_instruction(:StringFindLast, node, body)
+      end
+
+      def on_raw_string_starts_with(node, body)
+        raw_binary_instruction(:StringStartsWith, node, body)
+      end
+
+      def on_raw_string_ends_with(node, body)
+        raw_binary_instruction(:StringEndsWith, node, body)
+      end
+
+      def on_raw_string_split(node, body)
+        raw_binary_instruction(:StringSplit, node, body)
+      end
+
+      def on_raw_string_replace(node, body)
+        raw_ternary_instruction(:StringReplace, node, body)
+      end
+
+      def on_raw_string_trim(node, body)
+        raw_unary_instruction(:StringTrim, node, body)
+      end
+
+      def on_raw_string_trim_start(node, body)
+        raw_unary_instruction(:StringTrimStart, node, body)
+      end
+
+      def on_raw_string_trim_end(node, body)
+        raw_unary_instruction(:StringTrimEnd, node, body)
+      end
+
+      def on_raw_string_characters(node, body)
+        raw_unary_instruction(:StringCharacters, node, body)
+      end
+
+      def on_raw_string_graphemes(node, body)
+        raw_unary_instruction(:StringGraphemes, node, body)
+      end
+
+      def on_raw_regex_compile(node, body)
+        raw_unary_instruction(:RegexCompile, node, body)
+      end
+
+      def on_raw_regex_match(node, body)
+        raw_binary_instruction(:RegexMatch, node, body)
+      end
+
+      def on_raw_regex_find_all(node, body)
+        raw_binary_instruction(:RegexFindAll, node, body)
+      end
+
+      def on_raw_regex_split(node, body)
+        raw_binary_instruction(:RegexSplit, node, body)
+      end
+
+      def on_raw_regex_replace(node, body)
+        raw_ternary_instruction(:RegexReplace, node, body)
+      end
+
       def on_raw_stdin_read(node, body)
         raw_binary_instruction(:StdinRead, node, body)
       end
@@ -1398,6 +1462,42 @@ module Inkoc
         raw_binary_instruction(:ByteArrayToString, node, body)
       end
 
+      def on_raw_byte_array_slice(node, body)
+        raw_ternary_instruction(:ByteArraySlice, node, body)
+      end
+
+      def on_raw_byte_array_copy_range(node, body)
+        raw_quinary_instruction(:ByteArrayCopyRange, node, body)
+      end
+
+      def on_raw_byte_array_append(node, body)
+        raw_binary_instruction(:ByteArrayAppend, node, body)
+      end
+
+      def on_raw_byte_array_fill(node, body)
+        raw_quaternary_instruction(:ByteArrayFill, node, body)
+      end
+
+      def on_raw_byte_array_find(node, body)
+        raw_ternary_instruction(:ByteArrayFind, node, body)
+      end
+
+      def on_raw_byte_array_read_integer(node, body)
+        raw_quinary_instruction(:ByteArrayReadInteger, node, body)
+      end
+
+      def on_raw_byte_array_write_integer(node, body)
+        raw_quinary_instruction(:ByteArrayWriteInteger, node, body)
+      end
+
+      def on_raw_byte_array_read_float(node, body)
+        raw_quaternary_instruction(:ByteArrayReadFloat, node, body)
+      end
+
+      def on_raw_byte_array_write_float(node, body)
+        raw_quinary_instruction(:ByteArrayWriteFloat, node, body)
+      end
+
       def on_raw_get_boolean_prototype(node, body)
         builtin_prototype_instruction(PrototypeID::BOOLEAN, node, body)
       end
@@ -1450,6 +1550,14 @@ module Inkoc
         builtin_prototype_instruction(PrototypeID::HASHER, node, body)
       end
 
+      def on_raw_get_decimal_prototype(node, body)
+        builtin_prototype_instruction(PrototypeID::DECIMAL, node, body)
+      end
+
+      def on_raw_get_regex_prototype(node, body)
+        builtin_prototype_instruction(PrototypeID::REGEX, node, body)
+      end
+
       def on_raw_get_byte_array_prototype(node, body)
         builtin_prototype_instruction(PrototypeID::BYTE_ARRAY, node, body)
       end
@@ -1535,6 +1643,170 @@ module Inkoc
         raw_unary_instruction(:ProcessIdentifier, node, body)
       end
 
+      def on_raw_signal_subscribe(node, body)
+        raw_unary_instruction(:SignalSubscribe, node, body)
+      end
+
+      def on_raw_signal_unsubscribe(node, body)
+        raw_unary_instruction(:SignalUnsubscribe, node, body)
+      end
+
+      def on_raw_process_set_priority(node, body)
+        raw_binary_instruction(:ProcessSetPriority, node, body)
+      end
+
+      def on_raw_process_priority(node, body)
+        raw_unary_instruction(:ProcessPriority, node, body)
+      end
+
+      def on_raw_timer_start(node, body)
+        raw_quaternary_instruction(:TimerStart, node, body)
+      end
+
+      def on_raw_timer_cancel(node, body)
+        raw_unary_instruction(:TimerCancel, node, body)
+      end
+
+      def on_raw_process_terminate(node, body)
+        raw_binary_instruction(:ProcessTerminate, node, body)
+      end
+
+      def on_raw_process_termination_reason(node, body)
+        raw_nullary_instruction(:ProcessTerminationReason, node, body)
+      end
+
+      def on_raw_process_list(node, body)
+        raw_nullary_instruction(:ProcessList, node, body)
+      end
+
+      def on_raw_process_info(node, body)
+        raw_unary_instruction(:ProcessInfo, node, body)
+      end
+
+      def on_raw_process_set_reduction_budget(node, body)
+        raw_ternary_instruction(:ProcessSetReductionBudget, node, body)
+      end
+
+      def on_raw_float_sqrt(node, body)
+        raw_unary_instruction(:FloatSqrt, node, body)
+      end
+
+      def on_raw_float_pow(node, body)
+        raw_binary_instruction(:FloatPow, node, body)
+      end
+
+      def on_raw_float_exp(node, body)
+        raw_unary_instruction(:FloatExp, node, body)
+      end
+
+      def on_raw_float_ln(node, body)
+        raw_unary_instruction(:FloatLn, node, body)
+      end
+
+      def on_raw_float_log2(node, body)
+        raw_unary_instruction(:FloatLog2, node, body)
+      end
+
+      def on_raw_float_log10(node, body)
+        raw_unary_instruction(:FloatLog10, node, body)
+      end
+
+      def on_raw_float_sin(node, body)
+        raw_unary_instruction(:FloatSin, node, body)
+      end
+
+      def on_raw_float_cos(node, body)
+        raw_unary_instruction(:FloatCos, node, body)
+      end
+
+      def on_raw_float_tan(node, body)
+        raw_unary_instruction(:FloatTan, node, body)
+      end
+
+      def on_raw_float_asin(node, body)
+        raw_unary_instruction(:FloatAsin, node, body)
+      end
+
+      def on_raw_float_acos(node, body)
+        raw_unary_instruction(:FloatAcos, node, body)
+      end
+
+      def on_raw_float_atan(node, body)
+        raw_unary_instruction(:FloatAtan, node, body)
+      end
+
+      def on_raw_float_atan2(node, body)
+        raw_binary_instruction(:FloatAtan2, node, body)
+      end
+
+      def on_raw_float_fma(node, body)
+        raw_ternary_instruction(:FloatFma, node, body)
+      end
+
+      def on_raw_integer_pow(node, body)
+        raw_binary_instruction(:IntegerPow, node, body)
+      end
+
+      def on_raw_integer_gcd(node, body)
+        raw_binary_instruction(:IntegerGcd, node, body)
+      end
+
+      def on_raw_integer_sqrt(node, body)
+        raw_unary_instruction(:IntegerSqrt, node, body)
+      end
+
+      def on_raw_decimal_from_string(node, body)
+        raw_unary_instruction(:DecimalFromString, node, body)
+      end
+
+      def on_raw_decimal_to_string(node, body)
+        raw_unary_instruction(:DecimalToString, node, body)
+      end
+
+      def on_raw_decimal_add(node, body)
+        raw_binary_instruction(:DecimalAdd, node, body)
+      end
+
+      def on_raw_decimal_sub(node, body)
+        raw_binary_instruction(:DecimalSub, node, body)
+      end
+
+      def on_raw_decimal_mul(node, body)
+        raw_binary_instruction(:DecimalMul, node, body)
+      end
+
+      def on_raw_decimal_div(node, body)
+        raw_quaternary_instruction(:DecimalDiv, node, body)
+      end
+
+      def on_raw_decimal_round(node, body)
+        raw_ternary_instruction(:DecimalRound, node, body)
+      end
+
+      def on_raw_decimal_scale(node, body)
+        raw_unary_instruction(:DecimalScale, node, body)
+      end
+
+      def on_raw_decimal_smaller(node, body)
+        raw_binary_instruction(:DecimalSmaller, node, body)
+      end
+
+      def on_raw_decimal_greater(node, body)
+        raw_binary_instruction(:DecimalGreater, node, body)
+      end
+
+      def on_raw_decimal_equals(node, body)
+        raw_binary_instruction(:DecimalEquals, node, body)
+      end
+
+      def on_raw_decimal_greater_or_equal(node, body)
+        raw_binary_instruction(:DecimalGreaterOrEqual, node, body)
+      end
+
+      def on_raw_decimal_smaller_or_equal(node, body)
+        raw_binary_instruction(:DecimalSmallerOrEqual, node, body)
+      end
+
       def on_raw_ffi_library_open(node, body)
         raw_unary_instruction(:FFILibraryOpen, node, body)
       end
@@ -1595,6 +1867,26 @@ module Inkoc
         raw_binary_instruction(:SocketWrite, node, body)
       end
 
+      def on_raw_socket_write_vectored(node, body)
+        raw_binary_instruction(:SocketWriteVectored, node, body)
+      end
+
+      def on_raw_socket_send_file(node, body)
+        raw_ternary_instruction(:SocketSendFile, node, body)
+      end
+
+      def on_raw_socket_peer_credentials(node, body)
+        raw_unary_instruction(:SocketPeerCredentials, node, body)
+      end
+
+      def on_raw_socket_send_handles(node, body)
+        raw_binary_instruction(:SocketSendHandles, node, body)
+      end
+
+      def on_raw_socket_receive_handles(node, body)
+        raw_unary_instruction(:SocketReceiveHandles, node, body)
+      end
+
       def on_raw_socket_read(node, body)
         raw_ternary_instruction(:SocketRead, node, body)
       end
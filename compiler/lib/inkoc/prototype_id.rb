@@ -22,5 +22,7 @@ module Inkoc
     WRITE_ONLY_FILE = 17
     READ_WRITE_FILE = 18
     HASHER = 19
+    DECIMAL = 20
+    REGEX = 21
   end
 end
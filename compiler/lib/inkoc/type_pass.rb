@@ -411,6 +411,14 @@ module Inkoc
       typedb.hasher_type
     end
 
+    def on_raw_get_decimal_prototype(*)
+      typedb.decimal_type
+    end
+
+    def on_raw_get_regex_prototype(*)
+      typedb.regex_type
+    end
+
     def on_raw_run_block(*)
       new_any_type
     end
@@ -510,6 +518,72 @@ module Inkoc
       typedb.integer_type.new_instance
     end
 
+    def on_raw_string_find(*)
+      TypeSystem::Optional.wrap(typedb.integer_type.new_instance)
+    end
+
+    def on_raw_string_find_last(*)
+      TypeSystem::Optional.wrap(typedb.integer_type.new_instance)
+    end
+
+    def on_raw_string_starts_with(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_string_ends_with(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_string_split(*)
+      typedb.new_array_of_type(typedb.string_type.new_instance)
+    end
+
+    def on_raw_string_replace(*)
+      typedb.string_type.new_instance
+    end
+
+    def on_raw_string_trim(*)
+      typedb.string_type.new_instance
+    end
+
+    def on_raw_string_trim_start(*)
+      typedb.string_type.new_instance
+    end
+
+    def on_raw_string_trim_end(*)
+      typedb.string_type.new_instance
+    end
+
+    def on_raw_string_characters(*)
+      typedb.new_array_of_type(typedb.string_type.new_instance)
+    end
+
+    def on_raw_string_graphemes(*)
+      typedb.new_array_of_type(typedb.string_type.new_instance)
+    end
+
+    def on_raw_regex_compile(*)
+      typedb.regex_type.new_instance
+    end
+
+    def on_raw_regex_match(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_regex_find_all(*)
+      group = TypeSystem::Optional.wrap(typedb.string_type.new_instance)
+
+      typedb.new_array_of_type(typedb.new_array_of_type(group))
+    end
+
+    def on_raw_regex_split(*)
+      typedb.new_array_of_type(typedb.string_type.new_instance)
+    end
+
+    def on_raw_regex_replace(*)
+      typedb.string_type.new_instance
+    end
+
     def on_raw_stdin_read(*)
       typedb.integer_type.new_instance
     end
@@ -700,6 +774,42 @@ module Inkoc
       typedb.string_type.new_instance
     end
 
+    def on_raw_byte_array_slice(*)
+      typedb.byte_array_type.new_instance
+    end
+
+    def on_raw_byte_array_copy_range(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_byte_array_append(*)
+      typedb.byte_array_type.new_instance
+    end
+
+    def on_raw_byte_array_fill(*)
+      typedb.byte_array_type.new_instance
+    end
+
+    def on_raw_byte_array_find(*)
+      TypeSystem::Optional.wrap(typedb.integer_type.new_instance)
+    end
+
+    def on_raw_byte_array_read_integer(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_byte_array_write_integer(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_byte_array_read_float(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_byte_array_write_float(*)
+      typedb.float_type.new_instance
+    end
+
     def on_raw_get_boolean_prototype(*)
       typedb.boolean_type.new_instance
     end
@@ -772,6 +882,170 @@ module Inkoc
       typedb.integer_type.new_instance
     end
 
+    def on_raw_signal_subscribe(*)
+      typedb.nil_type.new_instance
+    end
+
+    def on_raw_signal_unsubscribe(*)
+      typedb.nil_type.new_instance
+    end
+
+    def on_raw_process_set_priority(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_process_priority(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_timer_start(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_timer_cancel(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_process_terminate(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_process_termination_reason(*)
+      new_any_type
+    end
+
+    def on_raw_process_list(*)
+      typedb.new_array_of_type(new_any_type)
+    end
+
+    def on_raw_process_info(*)
+      typedb.new_array_of_type(new_any_type)
+    end
+
+    def on_raw_process_set_reduction_budget(*)
+      typedb.nil_type.new_instance
+    end
+
+    def on_raw_float_sqrt(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_pow(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_exp(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_ln(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_log2(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_log10(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_sin(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_cos(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_tan(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_asin(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_acos(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_atan(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_atan2(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_float_fma(*)
+      typedb.float_type.new_instance
+    end
+
+    def on_raw_integer_pow(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_integer_gcd(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_integer_sqrt(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_decimal_from_string(*)
+      typedb.decimal_type.new_instance
+    end
+
+    def on_raw_decimal_to_string(*)
+      typedb.string_type.new_instance
+    end
+
+    def on_raw_decimal_add(*)
+      typedb.decimal_type.new_instance
+    end
+
+    def on_raw_decimal_sub(*)
+      typedb.decimal_type.new_instance
+    end
+
+    def on_raw_decimal_mul(*)
+      typedb.decimal_type.new_instance
+    end
+
+    def on_raw_decimal_div(*)
+      typedb.decimal_type.new_instance
+    end
+
+    def on_raw_decimal_round(*)
+      typedb.decimal_type.new_instance
+    end
+
+    def on_raw_decimal_scale(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_decimal_smaller(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_decimal_greater(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_decimal_equals(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_decimal_greater_or_equal(*)
+      typedb.boolean_type.new_instance
+    end
+
+    def on_raw_decimal_smaller_or_equal(*)
+      typedb.boolean_type.new_instance
+    end
+
     def on_raw_ffi_library_open(node, _)
       typedb.ffi_library_type.new_instance
     end
@@ -832,6 +1106,26 @@ module Inkoc
       typedb.integer_type.new_instance
     end
 
+    def on_raw_socket_write_vectored(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_socket_send_file(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_socket_peer_credentials(*)
+      typedb.new_array_of_type(typedb.integer_type.new_instance)
+    end
+
+    def on_raw_socket_send_handles(*)
+      typedb.integer_type.new_instance
+    end
+
+    def on_raw_socket_receive_handles(*)
+      typedb.new_array_of_type(new_any_type)
+    end
+
     def on_raw_socket_read(*)
       typedb.integer_type.new_instance
     end
@@ -9,7 +9,8 @@ module Inkoc
                   :module_type, :ffi_library_type, :ffi_function_type,
                   :ffi_pointer_type, :ip_socket_type, :unix_socket_type,
                   :process_type, :read_only_file_type, :write_only_file_type,
-                  :read_write_file_type, :hasher_type
+                  :read_write_file_type, :hasher_type, :decimal_type,
+                  :regex_type
 
       def initialize
         @object_type = new_builtin_object(Config::OBJECT_CONST, nil)
@@ -34,6 +35,8 @@ module Inkoc
         @write_only_file_type = new_builtin_object(Config::WRITE_ONLY_FILE_TYPE)
         @read_write_file_type = new_builtin_object(Config::READ_WRITE_FILE_TYPE)
         @hasher_type = new_builtin_object(Config::HASHER_TYPE)
+        @decimal_type = new_builtin_object(Config::DECIMAL_TYPE)
+        @regex_type = new_builtin_object(Config::REGEX_TYPE)
         @trait_id = -1
       end
 
@@ -0,0 +1,121 @@
+//! Benchmarks for looking up attributes with and without inline caches.
+//!
+//! This benchmark simulates a program that sends many messages to instances of
+//! objects, with the methods defined at different depths of the prototype
+//! chain. Run it using `cargo bench --bench inline_caches`.
+use libinko::inline_cache::InlineCache;
+use libinko::object_pointer::ObjectPointer;
+use libinko::object_value;
+use libinko::vm::instructions::object;
+use libinko::vm::state::RcState;
+use libinko::vm::test::setup;
+use std::time::{Duration, Instant};
+
+/// The number of objects in the prototype chain of every receiver.
+const CHAIN_DEPTH: usize = 5;
+
+/// The number of methods defined on every object in the prototype chain.
+const METHODS_PER_OBJECT: usize = 8;
+
+/// The number of receivers to send messages to.
+const RECEIVERS: usize = 16;
+
+/// The number of times to send every message to every receiver.
+const ITERATIONS: usize = 20_000;
+
+fn run(
+    caches: Option<&[InlineCache]>,
+    state: &RcState,
+    receivers: &[ObjectPointer],
+    names: &[ObjectPointer],
+) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut checksum = 0_usize;
+
+    for _ in 0..ITERATIONS {
+        for receiver in receivers {
+            for (index, name) in names.iter().enumerate() {
+                let cache = caches.map(|caches| &caches[index]);
+                let value =
+                    object::get_attribute(state, cache, *receiver, *name);
+
+                // The checksum ensures the lookups can't be optimised away.
+                checksum = checksum.wrapping_add(value.raw.raw as usize);
+            }
+        }
+    }
+
+    (start.elapsed(), checksum)
+}
+
+fn main() {
+    let (machine, _block, process) = setup();
+    let state = &machine.state;
+    let mut prototype = state.object_prototype;
+    let mut names = Vec::new();
+
+    for depth in 0..CHAIN_DEPTH {
+        let object = state
+            .permanent_allocator
+            .lock()
+            .allocate_with_prototype(object_value::none(), prototype);
+
+        for method in 0..METHODS_PER_OBJECT {
+            let name =
+                state.intern_string(format!("method_{}_{}", depth, method));
+
+            object
+                .get_mut()
+                .add_attribute(name, ObjectPointer::integer(method as i64));
+
+            // Only some methods are called, similar to a real program.
+            if method % 2 == 0 {
+                names.push(name);
+            }
+        }
+
+        prototype = object;
+    }
+
+    let receivers: Vec<_> = (0..RECEIVERS)
+        .map(|index| {
+            let receiver = process.allocate(object_value::none(), prototype);
+            let name = state.intern_string("@value".to_string());
+
+            receiver
+                .get_mut()
+                .add_attribute(name, ObjectPointer::integer(index as i64));
+
+            receiver
+        })
+        .collect();
+
+    let caches: Vec<_> = names.iter().map(|_| InlineCache::new()).collect();
+    let lookups = (ITERATIONS * RECEIVERS * names.len()) as f64;
+
+    // Warm up the caches, so we only measure cache hits.
+    run(Some(&caches), state, &receivers, &names);
+
+    let (uncached, uncached_checksum) = run(None, state, &receivers, &names);
+    let (cached, cached_checksum) =
+        run(Some(&caches), state, &receivers, &names);
+
+    assert_eq!(uncached_checksum, cached_checksum);
+
+    println!(
+        "Without inline caches: {:?} ({:.2} ns per lookup)",
+        uncached,
+        uncached.as_nanos() as f64 / lookups
+    );
+
+    println!(
+        "With inline caches:    {:?} ({:.2} ns per lookup)",
+        cached,
+        cached.as_nanos() as f64 / lookups
+    );
+
+    println!(
+        "Speedup:               {:.2}x",
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
@@ -0,0 +1,372 @@
+//! Storing the attributes of objects using shared shapes.
+//!
+//! Instead of every object storing its attributes in a separate hash map,
+//! objects store the values of their attributes in a vector, along with a
+//! shape. The shape contains the names of the attributes, in the order of
+//! their slots (the indexes of the values). Objects that define the same
+//! attributes in the same order share the same shape, meaning the names only
+//! have to be stored once.
+//!
+//! Shapes form a tree of transitions: adding an attribute to an object moves
+//! the object to the shape that has all the attributes of its current shape,
+//! plus the new attribute. These transitions are stored in the shapes, so
+//! objects created the same way end up with the same shape. Shapes are shared
+//! by all processes and are never deallocated.
+//!
+//! Objects with many attributes, or objects that had an attribute removed,
+//! store their attributes in a hash map instead.
+//!
+//! Attribute names must be permanent objects, which in practice are interned
+//! strings. This is necessary as shapes are shared by all processes, and the
+//! garbage collector doesn't trace attribute names.
+use crate::object_pointer::ObjectPointer;
+use ahash::AHashMap;
+use parking_lot::{const_rwlock, RwLock};
+use std::collections::hash_map;
+use std::iter::Zip;
+use std::slice;
+
+/// A hash map used for objects that can't use a shape.
+pub type AttributesMap = AHashMap<ObjectPointer, ObjectPointer>;
+
+/// The maximum number of attributes that can be stored using a shape.
+///
+/// Looking up an attribute requires a linear search through the names of the
+/// shape, and adding an attribute copies these names. For objects with more
+/// attributes than this, a hash map is faster.
+pub const MAX_SHAPE_ATTRIBUTES: usize = 8;
+
+/// The shape of objects without any attributes, and the root of all shape
+/// transitions.
+static EMPTY_SHAPE: Shape = Shape {
+    names: Vec::new(),
+    filter: 0,
+    transitions: const_rwlock(Vec::new()),
+};
+
+/// The names of the attributes of an object, and the slots of their values.
+pub struct Shape {
+    /// The names of the attributes, in the order of their slots.
+    names: Vec<ObjectPointer>,
+
+    /// A bitmap with a bit set for every name, allowing us to determine that
+    /// an attribute is missing without searching through all names.
+    filter: u64,
+
+    /// The shapes to transition to when adding an attribute, sorted by the
+    /// address of the attribute's name.
+    transitions: RwLock<Vec<(usize, &'static Shape)>>,
+}
+
+impl Shape {
+    /// Returns the shape of objects without any attributes.
+    pub fn empty() -> &'static Shape {
+        &EMPTY_SHAPE
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn names(&self) -> &[ObjectPointer] {
+        &self.names
+    }
+
+    /// Returns the slot of the given attribute.
+    #[inline(always)]
+    pub fn slot(&self, name: ObjectPointer) -> Option<usize> {
+        if self.filter & filter_bit(name) == 0 {
+            return None;
+        }
+
+        self.names.iter().position(|current| *current == name)
+    }
+
+    /// Returns the shape to use after adding the given attribute, creating it
+    /// if necessary.
+    pub fn transition(&self, name: ObjectPointer) -> &'static Shape {
+        let key = name.raw.raw as usize;
+
+        {
+            let transitions = self.transitions.read();
+
+            if let Ok(index) = search(&transitions, key) {
+                return transitions[index].1;
+            }
+        }
+
+        let mut transitions = self.transitions.write();
+
+        // Another thread may have added the transition while we were waiting
+        // for the write lock.
+        match search(&transitions, key) {
+            Ok(index) => transitions[index].1,
+            Err(index) => {
+                let mut names = Vec::with_capacity(self.names.len() + 1);
+
+                names.extend_from_slice(&self.names);
+                names.push(name);
+
+                let shape: &'static Shape = Box::leak(Box::new(Shape {
+                    names,
+                    filter: self.filter | filter_bit(name),
+                    transitions: RwLock::new(Vec::new()),
+                }));
+
+                transitions.insert(index, (key, shape));
+                shape
+            }
+        }
+    }
+}
+
+/// Returns the bit to set in a shape's filter for an attribute name.
+#[inline(always)]
+fn filter_bit(name: ObjectPointer) -> u64 {
+    // Objects are aligned to at least 8 bytes, so the lower bits are ignored.
+    // The remaining bits are mixed so objects allocated close to each other
+    // don't end up using the same bit.
+    let hash = ((name.raw.raw as usize as u64) >> 3)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+
+    1 << (hash >> 58)
+}
+
+fn search(
+    transitions: &[(usize, &'static Shape)],
+    key: usize,
+) -> Result<usize, usize> {
+    transitions.binary_search_by_key(&key, |(address, _)| *address)
+}
+
+/// The attributes of a single object.
+pub enum Attributes {
+    /// Attributes stored using a shape, with the values stored in the order of
+    /// their slots.
+    Shaped(&'static Shape, Vec<ObjectPointer>),
+
+    /// Attributes stored in a hash map.
+    Map(Box<AttributesMap>),
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Attributes::Shaped(Shape::empty(), Vec::new())
+    }
+
+    /// Returns the value of an attribute.
+    #[inline(always)]
+    pub fn get(&self, name: ObjectPointer) -> Option<ObjectPointer> {
+        match self {
+            Attributes::Shaped(shape, values) => {
+                shape.slot(name).map(|slot| values[slot])
+            }
+            Attributes::Map(map) => map.get(&name).cloned(),
+        }
+    }
+
+    /// Adds or replaces an attribute.
+    pub fn insert(&mut self, name: ObjectPointer, value: ObjectPointer) {
+        match self {
+            Attributes::Shaped(shape, values) => {
+                if let Some(slot) = shape.slot(name) {
+                    values[slot] = value;
+                    return;
+                }
+
+                if shape.len() < MAX_SHAPE_ATTRIBUTES {
+                    *shape = shape.transition(name);
+                    values.push(value);
+                    return;
+                }
+            }
+            Attributes::Map(map) => {
+                map.insert(name, value);
+                return;
+            }
+        }
+
+        self.convert_to_map().insert(name, value);
+    }
+
+    /// Removes an attribute and returns its value.
+    pub fn remove(&mut self, name: ObjectPointer) -> Option<ObjectPointer> {
+        match self {
+            Attributes::Shaped(shape, _) => {
+                shape.slot(name)?;
+
+                // There's no shape to transition to for a removed attribute, so
+                // we switch to a hash map instead.
+                self.convert_to_map().remove(&name)
+            }
+            Attributes::Map(map) => map.remove(&name),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Attributes::Shaped(_, values) => values.len(),
+            Attributes::Map(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the attributes are stored using a shape.
+    pub fn is_shaped(&self) -> bool {
+        match self {
+            Attributes::Shaped(_, _) => true,
+            Attributes::Map(_) => false,
+        }
+    }
+
+    /// Returns an iterator over the names and values of all attributes.
+    pub fn iter(&self) -> Iter<'_> {
+        match self {
+            Attributes::Shaped(shape, values) => {
+                Iter::Shaped(shape.names().iter().zip(values.iter()))
+            }
+            Attributes::Map(map) => Iter::Map(map.iter()),
+        }
+    }
+
+    /// Stores the attributes in a hash map, returning the map.
+    fn convert_to_map(&mut self) -> &mut AttributesMap {
+        if let Attributes::Shaped(_, _) = self {
+            let map =
+                self.iter().map(|(name, value)| (*name, *value)).collect();
+
+            *self = Attributes::Map(Box::new(map));
+        }
+
+        match self {
+            Attributes::Map(map) => map,
+            Attributes::Shaped(_, _) => unreachable!(),
+        }
+    }
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over the names and values of attributes.
+pub enum Iter<'a> {
+    Shaped(Zip<slice::Iter<'a, ObjectPointer>, slice::Iter<'a, ObjectPointer>>),
+    Map(hash_map::Iter<'a, ObjectPointer, ObjectPointer>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a ObjectPointer, &'a ObjectPointer);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Shaped(iter) => iter.next(),
+            Iter::Map(iter) => iter.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_pointer::RawObjectPointer;
+    use std::mem;
+
+    fn name(address: usize) -> ObjectPointer {
+        // The addresses are multiplied so they don't clash with the names used
+        // by other tests, which share the same shapes.
+        ObjectPointer::new((0x1000_0000 + address * 8) as RawObjectPointer)
+    }
+
+    #[test]
+    fn test_shape_transition() {
+        let shape = Shape::empty().transition(name(1));
+
+        assert_eq!(shape.len(), 1);
+        assert_eq!(shape.slot(name(1)), Some(0));
+        assert_eq!(Shape::empty().len(), 0);
+    }
+
+    #[test]
+    fn test_shape_transition_is_shared() {
+        let shape1 = Shape::empty().transition(name(2)).transition(name(3));
+        let shape2 = Shape::empty().transition(name(2)).transition(name(3));
+        let shape3 = Shape::empty().transition(name(3)).transition(name(2));
+
+        assert!(shape1 as *const Shape == shape2 as *const Shape);
+        assert!(shape1 as *const Shape != shape3 as *const Shape);
+        assert_eq!(shape1.slot(name(3)), Some(1));
+        assert_eq!(shape3.slot(name(3)), Some(0));
+    }
+
+    #[test]
+    fn test_attributes_insert_and_get() {
+        let mut attrs = Attributes::new();
+
+        attrs.insert(name(4), ObjectPointer::integer(1));
+        attrs.insert(name(5), ObjectPointer::integer(2));
+        attrs.insert(name(4), ObjectPointer::integer(3));
+
+        assert!(attrs.is_shaped());
+        assert_eq!(attrs.len(), 2);
+        assert!(attrs.get(name(4)) == Some(ObjectPointer::integer(3)));
+        assert!(attrs.get(name(5)) == Some(ObjectPointer::integer(2)));
+        assert!(attrs.get(name(6)).is_none());
+    }
+
+    #[test]
+    fn test_attributes_insert_too_many_attributes() {
+        let mut attrs = Attributes::new();
+
+        for index in 0..=MAX_SHAPE_ATTRIBUTES {
+            attrs.insert(name(100 + index), ObjectPointer::integer(1));
+        }
+
+        assert_eq!(attrs.is_shaped(), false);
+        assert_eq!(attrs.len(), MAX_SHAPE_ATTRIBUTES + 1);
+        assert!(attrs.get(name(100)).is_some());
+    }
+
+    #[test]
+    fn test_attributes_remove() {
+        let mut attrs = Attributes::new();
+
+        attrs.insert(name(7), ObjectPointer::integer(1));
+        attrs.insert(name(8), ObjectPointer::integer(2));
+
+        assert!(attrs.remove(name(9)).is_none());
+        assert!(attrs.is_shaped());
+        assert!(attrs.remove(name(7)) == Some(ObjectPointer::integer(1)));
+        assert_eq!(attrs.is_shaped(), false);
+        assert!(attrs.get(name(7)).is_none());
+        assert!(attrs.get(name(8)) == Some(ObjectPointer::integer(2)));
+    }
+
+    #[test]
+    fn test_attributes_iter() {
+        let mut attrs = Attributes::new();
+
+        attrs.insert(name(10), ObjectPointer::integer(1));
+        attrs.insert(name(11), ObjectPointer::integer(2));
+
+        let pairs: Vec<_> = attrs.iter().collect();
+
+        assert_eq!(pairs.len(), 2);
+        assert!(*pairs[0].0 == name(10));
+        assert!(*pairs[1].1 == ObjectPointer::integer(2));
+    }
+
+    #[test]
+    fn test_attributes_size() {
+        assert_eq!(mem::size_of::<Attributes>(), 32);
+    }
+}
@@ -8,8 +8,12 @@
 //! same endianness means we don't have to flip bits around on these CPUs.
 use crate::catch_table::{CatchEntry, CatchTable};
 use crate::compiled_code::CompiledCode;
+use crate::inline_cache;
+#[cfg(feature = "jit")]
+use crate::jit::JitState;
 use crate::module::Module;
 use crate::object_pointer::ObjectPointer;
+use crate::optimiser;
 use crate::vm::instruction::{Instruction, Opcode};
 use crate::vm::state::State;
 use crossbeam_channel::bounded;
@@ -325,9 +329,13 @@ fn read_compiled_code(
     let locals = read_u16(stream)?;
     let registers = read_u16(stream)?;
     let captures = read_bool(stream)?;
-    let instructions = read_instructions(stream)?;
+    let mut instructions = read_instructions(stream)?;
     let code_objects = read_code_vector(state, stream, literals)?;
-    let catch_table = read_catch_table(stream)?;
+    let mut catch_table = read_catch_table(stream)?;
+
+    optimiser::optimise(&mut instructions, &mut catch_table);
+
+    let inline_caches = inline_cache::assign(&mut instructions);
 
     Ok(CompiledCode {
         name,
@@ -341,6 +349,9 @@ fn read_compiled_code(
         instructions,
         code_objects,
         catch_table,
+        inline_caches,
+        #[cfg(feature = "jit")]
+        jit: JitState::new(),
     })
 }
 
@@ -388,7 +399,15 @@ fn read_literal(
 
             state.allocate_permanent_bigint(bigint)
         }
-        LITERAL_FLOAT => state.allocate_permanent_float(read_f64(stream)?),
+        LITERAL_FLOAT => {
+            let num = read_f64(stream)?;
+
+            if ObjectPointer::float_too_large(num) {
+                state.allocate_permanent_float(num)
+            } else {
+                ObjectPointer::float(num)
+            }
+        }
         LITERAL_STRING => state.intern_string(read_string(stream)?),
         _ => {
             return Err(format!("The literal type {} is invalid", literal_type))
@@ -51,6 +51,11 @@ impl<T> Chunk<T> {
         self.capacity
     }
 
+    /// Returns a raw pointer to the first value.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
+    }
+
     pub fn reset(&mut self) {
         unsafe {
             if !self.ptr.is_null() {
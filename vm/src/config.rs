@@ -3,8 +3,11 @@
 //! Various virtual machine settings that can be changed by the user, such as
 //! the number of threads to run.
 use crate::immix::block::BLOCK_SIZE;
+use crate::runtime_panic::PanicFormat;
+use crate::sandbox::{AddressAllowlist, Capabilities, PathAllowlist, Sandbox};
 use std::cmp::min;
 use std::env;
+use std::path::PathBuf;
 
 /// Sets a configuration field based on an environment variable.
 macro_rules! set_from_env {
@@ -17,13 +20,50 @@ macro_rules! set_from_env {
     }};
 }
 
+/// Sets a configuration field based on an environment variable, returning an
+/// error if the value is invalid.
+///
+/// This is used for settings where ignoring an invalid value isn't safe, such
+/// as the sandbox settings.
+macro_rules! try_set_from_env {
+    ($config:expr, $field:ident, $key:expr, $value_type:ty) => {{
+        let key = concat!("INKO_", $key);
+
+        match env::var(key) {
+            Ok(raw_value) => {
+                $config.$field =
+                    raw_value.parse::<$value_type>().map_err(|error| {
+                        format!("The value of {} is invalid: {}", key, error)
+                    })?;
+            }
+            Err(env::VarError::NotUnicode(_)) => {
+                return Err(format!(
+                    "The value of {} is not valid Unicode",
+                    key
+                ));
+            }
+            Err(env::VarError::NotPresent) => {}
+        }
+    }};
+}
+
 const DEFAULT_YOUNG_THRESHOLD: u32 = (2 * 1024 * 1024) / (BLOCK_SIZE as u32);
 const DEFAULT_MATURE_THRESHOLD: u32 = (4 * 1024 * 1024) / (BLOCK_SIZE as u32);
 const DEFAULT_GROWTH_FACTOR: f64 = 1.5;
 const DEFAULT_GROWTH_THRESHOLD: f64 = 0.9;
 const DEFAULT_REDUCTIONS: usize = 1000;
+const DEFAULT_DEADLOCK_DETECTION_INTERVAL: u64 = 1000;
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 100_000;
+const DEFAULT_JIT_THRESHOLD: usize = 1000;
+
+#[cfg(unix)]
+const DEFAULT_STACK_DUMP_SIGNAL: i32 = nix::libc::SIGQUIT;
+
+#[cfg(not(unix))]
+const DEFAULT_STACK_DUMP_SIGNAL: i32 = 0;
 
 /// Structure containing the configuration settings for the virtual machine.
+#[derive(Debug)]
 pub struct Config {
     /// The number of primary process threads to run.
     ///
@@ -69,6 +109,10 @@ pub struct Config {
     /// Defaults to 1000.
     pub reductions: usize,
 
+    /// The maximum number of nested calls a process can perform, before
+    /// panicking. Defaults to 100 000.
+    pub max_call_depth: usize,
+
     /// The number of memory blocks that can be allocated before triggering a
     /// young collection.
     pub young_threshold: u32,
@@ -86,6 +130,47 @@ pub struct Config {
 
     /// When enabled, GC timings will be printed to STDERR.
     pub print_gc_timings: bool,
+
+    /// The number of the signal that prints the stack traces of all processes
+    /// to STDERR.
+    ///
+    /// This defaults to SIGQUIT on Unix systems. Setting this to 0 disables
+    /// stack dumps. Processes subscribed to this signal won't receive it.
+    pub stack_dump_signal: i32,
+
+    /// The interval (in milliseconds) for checking if all processes are
+    /// waiting for a message that will never arrive.
+    ///
+    /// Setting this to 0 disables the detection of deadlocks.
+    pub deadlock_detection_interval: u64,
+
+    /// The operations the program isn't allowed to perform.
+    ///
+    /// By default all operations are allowed.
+    pub sandbox: Sandbox,
+
+    /// The directory to write crash reports to.
+    ///
+    /// Crash reports are written when a panic isn't handled, or when the VM
+    /// itself panics. By default no crash reports are written.
+    pub crash_report_directory: Option<PathBuf>,
+
+    /// The format to use for printing panics and stack traces to STDERR.
+    pub panic_format: PanicFormat,
+
+    /// The file to write line coverage to, in the LCOV format.
+    ///
+    /// When set, the VM records every instruction that is executed, and writes
+    /// the coverage to this file once the program finishes. By default no
+    /// coverage is recorded.
+    pub coverage_file: Option<PathBuf>,
+
+    /// The number of times a code object has to be executed before it's
+    /// compiled to machine code.
+    ///
+    /// Setting this to 0 disables the JIT. This setting is ignored if the VM is
+    /// built without the "jit" feature.
+    pub jit_threshold: usize,
 }
 
 impl Config {
@@ -99,20 +184,32 @@ impl Config {
             tracer_threads: cpu_count,
             bytecode_threads: min(4, cpu_count),
             reductions: DEFAULT_REDUCTIONS,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
             young_threshold: DEFAULT_YOUNG_THRESHOLD,
             mature_threshold: DEFAULT_MATURE_THRESHOLD,
             heap_growth_factor: DEFAULT_GROWTH_FACTOR,
             heap_growth_threshold: DEFAULT_GROWTH_THRESHOLD,
             print_gc_timings: false,
+            stack_dump_signal: DEFAULT_STACK_DUMP_SIGNAL,
+            deadlock_detection_interval: DEFAULT_DEADLOCK_DETECTION_INTERVAL,
+            sandbox: Sandbox::new(),
+            crash_report_directory: None,
+            panic_format: PanicFormat::Text,
+            coverage_file: None,
+            jit_threshold: DEFAULT_JIT_THRESHOLD,
         }
     }
 
     /// Populates configuration settings based on environment variables.
+    ///
+    /// Most invalid values are ignored, but an error is returned if any of the
+    /// sandbox settings are invalid. Ignoring these would result in the
+    /// sandbox allowing more than intended.
     #[cfg_attr(
         feature = "cargo-clippy",
         allow(cyclomatic_complexity, cognitive_complexity)
     )]
-    pub fn populate_from_env(&mut self) {
+    pub fn populate_from_env(&mut self) -> Result<(), String> {
         set_from_env!(self, primary_threads, "PRIMARY_THREADS", usize);
         set_from_env!(self, blocking_threads, "BLOCKING_THREADS", usize);
         set_from_env!(self, gc_threads, "GC_THREADS", usize);
@@ -120,6 +217,8 @@ impl Config {
         set_from_env!(self, bytecode_threads, "BYTECODE_THREADS", usize);
 
         set_from_env!(self, reductions, "REDUCTIONS", usize);
+        set_from_env!(self, max_call_depth, "MAX_CALL_DEPTH", usize);
+        set_from_env!(self, jit_threshold, "JIT_THRESHOLD", usize);
 
         set_from_env!(self, young_threshold, "YOUNG_THRESHOLD", u32);
         set_from_env!(self, mature_threshold, "MATURE_THRESHOLD", u32);
@@ -133,6 +232,35 @@ impl Config {
         );
 
         set_from_env!(self, print_gc_timings, "PRINT_GC_TIMINGS", bool);
+        set_from_env!(self, stack_dump_signal, "STACK_DUMP_SIGNAL", i32);
+        set_from_env!(self, panic_format, "PANIC_FORMAT", PanicFormat);
+
+        set_from_env!(
+            self,
+            deadlock_detection_interval,
+            "DEADLOCK_DETECTION_INTERVAL",
+            u64
+        );
+
+        try_set_from_env!(self.sandbox, denied, "SANDBOX", Capabilities);
+        try_set_from_env!(self.sandbox, paths, "SANDBOX_PATHS", PathAllowlist);
+
+        try_set_from_env!(
+            self.sandbox,
+            addresses,
+            "SANDBOX_ADDRESSES",
+            AddressAllowlist
+        );
+
+        if let Some(directory) = env::var_os("INKO_CRASH_REPORT_DIRECTORY") {
+            self.crash_report_directory = Some(PathBuf::from(directory));
+        }
+
+        if let Some(file) = env::var_os("INKO_COVERAGE_FILE") {
+            self.coverage_file = Some(PathBuf::from(file));
+        }
+
+        Ok(())
     }
 }
 
@@ -148,6 +276,8 @@ mod tests {
         assert!(config.primary_threads >= 1);
         assert!(config.gc_threads >= 1);
         assert_eq!(config.reductions, 1000);
+        assert_eq!(config.max_call_depth, 100_000);
+        assert!(config.sandbox.denied.is_empty());
     }
 
     #[test]
@@ -156,13 +286,31 @@ mod tests {
         env::set_var("INKO_HEAP_GROWTH_FACTOR", "4.2");
 
         let mut config = Config::new();
-
-        config.populate_from_env();
+        let result = config.populate_from_env();
 
         // Unset before any assertions may fail.
         env::remove_var("INKO_HEAP_GROWTH_FACTOR");
 
+        assert!(result.is_ok());
         assert_eq!(config.primary_threads, 42);
         assert_eq!(config.heap_growth_factor, 4.2);
+
+        // This is tested here instead of in a separate test, as tests run in
+        // parallel and share the same environment variables.
+        env::set_var("INKO_SANDBOX", "ffi,sokets");
+
+        let result = config.populate_from_env();
+
+        // Unset before any assertions may fail.
+        env::remove_var("INKO_SANDBOX");
+
+        assert_eq!(
+            result,
+            Err(
+                "The value of INKO_SANDBOX is invalid: sokets is not a valid \
+                 capability"
+                    .to_string()
+            )
+        );
     }
 }
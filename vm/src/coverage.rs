@@ -0,0 +1,212 @@
+//! Collecting the lines of code executed by a program.
+//!
+//! When enabled, every instruction executed increments a counter for the
+//! instruction. Once the program finishes, the counters are combined into the
+//! number of hits per line, and written to a file in the LCOV format. This
+//! makes it possible to find out which parts of a program (e.g. a test suite)
+//! are executed.
+//!
+//! Counters are allocated the first time a code object is executed, using its
+//! address as the key. Since code objects are never moved or removed once
+//! parsed, these addresses remain valid for the lifetime of the program.
+use crate::compiled_code::CompiledCode;
+use crate::modules::Modules;
+use ahash::AHashMap;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The number of hits per line, for every source file.
+type Lines = BTreeMap<String, BTreeMap<u16, u64>>;
+
+/// The number of times instructions have been executed.
+pub struct Coverage {
+    /// The hit counters of every executed code object, with one counter per
+    /// instruction.
+    hits: RwLock<AHashMap<usize, Box<[AtomicU64]>>>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Coverage {
+            hits: RwLock::new(AHashMap::default()),
+        }
+    }
+
+    /// Records the execution of the instruction at the given index.
+    #[inline(always)]
+    pub fn hit(&self, code: &CompiledCode, index: usize) {
+        let key = code as *const CompiledCode as usize;
+
+        if let Some(counters) = self.hits.read().get(&key) {
+            counters[index].fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let mut hits = self.hits.write();
+        let counters = hits.entry(key).or_insert_with(|| {
+            code.instructions
+                .iter()
+                .map(|_| AtomicU64::new(0))
+                .collect()
+        });
+
+        counters[index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Writes the coverage of all modules to the given path, in the LCOV
+    /// format.
+    pub fn write(&self, modules: &Modules, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_lcov(modules))
+    }
+
+    /// Returns the coverage of all modules in the LCOV format.
+    pub fn to_lcov(&self, modules: &Modules) -> String {
+        let mut lines = Lines::new();
+
+        for pointer in modules.list() {
+            if let Ok(module) = pointer.module_value() {
+                self.collect_lines(&module.code(), &mut lines);
+            }
+        }
+
+        lcov(&lines)
+    }
+
+    /// Adds the hits per line of a code object and all the code objects
+    /// defined in it.
+    ///
+    /// Lines that are never executed are included with zero hits.
+    fn collect_lines(&self, code: &CompiledCode, lines: &mut Lines) {
+        let file = code.file.string_value().unwrap().to_owned_string();
+        let hits = self.hits.read();
+        let counters = hits.get(&(code as *const CompiledCode as usize));
+        let file_lines = lines.entry(file).or_default();
+
+        for (index, instruction) in code.instructions.iter().enumerate() {
+            // Instructions generated by the compiler without a corresponding
+            // line of source code use line 0.
+            if instruction.line == 0 {
+                continue;
+            }
+
+            let count = counters
+                .map(|counters| counters[index].load(Ordering::Relaxed))
+                .unwrap_or(0);
+
+            *file_lines.entry(instruction.line).or_insert(0) += count;
+        }
+
+        drop(hits);
+
+        for child in &code.code_objects {
+            self.collect_lines(child, lines);
+        }
+    }
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats the hits per line using the LCOV format.
+fn lcov(lines: &Lines) -> String {
+    let mut buffer = String::new();
+
+    buffer.push_str("TN:\n");
+
+    for (file, file_lines) in lines {
+        let hit = file_lines.values().filter(|count| **count > 0).count();
+
+        buffer.push_str(&format!("SF:{}\n", file));
+
+        for (line, count) in file_lines {
+            buffer.push_str(&format!("DA:{},{}\n", line, count));
+        }
+
+        buffer.push_str(&format!("LH:{}\n", hit));
+        buffer.push_str(&format!("LF:{}\n", file_lines.len()));
+        buffer.push_str("end_of_record\n");
+    }
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::vm::instruction::{Instruction, Opcode};
+    use crate::vm::state::State;
+
+    fn compiled_code(state: &State, file: &str, lines: &[u16]) -> CompiledCode {
+        let instructions = lines
+            .iter()
+            .map(|line| Instruction::new(Opcode::Return, [0; 6], *line))
+            .collect();
+
+        CompiledCode::new(
+            state.intern_string("foo".to_string()),
+            state.intern_string(file.to_string()),
+            1,
+            instructions,
+        )
+    }
+
+    #[test]
+    fn test_hit() {
+        let state = State::with_rc(Config::new(), &[]);
+        let code = compiled_code(&state, "a.inko", &[1, 1]);
+        let coverage = Coverage::new();
+
+        coverage.hit(&code, 1);
+        coverage.hit(&code, 1);
+
+        let hits = coverage.hits.read();
+        let counters = hits.get(&(&code as *const CompiledCode as usize));
+
+        assert_eq!(counters.unwrap()[0].load(Ordering::Relaxed), 0);
+        assert_eq!(counters.unwrap()[1].load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_collect_lines() {
+        let state = State::with_rc(Config::new(), &[]);
+        let mut code = compiled_code(&state, "a.inko", &[1, 1, 2, 0]);
+        let coverage = Coverage::new();
+        let mut lines = Lines::new();
+
+        code.code_objects
+            .push(compiled_code(&state, "b.inko", &[3]));
+
+        coverage.hit(&code, 0);
+        coverage.hit(&code, 1);
+        coverage.hit(&code, 3);
+        coverage.collect_lines(&code, &mut lines);
+
+        assert_eq!(lines["a.inko"].len(), 2);
+        assert_eq!(lines["a.inko"][&1], 2);
+        assert_eq!(lines["a.inko"][&2], 0);
+        assert_eq!(lines["b.inko"][&3], 0);
+    }
+
+    #[test]
+    fn test_lcov() {
+        let mut lines = Lines::new();
+        let mut file_lines = BTreeMap::new();
+
+        file_lines.insert(1, 2);
+        file_lines.insert(2, 0);
+        lines.insert("a.inko".to_string(), file_lines);
+
+        assert_eq!(
+            lcov(&lines),
+            "TN:\nSF:a.inko\nDA:1,2\nDA:2,0\nLH:1\nLF:2\nend_of_record\n"
+        );
+    }
+}
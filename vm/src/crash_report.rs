@@ -0,0 +1,254 @@
+//! Writing crash reports to disk.
+//!
+//! When a process panics without the panic being handled, or the VM itself
+//! panics, a crash report can be written to a directory. This makes it possible
+//! to debug crashes of programs whose STDERR output isn't stored, such as
+//! programs running under a supervisor.
+//!
+//! A crash report contains the panic message, the VM configuration, the stack
+//! of the panicking process including the values of its registers and local
+//! variables, the list of loaded modules, and statistics about the heap of the
+//! process.
+//!
+//! Crash reports are only generated by the thread running the panicking
+//! process, meaning we can safely inspect its stack and heap.
+use crate::object_pointer::ObjectPointer;
+use crate::object_value::ObjectValue;
+use crate::process::RcProcess;
+use crate::vm::state::State;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The version of the VM, included in every crash report.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Writes a crash report for the process, if crash reports are enabled.
+pub fn write(state: &State, process: &RcProcess, message: &str) {
+    let directory =
+        if let Some(directory) = state.config.crash_report_directory.as_ref() {
+            directory
+        } else {
+            return;
+        };
+
+    match write_to(directory, state, process, message) {
+        Ok(path) => eprintln!("Crash report written to {}", path.display()),
+        Err(error) => eprintln!("Failed to write a crash report: {}", error),
+    }
+}
+
+fn write_to(
+    directory: &Path,
+    state: &State,
+    process: &RcProcess,
+    message: &str,
+) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let path = directory.join(format!(
+        "inko-crash-{}-{}-{:#x}.txt",
+        timestamp,
+        process::id(),
+        process.identifier()
+    ));
+
+    fs::write(&path, generate(state, process, message))?;
+
+    Ok(path)
+}
+
+/// Generates the contents of a crash report.
+pub fn generate(state: &State, process: &RcProcess, message: &str) -> String {
+    let mut buffer = String::new();
+
+    buffer.push_str(&format!("Inko crash report\n\nVM version: {}\n", VERSION));
+    buffer.push_str(&format!("Process: {:#x}\n", process.identifier()));
+    buffer.push_str(&format!("Panic: {}\n", message));
+
+    buffer.push_str("\n== Configuration ==\n\n");
+    buffer.push_str(&format!("{:#?}\n", state.config));
+
+    buffer.push_str("\n== Stack (the most recent call comes first) ==\n");
+
+    for (index, context) in process.context().contexts().enumerate() {
+        buffer.push_str(&format!(
+            "\n{}: \"{}\" line {}, in \"{}\"\n",
+            index,
+            context.code.file.string_value().unwrap(),
+            context.line(),
+            context.code.name.string_value().unwrap()
+        ));
+
+        buffer.push_str("  Registers:\n");
+
+        let registers = &context.registers.values;
+
+        for register in 0..registers.len() {
+            push_value(&mut buffer, state, "r", register, registers[register]);
+        }
+
+        buffer.push_str("  Locals:\n");
+
+        let locals = context.binding.locals();
+
+        for local in 0..locals.len() {
+            push_value(&mut buffer, state, "l", local, locals[local]);
+        }
+    }
+
+    let local_data = process.local_data();
+
+    buffer.push_str("\n== Mailbox ==\n\n");
+    buffer
+        .push_str(&format!("Messages: {}\n", local_data.mailbox.lock().len()));
+
+    buffer.push_str("\n== Modules ==\n\n");
+
+    for name in state.modules.lock().names() {
+        buffer.push_str(&format!("{}\n", name));
+    }
+
+    let allocator = &local_data.allocator;
+    let young_blocks: usize = allocator
+        .young_generation
+        .iter()
+        .map(|bucket| bucket.blocks.len())
+        .sum();
+
+    buffer.push_str("\n== Garbage collection ==\n\n");
+    buffer.push_str(&format!("Young blocks: {}\n", young_blocks));
+    buffer.push_str(&format!(
+        "Young block allocations: {} of {}\n",
+        allocator.young_config.block_allocations,
+        allocator.young_config.threshold
+    ));
+
+    buffer.push_str(&format!(
+        "Mature blocks: {}\n",
+        allocator.mature_generation.blocks.len()
+    ));
+
+    buffer.push_str(&format!(
+        "Mature block allocations: {} of {}\n",
+        allocator.mature_config.block_allocations,
+        allocator.mature_config.threshold
+    ));
+
+    buffer
+}
+
+/// Adds a register or local variable to the report, if it's set.
+fn push_value(
+    buffer: &mut String,
+    state: &State,
+    prefix: &str,
+    index: usize,
+    pointer: ObjectPointer,
+) {
+    if pointer.is_null() {
+        return;
+    }
+
+    buffer.push_str(&format!(
+        "    {}{}: {}\n",
+        prefix,
+        index,
+        format_value(state, pointer)
+    ));
+}
+
+/// Formats a value for display in a crash report.
+fn format_value(state: &State, pointer: ObjectPointer) -> String {
+    if pointer.is_tagged_integer() {
+        return format!("Integer {}", pointer.integer_value().unwrap());
+    }
+
+    if pointer.is_tagged_float() {
+        return format!("Float {}", pointer.float_value().unwrap());
+    }
+
+    if pointer == state.nil_object {
+        return "Nil".to_string();
+    }
+
+    if pointer == state.true_object {
+        return "True".to_string();
+    }
+
+    if pointer == state.false_object {
+        return "False".to_string();
+    }
+
+    let value = &pointer.get().value;
+    let name = value.name();
+
+    match value {
+        ObjectValue::Float(number) => format!("{} {}", name, number),
+        ObjectValue::Integer(number) => format!("{} {}", name, number),
+        ObjectValue::BigInt(number) => format!("{} {}", name, number),
+        ObjectValue::Decimal(number) => format!("{} {}", name, number),
+        ObjectValue::String(string) | ObjectValue::InternedString(string) => {
+            format!("{} {:?}", name, string.as_slice())
+        }
+        ObjectValue::Array(array) => {
+            format!("{} with {} values", name, array.len())
+        }
+        ObjectValue::ByteArray(bytes) => {
+            format!("{} with {} bytes", name, bytes.len())
+        }
+        ObjectValue::Process(process) => {
+            format!("{} {:#x}", name, process.identifier())
+        }
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_value;
+    use crate::vm::test::setup;
+    use std::env;
+
+    #[test]
+    fn test_generate() {
+        let (machine, _block, process) = setup();
+        let string = process.allocate(
+            object_value::string("hello".to_string()),
+            machine.state.string_prototype,
+        );
+
+        process
+            .context_mut()
+            .set_register(0, ObjectPointer::integer(42));
+        process.context_mut().set_register(1, string);
+        process.context_mut().set_local(0, machine.state.nil_object);
+
+        let report = generate(&machine.state, &process, "oops");
+
+        assert!(report.contains("Panic: oops"));
+        assert!(report.contains("r0: Integer 42"));
+        assert!(report.contains("r1: String \"hello\""));
+        assert!(report.contains("l0: Nil"));
+        assert!(report.contains("Messages: 0"));
+    }
+
+    #[test]
+    fn test_write_to() {
+        let (machine, _block, process) = setup();
+        let path = write_to(&env::temp_dir(), &machine.state, &process, "oops")
+            .unwrap();
+
+        let report = fs::read_to_string(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        assert!(report.starts_with("Inko crash report"));
+    }
+}
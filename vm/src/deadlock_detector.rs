@@ -0,0 +1,200 @@
+//! Detecting programs that are waiting for messages that never arrive.
+//!
+//! A program is deadlocked when all its processes are waiting for a message
+//! without a timeout, and nothing else can wake them up: no timers have yet to
+//! expire, no processes are subscribed to signals, and no processes are
+//! running or waiting for a socket. Without detecting this, the VM would wait
+//! forever using idle threads.
+//!
+//! The detector checks for this state periodically. A check is only performed
+//! when no processes were rescheduled since the previous check. To prevent
+//! processes waking up during a check from producing false positives, the
+//! number of wake-ups must also remain the same while inspecting all processes.
+//!
+//! When a deadlock is detected, the stack traces of all processes are printed
+//! to STDERR, and the VM terminates using `EXIT_STATUS`.
+use crate::process::{Process, RcProcess};
+use crate::process_status::ProcessState;
+use crate::runtime_panic;
+use crate::vm::state::RcState;
+use parking_lot::{Condvar, Mutex};
+use std::time::Duration;
+
+/// The exit status to use when a deadlock is detected.
+pub const EXIT_STATUS: i32 = 3;
+
+/// A type used for waiting between deadlock checks.
+pub struct DeadlockDetector {
+    /// A boolean indicating if the detector should keep running.
+    alive: Mutex<bool>,
+
+    /// The condition variable used for waking up the detector when it should
+    /// terminate.
+    cvar: Condvar,
+}
+
+impl DeadlockDetector {
+    pub fn new() -> Self {
+        DeadlockDetector {
+            alive: Mutex::new(true),
+            cvar: Condvar::new(),
+        }
+    }
+
+    pub fn terminate(&self) {
+        *self.alive.lock() = false;
+        self.cvar.notify_all();
+    }
+
+    /// Waits for the given duration, returning `false` if the detector should
+    /// terminate.
+    fn wait(&self, duration: Duration) -> bool {
+        let mut alive = self.alive.lock();
+
+        if *alive {
+            self.cvar.wait_for(&mut alive, duration);
+        }
+
+        *alive
+    }
+}
+
+impl Default for DeadlockDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A thread that periodically checks if the program is deadlocked.
+pub struct Worker {
+    state: RcState,
+}
+
+impl Worker {
+    pub fn new(state: RcState) -> Self {
+        Worker { state }
+    }
+
+    pub fn run(&self) {
+        let interval = self.state.config.deadlock_detection_interval;
+
+        if interval == 0 {
+            return;
+        }
+
+        let duration = Duration::from_millis(interval);
+        let mut wake_ups = Process::wake_ups();
+
+        while self.state.deadlock_detector.wait(duration) {
+            let current = Process::wake_ups();
+
+            if current != wake_ups {
+                wake_ups = current;
+                continue;
+            }
+
+            if let Some(processes) = self.deadlocked_processes() {
+                // If any process was rescheduled while we were inspecting the
+                // processes, our view of the processes may be out of date.
+                if Process::wake_ups() == wake_ups {
+                    self.report(&processes);
+                    self.state.terminate(EXIT_STATUS);
+
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns all processes if the program is deadlocked.
+    fn deadlocked_processes(&self) -> Option<Vec<RcProcess>> {
+        if self.state.timeout_worker.has_timers()
+            || self.state.signals.has_subscribers()
+        {
+            return None;
+        }
+
+        let processes = self.state.processes.processes();
+        let waiting = processes.iter().all(|process| {
+            process.is_waiting_for_message_without_timeout()
+                && !process.has_messages()
+        });
+
+        if processes.is_empty() || !waiting {
+            None
+        } else {
+            Some(processes)
+        }
+    }
+
+    fn report(&self, processes: &[RcProcess]) {
+        eprintln!(
+            "Deadlock detected: all {} processes are waiting for a message \
+             that will never arrive",
+            processes.len()
+        );
+
+        for process in processes {
+            // A process may still be finishing its last run, in which case we
+            // can't safely inspect its call stack.
+            if let Some(_running) = process.try_lock_running() {
+                runtime_panic::display_stack(
+                    process,
+                    ProcessState::WaitingForMessage,
+                    self.state.config.panic_format,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_pointer::ObjectPointer;
+    use crate::vm::test::setup;
+
+    #[test]
+    fn test_terminate() {
+        let detector = DeadlockDetector::new();
+
+        assert!(detector.wait(Duration::from_millis(1)));
+
+        detector.terminate();
+
+        assert_eq!(detector.wait(Duration::from_secs(1)), false);
+    }
+
+    #[test]
+    fn test_deadlocked_processes_with_running_process() {
+        let (machine, _block, _process) = setup();
+        let worker = Worker::new(machine.state.clone());
+
+        assert!(worker.deadlocked_processes().is_none());
+    }
+
+    #[test]
+    fn test_deadlocked_processes_with_waiting_process() {
+        let (machine, _block, process) = setup();
+        let worker = Worker::new(machine.state.clone());
+
+        process.waiting_for_message();
+        process.suspend_without_timeout();
+
+        let processes = worker.deadlocked_processes();
+
+        assert_eq!(processes.map(|p| p.len()), Some(1));
+    }
+
+    #[test]
+    fn test_deadlocked_processes_with_pending_message() {
+        let (machine, _block, process) = setup();
+        let worker = Worker::new(machine.state.clone());
+
+        process.waiting_for_message();
+        process.suspend_without_timeout();
+        process.send_message_from_self(ObjectPointer::integer(5));
+
+        assert!(worker.deadlocked_processes().is_none());
+    }
+}
@@ -0,0 +1,476 @@
+//! Arbitrary precision decimal numbers.
+//!
+//! A Decimal is stored as an arbitrary precision integer and a scale, with the
+//! value being `value * 10^-scale`. Addition, subtraction, and multiplication
+//! are exact. Division and rescaling require an explicit scale and rounding
+//! mode, as their results may not be representable exactly.
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// The maximum scale of a Decimal.
+///
+/// This prevents accidentally allocating huge integers, for example when
+/// passing the wrong argument as the scale, or when repeatedly multiplying
+/// Decimals.
+pub const MAX_SCALE: u32 = 4096;
+
+/// The ways in which a Decimal can be rounded when reducing its scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Rounds towards zero.
+    Down,
+
+    /// Rounds away from zero.
+    Up,
+
+    /// Rounds towards negative infinity.
+    Floor,
+
+    /// Rounds towards positive infinity.
+    Ceiling,
+
+    /// Rounds to the nearest neighbour, rounding ties away from zero.
+    HalfUp,
+
+    /// Rounds to the nearest neighbour, rounding ties towards zero.
+    HalfDown,
+
+    /// Rounds to the nearest neighbour, rounding ties to the even neighbour.
+    HalfEven,
+}
+
+impl RoundingMode {
+    pub fn from_integer(value: i64) -> Result<Self, String> {
+        let mode = match value {
+            0 => RoundingMode::Down,
+            1 => RoundingMode::Up,
+            2 => RoundingMode::Floor,
+            3 => RoundingMode::Ceiling,
+            4 => RoundingMode::HalfUp,
+            5 => RoundingMode::HalfDown,
+            6 => RoundingMode::HalfEven,
+            _ => return Err(format!("{} is not a valid rounding mode", value)),
+        };
+
+        Ok(mode)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Decimal {
+    /// The unscaled value of this Decimal.
+    value: BigInt,
+
+    /// The number of digits after the decimal point.
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(value: BigInt, scale: u32) -> Self {
+        Decimal { value, scale }
+    }
+
+    /// Parses a Decimal such as "10", "-1.25", or "+0.50".
+    ///
+    /// The scale of the Decimal equals the number of digits after the decimal
+    /// point, which can't be greater than `MAX_SCALE`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let error = || format!("{:?} is not a valid Decimal", input);
+        let (negative, digits) = match input.as_bytes().first() {
+            Some(b'-') => (true, &input[1..]),
+            Some(b'+') => (false, &input[1..]),
+            _ => (false, input),
+        };
+
+        let (integral, fraction) = match digits.find('.') {
+            Some(index) => (&digits[..index], &digits[index + 1..]),
+            None => (digits, ""),
+        };
+
+        let valid = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+
+        if integral.is_empty()
+            || (digits.contains('.') && fraction.is_empty())
+            || !valid(integral)
+            || !valid(fraction)
+        {
+            return Err(error());
+        }
+
+        let scale = u32::try_from(fraction.len()).map_err(|_| error())?;
+
+        check_scale(scale)?;
+
+        let unscaled = format!("{}{}", integral, fraction);
+        let mut value = unscaled.parse::<BigInt>().map_err(|_| error())?;
+
+        if negative {
+            value = -value;
+        }
+
+        Ok(Decimal::new(value, scale))
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    pub fn add(&self, other: &Decimal) -> Decimal {
+        let (left, right, scale) = self.aligned(other);
+
+        Decimal::new(left + right, scale)
+    }
+
+    pub fn sub(&self, other: &Decimal) -> Decimal {
+        let (left, right, scale) = self.aligned(other);
+
+        Decimal::new(left - right, scale)
+    }
+
+    pub fn mul(&self, other: &Decimal) -> Result<Decimal, String> {
+        let scale = add_scales(self.scale, other.scale)?;
+
+        check_scale(scale)?;
+
+        Ok(Decimal::new(&self.value * &other.value, scale))
+    }
+
+    /// Divides `self` by `other`, producing a Decimal with the given scale.
+    pub fn div(
+        &self,
+        other: &Decimal,
+        scale: u32,
+        mode: RoundingMode,
+    ) -> Result<Decimal, String> {
+        if other.is_zero() {
+            return Err("Can not divide a Decimal by 0".to_string());
+        }
+
+        check_scale(scale)?;
+
+        // (a / 10^as) / (b / 10^bs) * 10^scale, rewritten so that only the
+        // final division can produce a remainder.
+        let numerator =
+            &self.value * power_of_ten(add_scales(other.scale, scale)?);
+        let denominator = &other.value * power_of_ten(self.scale);
+
+        Ok(Decimal::new(divide(numerator, denominator, mode), scale))
+    }
+
+    /// Returns a Decimal with the given scale, rounding the value if the scale
+    /// is reduced.
+    pub fn round(
+        &self,
+        scale: u32,
+        mode: RoundingMode,
+    ) -> Result<Decimal, String> {
+        check_scale(scale)?;
+
+        let value = if scale >= self.scale {
+            &self.value * power_of_ten(scale - self.scale)
+        } else {
+            divide(self.value.clone(), power_of_ten(self.scale - scale), mode)
+        };
+
+        Ok(Decimal::new(value, scale))
+    }
+
+    /// Returns the unscaled values of `self` and `other`, using the largest
+    /// scale of the two.
+    fn aligned(&self, other: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        let left = &self.value * power_of_ten(scale - self.scale);
+        let right = &other.value * power_of_ten(scale - other.scale);
+
+        (left, right, scale)
+    }
+
+    /// Returns the unscaled value and scale without any trailing zeroes in the
+    /// fractional part.
+    fn normalized(&self) -> (BigInt, u32) {
+        let ten = BigInt::from(10);
+        let mut value = self.value.clone();
+        let mut scale = self.scale;
+
+        while scale > 0 && !value.is_zero() && (&value % &ten).is_zero() {
+            value /= &ten;
+            scale -= 1;
+        }
+
+        if value.is_zero() {
+            scale = 0;
+        }
+
+        (value, scale)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        let (left, right, _) = self.aligned(other);
+
+        left.cmp(&right)
+    }
+}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Decimals that only differ in their scale are equal, and thus must
+        // produce the same hash.
+        self.normalized().hash(state);
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = self.scale as usize;
+        let mut digits = self.value.abs().to_string();
+
+        if digits.len() <= scale {
+            digits.insert_str(0, &"0".repeat(scale - digits.len() + 1));
+        }
+
+        if self.value.sign() == Sign::Minus {
+            f.write_str("-")?;
+        }
+
+        if scale == 0 {
+            f.write_str(&digits)
+        } else {
+            let (integral, fraction) = digits.split_at(digits.len() - scale);
+
+            write!(f, "{}.{}", integral, fraction)
+        }
+    }
+}
+
+fn check_scale(scale: u32) -> Result<(), String> {
+    if scale > MAX_SCALE {
+        Err(format!(
+            "The scale {} is greater than the maximum of {}",
+            scale, MAX_SCALE
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn add_scales(left: u32, right: u32) -> Result<u32, String> {
+    left.checked_add(right)
+        .ok_or_else(|| format!("The scale {} + {} is too great", left, right))
+}
+
+fn power_of_ten(exponent: u32) -> BigInt {
+    num_traits::pow(BigInt::from(10), exponent as usize)
+}
+
+/// Divides two integers, rounding the quotient according to a rounding mode.
+fn divide(
+    mut numerator: BigInt,
+    mut denominator: BigInt,
+    mode: RoundingMode,
+) -> BigInt {
+    if denominator.is_negative() {
+        numerator = -numerator;
+        denominator = -denominator;
+    }
+
+    let (quotient, remainder) = numerator.div_rem(&denominator);
+
+    if remainder.is_zero() {
+        return quotient;
+    }
+
+    // The remainder has the same sign as the numerator, which is also the sign
+    // of the exact quotient.
+    let positive = remainder.is_positive();
+    let half = (remainder.abs() * 2u32).cmp(&denominator);
+    let away_from_zero = match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::Floor => !positive,
+        RoundingMode::Ceiling => positive,
+        RoundingMode::HalfUp => half != Ordering::Less,
+        RoundingMode::HalfDown => half == Ordering::Greater,
+        RoundingMode::HalfEven => {
+            half == Ordering::Greater
+                || (half == Ordering::Equal && quotient.is_odd())
+        }
+    };
+
+    if !away_from_zero {
+        quotient
+    } else if positive {
+        quotient + BigInt::one()
+    } else {
+        quotient - BigInt::one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn decimal(input: &str) -> Decimal {
+        Decimal::parse(input).unwrap()
+    }
+
+    fn rounded(input: &str, scale: u32, mode: RoundingMode) -> String {
+        decimal(input).round(scale, mode).unwrap().to_string()
+    }
+
+    fn hash(value: &Decimal) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_rounding_mode_from_integer() {
+        assert_eq!(RoundingMode::from_integer(0), Ok(RoundingMode::Down));
+        assert_eq!(RoundingMode::from_integer(6), Ok(RoundingMode::HalfEven));
+        assert!(RoundingMode::from_integer(7).is_err());
+        assert!(RoundingMode::from_integer(-1).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(decimal("10").to_string(), "10");
+        assert_eq!(decimal("1.50").to_string(), "1.50");
+        assert_eq!(decimal("-0.05").to_string(), "-0.05");
+        assert_eq!(decimal("+2.5").to_string(), "2.5");
+        assert_eq!(decimal("1.50").scale(), 2);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for input in &["", "-", ".5", "1.", "1.2.3", "1e5", "a", " 1", "--1"] {
+            assert!(Decimal::parse(input).is_err(), "{:?} parsed", input);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(decimal("0.1").add(&decimal("0.2")).to_string(), "0.3");
+        assert_eq!(decimal("1.5").add(&decimal("-2.25")).to_string(), "-0.75");
+        assert_eq!(decimal("1").sub(&decimal("0.01")).to_string(), "0.99");
+        assert_eq!(
+            decimal("1.5").mul(&decimal("-0.25")).unwrap().to_string(),
+            "-0.375"
+        );
+    }
+
+    #[test]
+    fn test_parse_with_too_great_scale() {
+        let valid = format!("0.{}", "1".repeat(MAX_SCALE as usize));
+        let invalid = format!("0.{}", "1".repeat(MAX_SCALE as usize + 1));
+
+        assert_eq!(decimal(&valid).scale(), MAX_SCALE);
+        assert!(Decimal::parse(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_mul_with_too_great_scale() {
+        let left = Decimal::new(BigInt::from(1), MAX_SCALE);
+        let right = Decimal::new(BigInt::from(1), 1);
+
+        assert!(left.mul(&right).is_err());
+        assert!(left.mul(&decimal("2")).is_ok());
+        assert!(Decimal::new(BigInt::from(1), u32::MAX).mul(&right).is_err());
+    }
+
+    #[test]
+    fn test_div() {
+        let div = |a: &str, b: &str, scale, mode| {
+            decimal(a)
+                .div(&decimal(b), scale, mode)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(div("10", "3", 2, RoundingMode::HalfUp), "3.33");
+        assert_eq!(div("20", "3", 2, RoundingMode::HalfUp), "6.67");
+        assert_eq!(div("20", "3", 2, RoundingMode::Down), "6.66");
+        assert_eq!(div("-20", "3", 2, RoundingMode::Floor), "-6.67");
+        assert_eq!(div("1.5", "-0.5", 0, RoundingMode::Down), "-3");
+        assert_eq!(div("1", "8", 5, RoundingMode::Down), "0.12500");
+    }
+
+    #[test]
+    fn test_div_invalid() {
+        let one = decimal("1");
+
+        assert!(one.div(&decimal("0.00"), 2, RoundingMode::Up).is_err());
+        assert!(one.div(&one, MAX_SCALE + 1, RoundingMode::Up).is_err());
+        assert!(one
+            .div(
+                &Decimal::new(BigInt::from(1), u32::MAX),
+                1,
+                RoundingMode::Up
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_round() {
+        let cases = vec![
+            (RoundingMode::Down, ["2", "-2", "2", "2"]),
+            (RoundingMode::Up, ["3", "-3", "3", "3"]),
+            (RoundingMode::Floor, ["2", "-3", "2", "2"]),
+            (RoundingMode::Ceiling, ["3", "-2", "3", "3"]),
+            (RoundingMode::HalfUp, ["3", "-3", "3", "2"]),
+            (RoundingMode::HalfDown, ["2", "-2", "3", "2"]),
+            (RoundingMode::HalfEven, ["2", "-2", "3", "2"]),
+        ];
+
+        for (mode, expected) in cases {
+            assert_eq!(rounded("2.5", 0, mode), expected[0], "{:?}", mode);
+            assert_eq!(rounded("-2.5", 0, mode), expected[1], "{:?}", mode);
+            assert_eq!(rounded("2.51", 0, mode), expected[2], "{:?}", mode);
+            assert_eq!(rounded("2.49", 0, mode), expected[3], "{:?}", mode);
+        }
+
+        assert_eq!(rounded("3.5", 0, RoundingMode::HalfEven), "4");
+        assert_eq!(rounded("2.00", 0, RoundingMode::Up), "2");
+        assert_eq!(rounded("1.5", 3, RoundingMode::Down), "1.500");
+    }
+
+    #[test]
+    fn test_comparison() {
+        assert_eq!(decimal("1.50"), decimal("1.5"));
+        assert!(decimal("1.05") < decimal("1.5"));
+        assert!(decimal("-1") < decimal("0.01"));
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(&decimal("1.50")), hash(&decimal("1.5")));
+        assert_eq!(hash(&decimal("0.00")), hash(&decimal("0")));
+        assert_ne!(hash(&decimal("1.5")), hash(&decimal("15")));
+    }
+}
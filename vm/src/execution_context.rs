@@ -268,6 +268,18 @@ impl ExecutionContext {
     }
 }
 
+impl Drop for ExecutionContext {
+    fn drop(&mut self) {
+        // Dropping the parents recursively could overflow the stack of the
+        // dropping thread for deep call stacks, so we unlink them in a loop.
+        let mut next = self.parent.take();
+
+        while let Some(mut context) = next {
+            next = context.parent.take();
+        }
+    }
+}
+
 impl<'a> Iterator for ExecutionContextIterator<'a> {
     type Item = &'a ExecutionContext;
 
@@ -290,8 +302,10 @@ impl<'a> Iterator for ExecutionContextIterator<'a> {
 mod tests {
     use super::*;
     use crate::object_pointer::{ObjectPointer, RawObjectPointer};
+    use crate::config::DEFAULT_MAX_CALL_DEPTH;
     use crate::vm::test::*;
     use std::mem;
+    use std::thread;
 
     #[test]
     fn test_set_parent() {
@@ -304,6 +318,28 @@ mod tests {
         assert!(context2.parent.is_some());
     }
 
+    #[test]
+    fn test_drop_deep_call_stack() {
+        let (_machine, block, _) = setup();
+        let mut context = Box::new(ExecutionContext::from_block(&block));
+
+        for _ in 0..DEFAULT_MAX_CALL_DEPTH {
+            let mut child = Box::new(ExecutionContext::from_block(&block));
+
+            child.set_parent(context);
+            context = child;
+        }
+
+        // Worker threads use the default stack size of 2 MiB, so we drop the
+        // contexts using a thread with the same stack size.
+        let handle = thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(move || drop(context))
+            .unwrap();
+
+        assert!(handle.join().is_ok());
+    }
+
     #[test]
     fn test_parent_without_parent() {
         let (_machine, block, _) = setup();
@@ -324,6 +324,10 @@ impl Argument {
                     // Integers are handled differently since they can either be
                     // heap allocated, or use tagged pointers.
                     Argument::I64(ptr.integer_value().unwrap())
+                } else if ptr.is_tagged_float() {
+                    // Tagged floats don't point to any memory, so we must not
+                    // dereference them.
+                    Argument::F64(ptr.float_value()?)
                 } else {
                     let obj = ptr.get();
 
@@ -927,4 +931,18 @@ mod tests_for_all_platforms {
 
         assert!(ptr.is_err());
     }
+
+    #[test]
+    fn test_argument_wrap_pointer_with_tagged_float() {
+        for value in &[0.0, 1.5] {
+            let arg = unsafe {
+                Argument::wrap(ffi_type!(pointer), ObjectPointer::float(*value))
+            };
+
+            match arg {
+                Ok(Argument::F64(float)) => assert_eq!(float, *value),
+                _ => panic!("Expected a float argument"),
+            }
+        }
+    }
 }
@@ -49,6 +49,13 @@ impl File {
         })
     }
 
+    pub fn new(file: fs::File, path: ObjectPointer) -> File {
+        File {
+            inner: ClosableFile::new(file),
+            path,
+        }
+    }
+
     pub fn path(&self) -> &ObjectPointer {
         &self.path
     }
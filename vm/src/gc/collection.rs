@@ -3,6 +3,7 @@ use crate::gc::statistics::{CollectionStatistics, TraceStatistics};
 use crate::gc::tracer::Pool;
 use crate::mailbox::Mailbox;
 use crate::process::RcProcess;
+use crate::scheduler::priority::{Prioritize, Priority};
 use crate::vm::state::State;
 use std::time::Instant;
 
@@ -29,6 +30,10 @@ impl Collection {
         vm_state: &State,
         tracers: &Pool,
     ) -> CollectionStatistics {
+        // The process may still be finishing its last run when the collection
+        // starts, so we wait for it to finish.
+        let running = self.process.lock_running();
+
         // We must lock the mailbox before performing any work, as otherwise new
         // objects may be allocated during garbage collection.
         let local_data = self.process.local_data_mut();
@@ -44,6 +49,7 @@ impl Collection {
         // immediately start receiving messages again, and so it can send itself
         // messages.
         drop(mailbox);
+        drop(running);
 
         vm_state.scheduler.schedule(self.process.clone());
 
@@ -99,6 +105,12 @@ impl Collection {
     }
 }
 
+impl Prioritize for Collection {
+    fn priority(&self) -> Priority {
+        self.process.priority()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
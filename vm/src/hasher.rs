@@ -1,4 +1,5 @@
 //! Types and methods for hashing objects.
+use crate::decimal::Decimal;
 use ahash::AHasher;
 use num_bigint::BigInt;
 use std::hash::{Hash, Hasher as HasherTrait};
@@ -38,6 +39,10 @@ impl Hasher {
         value.hash(&mut self.hasher);
     }
 
+    pub fn write_decimal(&mut self, value: &Decimal) {
+        value.hash(&mut self.hasher);
+    }
+
     pub fn write_string(&mut self, value: &str) {
         value.hash(&mut self.hasher);
     }
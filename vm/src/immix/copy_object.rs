@@ -3,8 +3,9 @@
 //! The CopyObject trait can be implemented by allocators to support copying of
 //! objects into a heap.
 
+use crate::attributes::{Attributes, AttributesMap};
 use crate::block::Block;
-use crate::object::{AttributesMap, Object};
+use crate::object::Object;
 use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::object_value::ObjectValue;
@@ -31,6 +32,9 @@ pub trait CopyObject: Sized {
             ObjectValue::BigInt(ref bigint) => {
                 ObjectValue::BigInt(bigint.clone())
             }
+            ObjectValue::Decimal(ref decimal) => {
+                ObjectValue::Decimal(decimal.clone())
+            }
             ObjectValue::String(ref string) => {
                 ObjectValue::String(string.clone())
             }
@@ -85,6 +89,7 @@ pub trait CopyObject: Sized {
             ObjectValue::Module(ref module) => {
                 ObjectValue::Module(module.clone())
             }
+            ObjectValue::Regex(ref regex) => ObjectValue::Regex(regex.clone()),
         };
 
         let mut copy = if let Some(proto_ptr) = to_copy.prototype() {
@@ -95,17 +100,29 @@ pub trait CopyObject: Sized {
             Object::new(value_copy)
         };
 
-        if let Some(map) = to_copy.attributes_map() {
-            let mut map_copy = AttributesMap::default();
-
-            for (key, val) in map.iter() {
-                let key_copy = self.copy_object(*key);
-                let val_copy = self.copy_object(*val);
-
-                map_copy.insert(key_copy, val_copy);
-            }
-
-            copy.set_attributes_map(map_copy);
+        if let Some(attributes) = to_copy.attributes_map() {
+            let attributes_copy = match attributes {
+                // Attribute names are permanent, so the copy can share the
+                // shape of the original object.
+                Attributes::Shaped(shape, values) => Attributes::Shaped(
+                    shape,
+                    values.iter().map(|val| self.copy_object(*val)).collect(),
+                ),
+                Attributes::Map(map) => {
+                    let mut map_copy = AttributesMap::default();
+
+                    for (key, val) in map.iter() {
+                        let key_copy = self.copy_object(*key);
+                        let val_copy = self.copy_object(*val);
+
+                        map_copy.insert(key_copy, val_copy);
+                    }
+
+                    Attributes::Map(Box::new(map_copy))
+                }
+            };
+
+            copy.set_attributes_map(attributes_copy);
         }
 
         self.allocate_copy(copy)
@@ -118,6 +135,7 @@ mod tests {
     use crate::binding::Binding;
     use crate::compiled_code::CompiledCode;
     use crate::config::Config;
+    use crate::decimal::Decimal;
     use crate::immix::global_allocator::GlobalAllocator;
     use crate::immix::local_allocator::LocalAllocator;
     use crate::module::Module;
@@ -125,6 +143,7 @@ mod tests {
     use crate::object_pointer::ObjectPointer;
     use crate::object_value;
     use crate::vm::state::{RcState, State};
+    use regex::Regex;
 
     struct DummyAllocator {
         pub allocator: LocalAllocator,
@@ -187,6 +206,29 @@ mod tests {
         assert!(copy.get().attributes_map().is_some());
     }
 
+    #[test]
+    fn test_copy_with_shaped_attributes() {
+        let mut dummy = DummyAllocator::new();
+        let ptr1 = dummy.allocator.allocate_empty();
+        let ptr2 = dummy.allocator.allocate_empty();
+        let name = dummy.allocator.allocate_empty();
+
+        ptr1.get_mut().add_attribute(name, ptr2);
+
+        let copy = dummy.copy_object(ptr1);
+        let shape_of = |pointer: ObjectPointer| match pointer
+            .get()
+            .attributes_map()
+            .unwrap()
+        {
+            Attributes::Shaped(shape, _) => *shape as *const _,
+            Attributes::Map(_) => panic!("the attributes must be shaped"),
+        };
+
+        assert!(shape_of(ptr1) == shape_of(copy));
+        assert!(copy.get().lookup_attribute_in_self(name).is_some());
+    }
+
     #[test]
     fn test_copy_integer() {
         let mut dummy = DummyAllocator::new();
@@ -200,6 +242,34 @@ mod tests {
         assert_eq!(copy.integer_value().unwrap(), 5);
     }
 
+    #[test]
+    fn test_copy_decimal() {
+        let mut dummy = DummyAllocator::new();
+        let decimal = Decimal::parse("-1.50").unwrap();
+        let pointer = dummy
+            .allocator
+            .allocate_without_prototype(object_value::decimal(decimal));
+
+        let copy = dummy.copy_object(pointer);
+
+        assert!(copy.get().value.is_decimal());
+        assert_eq!(copy.decimal_value().unwrap().to_string(), "-1.50");
+    }
+
+    #[test]
+    fn test_copy_regex() {
+        let mut dummy = DummyAllocator::new();
+        let regex = Regex::new("a+").unwrap();
+        let pointer = dummy
+            .allocator
+            .allocate_without_prototype(object_value::regex(regex));
+
+        let copy = dummy.copy_object(pointer);
+
+        assert!(copy.get().value.is_regex());
+        assert_eq!(copy.regex_value().unwrap().as_str(), "a+");
+    }
+
     #[test]
     fn test_copy_float() {
         let mut dummy = DummyAllocator::new();
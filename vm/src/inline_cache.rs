@@ -0,0 +1,265 @@
+//! Inline caches for looking up attributes.
+//!
+//! Looking up an attribute requires walking the prototype chain of the
+//! receiver, performing a hash map lookup for every object in the chain. Since
+//! most call sites always send messages to instances of the same object, we
+//! cache the result of the lookup for every `GetAttribute` instruction. These
+//! caches are keyed on the prototype of the receiver and the attribute name,
+//! allowing repeated lookups to skip walking the prototype chain.
+//!
+//! Attributes defined on the receiver itself take precedence over those of its
+//! prototypes, so the receiver is always checked first. Caches are only filled
+//! when every object in the prototype chain (up to and including the object
+//! defining the attribute) is permanent. Permanent objects are never moved or
+//! garbage collected, meaning cached pointers remain valid.
+//!
+//! Adding an attribute to a permanent object increments a global epoch, and
+//! caches filled before this are ignored. Attributes are rarely added to
+//! permanent objects after a program has started, so this doesn't happen often.
+//!
+//! Caches can be accessed by multiple threads at once. To keep lookups cheap,
+//! each cache is a small sequence lock: writers that lose a race simply don't
+//! fill the cache, and readers that observe a write in progress treat it as a
+//! cache miss.
+use crate::object_pointer::{ObjectPointer, RawObjectPointer};
+use crate::vm::instruction::{Instruction, Opcode};
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+
+/// The index of the instruction argument that stores the cache index.
+pub const CACHE_ARGUMENT: usize = 3;
+
+/// The cache index to use for instructions without a cache.
+pub const NO_CACHE: u16 = u16::MAX;
+
+/// A counter that is incremented whenever cached lookups may be invalid.
+pub struct Epoch {
+    value: AtomicUsize,
+}
+
+impl Epoch {
+    pub fn new() -> Self {
+        Epoch {
+            value: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline(always)]
+    pub fn current(&self) -> usize {
+        self.value.load(Ordering::Acquire)
+    }
+
+    /// Invalidates all inline caches.
+    pub fn increment(&self) {
+        self.value.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+impl Default for Epoch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The cached result of an attribute lookup for a single instruction.
+pub struct InlineCache {
+    /// A sequence number that is odd while the cache is being written to.
+    sequence: AtomicUsize,
+
+    /// The prototype of the receiver.
+    prototype: AtomicUsize,
+
+    /// The name of the attribute.
+    name: AtomicUsize,
+
+    /// The value of the attribute.
+    value: AtomicUsize,
+
+    /// The epoch at the time the cache was filled.
+    epoch: AtomicUsize,
+}
+
+impl InlineCache {
+    pub fn new() -> Self {
+        InlineCache {
+            sequence: AtomicUsize::new(0),
+            prototype: AtomicUsize::new(0),
+            name: AtomicUsize::new(0),
+            value: AtomicUsize::new(0),
+            epoch: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the cached value for the given prototype and name, if any.
+    #[inline(always)]
+    pub fn lookup(
+        &self,
+        prototype: ObjectPointer,
+        name: ObjectPointer,
+        epoch: usize,
+    ) -> Option<ObjectPointer> {
+        let sequence = self.sequence.load(Ordering::Acquire);
+
+        if sequence & 1 == 1 {
+            return None;
+        }
+
+        let cached_prototype = self.prototype.load(Ordering::Relaxed);
+        let cached_name = self.name.load(Ordering::Relaxed);
+        let cached_value = self.value.load(Ordering::Relaxed);
+        let cached_epoch = self.epoch.load(Ordering::Relaxed);
+
+        fence(Ordering::Acquire);
+
+        if self.sequence.load(Ordering::Relaxed) != sequence
+            || cached_prototype != address(prototype)
+            || cached_name != address(name)
+            || cached_epoch != epoch
+        {
+            return None;
+        }
+
+        Some(ObjectPointer::new(cached_value as RawObjectPointer))
+    }
+
+    /// Caches the value of an attribute.
+    ///
+    /// If another thread is already writing to the cache, the value isn't
+    /// cached.
+    pub fn store(
+        &self,
+        prototype: ObjectPointer,
+        name: ObjectPointer,
+        value: ObjectPointer,
+        epoch: usize,
+    ) {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+
+        if sequence & 1 == 1
+            || self
+                .sequence
+                .compare_exchange(
+                    sequence,
+                    sequence.wrapping_add(1),
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+        {
+            return;
+        }
+
+        fence(Ordering::Release);
+
+        self.prototype.store(address(prototype), Ordering::Relaxed);
+        self.name.store(address(name), Ordering::Relaxed);
+        self.value.store(address(value), Ordering::Relaxed);
+        self.epoch.store(epoch, Ordering::Relaxed);
+
+        self.sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+}
+
+impl Default for InlineCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Assigns a cache to every instruction that looks up attributes, returning
+/// the caches.
+///
+/// The index of the cache is stored in the instruction. If a code object
+/// contains more of these instructions than we can index, the remaining
+/// instructions don't use a cache.
+pub fn assign(instructions: &mut [Instruction]) -> Vec<InlineCache> {
+    let mut caches = Vec::new();
+
+    for instruction in instructions.iter_mut() {
+        if instruction.opcode != Opcode::GetAttribute
+            && instruction.opcode != Opcode::GetLocalAttribute
+        {
+            continue;
+        }
+
+        if caches.len() < NO_CACHE as usize {
+            instruction.set_arg(CACHE_ARGUMENT, caches.len() as u16);
+            caches.push(InlineCache::new());
+        } else {
+            instruction.set_arg(CACHE_ARGUMENT, NO_CACHE);
+        }
+    }
+
+    caches
+}
+
+fn address(pointer: ObjectPointer) -> usize {
+    pointer.raw.raw as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_empty_cache() {
+        let cache = InlineCache::new();
+        let pointer = ObjectPointer::integer(1);
+
+        assert!(cache.lookup(pointer, pointer, 0).is_none());
+    }
+
+    #[test]
+    fn test_store_and_lookup() {
+        let cache = InlineCache::new();
+        let proto = ObjectPointer::integer(1);
+        let name = ObjectPointer::integer(2);
+        let value = ObjectPointer::integer(3);
+
+        cache.store(proto, name, value, 4);
+
+        assert!(cache.lookup(proto, name, 4) == Some(value));
+        assert!(cache.lookup(proto, name, 5).is_none());
+        assert!(cache.lookup(name, name, 4).is_none());
+        assert!(cache.lookup(proto, proto, 4).is_none());
+    }
+
+    #[test]
+    fn test_store_while_writing() {
+        let cache = InlineCache::new();
+        let pointer = ObjectPointer::integer(1);
+
+        cache.sequence.store(1, Ordering::Relaxed);
+        cache.store(pointer, pointer, pointer, 0);
+
+        assert_eq!(cache.sequence.load(Ordering::Relaxed), 1);
+        assert!(cache.lookup(pointer, pointer, 0).is_none());
+    }
+
+    #[test]
+    fn test_epoch() {
+        let epoch = Epoch::new();
+
+        epoch.increment();
+
+        assert_eq!(epoch.current(), 1);
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut instructions = vec![
+            Instruction::new(Opcode::GetAttribute, [0; 6], 1),
+            Instruction::new(Opcode::Return, [0; 6], 1),
+            Instruction::new(Opcode::GetAttribute, [0; 6], 1),
+            Instruction::new(Opcode::GetLocalAttribute, [0; 6], 1),
+        ];
+
+        let caches = assign(&mut instructions);
+
+        assert_eq!(caches.len(), 3);
+        assert_eq!(instructions[0].arg(CACHE_ARGUMENT), 0);
+        assert_eq!(instructions[1].arg(CACHE_ARGUMENT), 0);
+        assert_eq!(instructions[2].arg(CACHE_ARGUMENT), 1);
+        assert_eq!(instructions[3].arg(CACHE_ARGUMENT), 2);
+    }
+}
@@ -0,0 +1,297 @@
+//! A minimal x86-64 assembler.
+//!
+//! This assembler only supports the instructions used by the JIT, and only
+//! supports the registers listed in `Register`. Memory operands are always
+//! relative to the `rdi` register, which contains the address of the registers
+//! of the execution context.
+
+/// The general purpose registers used by the JIT.
+#[derive(Clone, Copy)]
+pub enum Register {
+    Rax = 0,
+    Rcx = 1,
+    Rdx = 2,
+    Rdi = 7,
+}
+
+/// The conditions to use for conditional jumps and moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Overflow = 0x0,
+
+    /// The zero flag is set, either because two values are equal or because
+    /// the result of an operation is zero.
+    Equal = 0x4,
+    NotEqual = 0x5,
+    Less = 0xC,
+    GreaterOrEqual = 0xD,
+    LessOrEqual = 0xE,
+    Greater = 0xF,
+}
+
+impl Condition {
+    /// Returns the condition that is met when the current one isn't.
+    pub fn negate(self) -> Condition {
+        match self {
+            Condition::Overflow => {
+                unreachable!("Overflow conditions can't be negated")
+            }
+            Condition::Equal => Condition::NotEqual,
+            Condition::NotEqual => Condition::Equal,
+            Condition::Less => Condition::GreaterOrEqual,
+            Condition::GreaterOrEqual => Condition::Less,
+            Condition::LessOrEqual => Condition::Greater,
+            Condition::Greater => Condition::LessOrEqual,
+        }
+    }
+}
+
+/// A jump of which the target is not yet known.
+pub struct Label {
+    /// The offset of the relative address to patch.
+    offset: usize,
+}
+
+/// A type for generating x86-64 machine code.
+pub struct Assembler {
+    code: Vec<u8>,
+}
+
+const REX_W: u8 = 0x48;
+
+fn mod_rm(mode: u8, register: u8, memory: u8) -> u8 {
+    mode << 6 | register << 3 | memory
+}
+
+impl Assembler {
+    pub fn new() -> Self {
+        Assembler { code: Vec::new() }
+    }
+
+    /// Returns the offset of the next instruction.
+    pub fn position(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn into_code(self) -> Vec<u8> {
+        self.code
+    }
+
+    /// Emits `mov target, [rdi + offset]`.
+    pub fn load(&mut self, target: Register, offset: u32) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x8B,
+            mod_rm(0b10, target as u8, Register::Rdi as u8),
+        ]);
+
+        self.code.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    /// Emits `mov [rdi + offset], source`.
+    pub fn store(&mut self, offset: u32, source: Register) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x89,
+            mod_rm(0b10, source as u8, Register::Rdi as u8),
+        ]);
+
+        self.code.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    /// Emits `mov target, value`.
+    pub fn move_immediate(&mut self, target: Register, value: u64) {
+        self.code.extend_from_slice(&[REX_W, 0xB8 + target as u8]);
+        self.code.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Emits `add target, source`.
+    pub fn add(&mut self, target: Register, source: Register) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x01,
+            mod_rm(0b11, source as u8, target as u8),
+        ]);
+    }
+
+    /// Emits `sub target, source`.
+    pub fn sub(&mut self, target: Register, source: Register) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x29,
+            mod_rm(0b11, source as u8, target as u8),
+        ]);
+    }
+
+    /// Emits `add target, value`.
+    pub fn add_immediate(&mut self, target: Register, value: i8) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x83,
+            mod_rm(0b11, 0, target as u8),
+            value as u8,
+        ]);
+    }
+
+    /// Emits `sub target, value`.
+    pub fn sub_immediate(&mut self, target: Register, value: i8) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x83,
+            mod_rm(0b11, 5, target as u8),
+            value as u8,
+        ]);
+    }
+
+    /// Emits `cmp left, right`.
+    pub fn compare(&mut self, left: Register, right: Register) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x39,
+            mod_rm(0b11, right as u8, left as u8),
+        ]);
+    }
+
+    /// Emits an instruction that sets the zero flag if the lowest bit of a
+    /// register isn't set.
+    ///
+    /// This only supports the rax, rcx, and rdx registers.
+    pub fn test_lowest_bit(&mut self, register: Register) {
+        self.code.extend_from_slice(&[
+            0xF6,
+            mod_rm(0b11, 0, register as u8),
+            1,
+        ]);
+    }
+
+    /// Emits `cmovcc target, source`.
+    pub fn move_if(
+        &mut self,
+        condition: Condition,
+        target: Register,
+        source: Register,
+    ) {
+        self.code.extend_from_slice(&[
+            REX_W,
+            0x0F,
+            0x40 + condition as u8,
+            mod_rm(0b11, target as u8, source as u8),
+        ]);
+    }
+
+    /// Emits a jump to a target that is bound later.
+    pub fn jump(&mut self) -> Label {
+        self.code.push(0xE9);
+        self.label()
+    }
+
+    /// Emits a conditional jump to a target that is bound later.
+    pub fn jump_if(&mut self, condition: Condition) -> Label {
+        self.code.extend_from_slice(&[0x0F, 0x80 + condition as u8]);
+        self.label()
+    }
+
+    /// Emits code that returns the given value.
+    pub fn return_value(&mut self, value: u32) {
+        self.code.push(0xB8);
+        self.code.extend_from_slice(&value.to_le_bytes());
+        self.code.push(0xC3);
+    }
+
+    /// Sets the target of a jump to the given offset.
+    pub fn bind(&mut self, label: Label, target: usize) {
+        let relative = target as i64 - (label.offset + 4) as i64;
+
+        self.code[label.offset..label.offset + 4]
+            .copy_from_slice(&(relative as i32).to_le_bytes());
+    }
+
+    fn label(&mut self) -> Label {
+        let offset = self.code.len();
+
+        self.code.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        Label { offset }
+    }
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assemble<F: FnOnce(&mut Assembler)>(block: F) -> Vec<u8> {
+        let mut asm = Assembler::new();
+
+        block(&mut asm);
+        asm.into_code()
+    }
+
+    #[test]
+    fn test_load_and_store() {
+        assert_eq!(
+            assemble(|asm| asm.load(Register::Rcx, 16)),
+            vec![0x48, 0x8B, 0x8F, 16, 0, 0, 0]
+        );
+
+        assert_eq!(
+            assemble(|asm| asm.store(8, Register::Rax)),
+            vec![0x48, 0x89, 0x87, 8, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            assemble(|asm| asm.add(Register::Rax, Register::Rcx)),
+            vec![0x48, 0x01, 0xC8]
+        );
+
+        assert_eq!(
+            assemble(|asm| asm.sub(Register::Rax, Register::Rcx)),
+            vec![0x48, 0x29, 0xC8]
+        );
+
+        assert_eq!(
+            assemble(|asm| asm.sub_immediate(Register::Rax, 1)),
+            vec![0x48, 0x83, 0xE8, 1]
+        );
+    }
+
+    #[test]
+    fn test_move_if() {
+        assert_eq!(
+            assemble(|asm| asm.move_if(
+                Condition::Less,
+                Register::Rax,
+                Register::Rdx
+            )),
+            vec![0x48, 0x0F, 0x4C, 0xC2]
+        );
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut asm = Assembler::new();
+        let label = asm.jump_if(Condition::Equal);
+
+        asm.return_value(1);
+
+        let target = asm.position();
+
+        asm.bind(label, target);
+
+        assert_eq!(&asm.into_code()[0..6], &[0x0F, 0x84, 6, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_condition_negate() {
+        assert_eq!(Condition::Less.negate(), Condition::GreaterOrEqual);
+        assert_eq!(Condition::Equal.negate(), Condition::NotEqual);
+    }
+}
@@ -0,0 +1,356 @@
+//! Compiling bytecode instructions to machine code.
+//!
+//! Every supported instruction is compiled using a fixed template, without any
+//! optimisations across instructions. The generated code only operates on the
+//! registers of an execution context, the address of which is passed in the
+//! `rdi` register. The code returns (in `rax`) the index of the instruction the
+//! interpreter should continue with.
+//!
+//! Native code never allocates, performs reductions, or enters a GC safepoint.
+//! Instructions that may do any of this are not supported, and return control
+//! to the interpreter. Integer operations only support tagged integers, and
+//! return to the interpreter (using the index of the instruction) if an
+//! operand isn't a tagged integer, or if the result doesn't fit in one. The
+//! interpreter then executes the instruction again, this time using its
+//! generic implementation.
+//!
+//! Jumps to earlier instructions also return to the interpreter, ensuring
+//! loops don't run in native code without ever giving the interpreter a chance
+//! to run.
+use crate::compiled_code::CompiledCode;
+use crate::jit::assembler::{Assembler, Condition, Label, Register};
+use crate::module::Module;
+use crate::object_pointer::ObjectPointer;
+use crate::optimiser::{
+    COMPARE_EQUALS, COMPARE_GREATER, COMPARE_GREATER_OR_EQUAL, COMPARE_SMALLER,
+    COMPARE_SMALLER_OR_EQUAL,
+};
+use crate::vm::instruction::{Instruction, Opcode};
+use crate::vm::state::State;
+use std::mem::size_of;
+
+/// Machine code for a CompiledCode, along with the entry points of its
+/// instructions.
+pub struct Output {
+    /// The machine code of all instructions.
+    pub code: Vec<u8>,
+
+    /// The offset of every instruction in the machine code, or None if the
+    /// instruction isn't supported.
+    pub entries: Vec<Option<u32>>,
+}
+
+/// The values of objects embedded in the machine code.
+struct Constants {
+    true_object: u64,
+    false_object: u64,
+    nil_object: u64,
+}
+
+/// A type for compiling the instructions of a single CompiledCode.
+pub struct Compiler<'a> {
+    code: &'a CompiledCode,
+    module: &'a Module,
+    constants: Constants,
+    asm: Assembler,
+
+    /// The offsets of the machine code of every instruction.
+    offsets: Vec<usize>,
+
+    /// Jumps to instructions that come after the current one.
+    forward_jumps: Vec<(Label, usize)>,
+
+    /// Jumps to code that returns to the interpreter.
+    exits: Vec<(Label, usize)>,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(
+        state: &State,
+        module: &'a Module,
+        code: &'a CompiledCode,
+    ) -> Self {
+        Compiler {
+            code,
+            module,
+            constants: Constants {
+                true_object: raw(state.true_object),
+                false_object: raw(state.false_object),
+                nil_object: raw(state.nil_object),
+            },
+            asm: Assembler::new(),
+            offsets: Vec::with_capacity(code.instructions.len() + 1),
+            forward_jumps: Vec::new(),
+            exits: Vec::new(),
+        }
+    }
+
+    /// Compiles all instructions.
+    ///
+    /// If none of the instructions are supported, None is returned.
+    pub fn compile(mut self) -> Option<Output> {
+        let code = self.code;
+        let mut entries = Vec::with_capacity(code.instructions.len());
+
+        for (index, instruction) in code.instructions.iter().enumerate() {
+            let offset = self.asm.position();
+
+            self.offsets.push(offset);
+
+            if self.instruction(index, instruction) {
+                entries.push(Some(offset as u32));
+            } else {
+                self.asm.return_value(index as u32);
+                entries.push(None);
+            }
+        }
+
+        if entries.iter().all(|entry| entry.is_none()) {
+            return None;
+        }
+
+        // Running past the last instruction is handled by the interpreter.
+        self.offsets.push(self.asm.position());
+        self.asm.return_value(code.instructions.len() as u32);
+
+        for (label, index) in self.forward_jumps.drain(..) {
+            self.asm.bind(label, self.offsets[index]);
+        }
+
+        for (label, index) in self.exits.drain(..) {
+            let offset = self.asm.position();
+
+            self.asm.bind(label, offset);
+            self.asm.return_value(index as u32);
+        }
+
+        Some(Output {
+            code: self.asm.into_code(),
+            entries,
+        })
+    }
+
+    /// Compiles a single instruction, returning false if it isn't supported.
+    fn instruction(&mut self, index: usize, ins: &Instruction) -> bool {
+        match ins.opcode {
+            Opcode::SetLiteral => {
+                let literal = self.literal(ins.arg(1));
+
+                self.asm.move_immediate(Register::Rax, literal);
+                self.asm.store(offset(ins.arg(0)), Register::Rax);
+            }
+            Opcode::GetTrue => {
+                self.set_constant(ins.arg(0), self.constants.true_object);
+            }
+            Opcode::GetFalse => {
+                self.set_constant(ins.arg(0), self.constants.false_object);
+            }
+            Opcode::GetNil => {
+                self.set_constant(ins.arg(0), self.constants.nil_object);
+            }
+            Opcode::CopyRegister => {
+                self.asm.load(Register::Rax, offset(ins.arg(1)));
+                self.asm.store(offset(ins.arg(0)), Register::Rax);
+            }
+            Opcode::IntegerAdd => {
+                self.integer_add(index, ins.arg(0), ins.arg(1), ins.arg(2));
+            }
+            Opcode::IntegerAddLiteral => {
+                let literal = self.literal(ins.arg(3));
+
+                if !is_tagged_integer(literal) {
+                    return false;
+                }
+
+                self.asm.move_immediate(Register::Rax, literal);
+                self.asm.store(offset(ins.arg(2)), Register::Rax);
+                self.integer_add(index, ins.arg(0), ins.arg(1), ins.arg(2));
+            }
+            Opcode::IntegerSub => {
+                self.load_integers(index, ins.arg(1), ins.arg(2));
+                self.asm.sub(Register::Rax, Register::Rcx);
+                self.exit_if(Condition::Overflow, index);
+
+                // Subtracting two tagged integers clears the tag bit, and
+                // setting it again can't overflow.
+                self.asm.add_immediate(Register::Rax, 1);
+                self.asm.store(offset(ins.arg(0)), Register::Rax);
+            }
+            Opcode::IntegerSmaller => {
+                self.integer_compare(index, ins, Condition::Less);
+            }
+            Opcode::IntegerGreater => {
+                self.integer_compare(index, ins, Condition::Greater);
+            }
+            Opcode::IntegerEquals => {
+                self.integer_compare(index, ins, Condition::Equal);
+            }
+            Opcode::IntegerGreaterOrEqual => {
+                self.integer_compare(index, ins, Condition::GreaterOrEqual);
+            }
+            Opcode::IntegerSmallerOrEqual => {
+                self.integer_compare(index, ins, Condition::LessOrEqual);
+            }
+            Opcode::IntegerCompareGoto => {
+                let condition = match ins.arg(4) {
+                    COMPARE_SMALLER => Condition::Less,
+                    COMPARE_GREATER => Condition::Greater,
+                    COMPARE_EQUALS => Condition::Equal,
+                    COMPARE_GREATER_OR_EQUAL => Condition::GreaterOrEqual,
+                    COMPARE_SMALLER_OR_EQUAL => Condition::LessOrEqual,
+                    _ => return false,
+                };
+
+                self.integer_compare(index, ins, condition);
+
+                // Storing the result doesn't change the flags set by the
+                // comparison.
+                let jump = if ins.arg(5) == 1 {
+                    condition
+                } else {
+                    condition.negate()
+                };
+
+                self.jump_if(jump, index, ins.arg(3) as usize);
+            }
+            Opcode::Goto => {
+                self.jump(index, ins.arg(0) as usize);
+            }
+            Opcode::GotoIfTrue => {
+                let falsy = self.compare_falsy(ins.arg(1));
+                let target = self.asm.jump();
+
+                self.jump_to(target, index, ins.arg(0) as usize);
+
+                let next = self.asm.position();
+
+                for label in falsy {
+                    self.asm.bind(label, next);
+                }
+            }
+            Opcode::GotoIfFalse => {
+                let target = ins.arg(0) as usize;
+
+                for label in self.compare_falsy(ins.arg(1)) {
+                    self.jump_to(label, index, target);
+                }
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn literal(&self, index: u16) -> u64 {
+        raw(unsafe { self.module.literal(index as usize) })
+    }
+
+    fn set_constant(&mut self, register: u16, value: u64) {
+        self.asm.move_immediate(Register::Rax, value);
+        self.asm.store(offset(register), Register::Rax);
+    }
+
+    /// Loads two tagged integers into the rax and rcx registers, returning to
+    /// the interpreter if either isn't a tagged integer.
+    fn load_integers(&mut self, index: usize, left: u16, right: u16) {
+        self.asm.load(Register::Rax, offset(left));
+        self.asm.load(Register::Rcx, offset(right));
+        self.asm.test_lowest_bit(Register::Rax);
+        self.exit_if(Condition::Equal, index);
+        self.asm.test_lowest_bit(Register::Rcx);
+        self.exit_if(Condition::Equal, index);
+    }
+
+    fn integer_add(
+        &mut self,
+        index: usize,
+        register: u16,
+        left: u16,
+        right: u16,
+    ) {
+        self.load_integers(index, left, right);
+
+        // The tag bit of one operand is cleared, so the sum has exactly one tag
+        // bit set. The result overflows if it doesn't fit in a tagged integer.
+        self.asm.sub_immediate(Register::Rax, 1);
+        self.asm.add(Register::Rax, Register::Rcx);
+        self.exit_if(Condition::Overflow, index);
+        self.asm.store(offset(register), Register::Rax);
+    }
+
+    /// Compares two tagged integers and stores a boolean in a register.
+    ///
+    /// Tagged integers compare the same way as their values, so they don't
+    /// need to be untagged first.
+    fn integer_compare(
+        &mut self,
+        index: usize,
+        ins: &Instruction,
+        condition: Condition,
+    ) {
+        self.load_integers(index, ins.arg(1), ins.arg(2));
+        self.asm.compare(Register::Rax, Register::Rcx);
+        self.asm
+            .move_immediate(Register::Rax, self.constants.false_object);
+        self.asm
+            .move_immediate(Register::Rdx, self.constants.true_object);
+        self.asm.move_if(condition, Register::Rax, Register::Rdx);
+        self.asm.store(offset(ins.arg(0)), Register::Rax);
+    }
+
+    /// Emits jumps that are taken if a register contains false or nil.
+    fn compare_falsy(&mut self, register: u16) -> Vec<Label> {
+        self.asm.load(Register::Rax, offset(register));
+
+        vec![self.constants.false_object, self.constants.nil_object]
+            .into_iter()
+            .map(|value| {
+                self.asm.move_immediate(Register::Rcx, value);
+                self.asm.compare(Register::Rax, Register::Rcx);
+                self.asm.jump_if(Condition::Equal)
+            })
+            .collect()
+    }
+
+    fn jump(&mut self, index: usize, target: usize) {
+        let label = self.asm.jump();
+
+        self.jump_to(label, index, target);
+    }
+
+    fn jump_if(&mut self, condition: Condition, index: usize, target: usize) {
+        let label = self.asm.jump_if(condition);
+
+        self.jump_to(label, index, target);
+    }
+
+    /// Sets the target of a jump emitted for the instruction at `index`.
+    fn jump_to(&mut self, label: Label, index: usize, target: usize) {
+        if target > index && target <= self.code.instructions.len() {
+            self.forward_jumps.push((label, target));
+        } else {
+            self.exits.push((label, target));
+        }
+    }
+
+    /// Emits a jump that returns to the interpreter, continuing at the given
+    /// instruction.
+    fn exit_if(&mut self, condition: Condition, index: usize) {
+        let label = self.asm.jump_if(condition);
+
+        self.exits.push((label, index));
+    }
+}
+
+/// Returns the offset of a register, relative to the first register.
+fn offset(register: u16) -> u32 {
+    u32::from(register) * size_of::<ObjectPointer>() as u32
+}
+
+fn raw(pointer: ObjectPointer) -> u64 {
+    pointer.raw.raw as usize as u64
+}
+
+fn is_tagged_integer(value: u64) -> bool {
+    value & 1 == 1
+}
@@ -0,0 +1,81 @@
+//! Memory for storing executable machine code.
+use nix::sys::mman::{mmap, mprotect, munmap, MapFlags, ProtFlags};
+use std::ffi::c_void;
+use std::ptr;
+
+/// A region of memory containing executable machine code.
+///
+/// The memory is only writable while copying the machine code into it, and is
+/// never writable and executable at the same time.
+pub struct ExecutableMemory {
+    pointer: *mut u8,
+    size: usize,
+}
+
+unsafe impl Send for ExecutableMemory {}
+unsafe impl Sync for ExecutableMemory {}
+
+impl ExecutableMemory {
+    /// Copies machine code into a new region of executable memory.
+    pub fn new(code: &[u8]) -> Result<Self, String> {
+        let size = code.len().max(1);
+
+        unsafe {
+            let pointer = mmap(
+                ptr::null_mut(),
+                size,
+                ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+            .map_err(|error| error.to_string())?;
+
+            let memory = ExecutableMemory {
+                pointer: pointer as *mut u8,
+                size,
+            };
+
+            ptr::copy_nonoverlapping(code.as_ptr(), memory.pointer, code.len());
+
+            mprotect(
+                pointer,
+                size,
+                ProtFlags::PROT_READ | ProtFlags::PROT_EXEC,
+            )
+            .map_err(|error| error.to_string())?;
+
+            Ok(memory)
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.pointer
+    }
+}
+
+impl Drop for ExecutableMemory {
+    fn drop(&mut self) {
+        unsafe {
+            // There's nothing we can do if this fails, other than leaking the
+            // memory.
+            let _ = munmap(self.pointer as *mut c_void, self.size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    #[test]
+    fn test_new() {
+        // mov eax, 42; ret
+        let memory = ExecutableMemory::new(&[0xB8, 42, 0, 0, 0, 0xC3]).unwrap();
+        let function: extern "C" fn() -> u32 =
+            unsafe { mem::transmute(memory.as_ptr()) };
+
+        assert_eq!(function(), 42);
+    }
+}
@@ -0,0 +1,473 @@
+//! A template JIT for x86-64 Linux.
+//!
+//! Once a CompiledCode has been executed a number of times (controlled using
+//! `Config::jit_threshold`), its instructions are compiled to machine code.
+//! Every supported instruction is an entry point into this machine code, and
+//! the interpreter enters the machine code whenever it's about to execute such
+//! an instruction. The machine code runs until it reaches an instruction it
+//! can't handle, at which point it returns the index of this instruction and
+//! the interpreter takes over again.
+//!
+//! The machine code never allocates, reduces, or enters a GC safepoint, so none
+//! of these have to be aware of the JIT. Instructions that need any of this
+//! (including sending messages, returning, and socket operations that may be
+//! retried using `try_runtime_error!`) always run in the interpreter.
+#[cfg(not(all(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("The JIT is only supported on x86-64 Linux");
+
+pub mod assembler;
+pub mod compiler;
+pub mod memory;
+
+use crate::compiled_code::CompiledCode;
+use crate::execution_context::ExecutionContext;
+use crate::jit::compiler::Compiler;
+use crate::jit::memory::ExecutableMemory;
+use crate::module::Module;
+use crate::object_pointer::ObjectPointer;
+use crate::vm::state::State;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// The signature of the machine code of an instruction.
+///
+/// The argument is a pointer to the registers of an execution context. The
+/// return value is the index of the instruction to continue with in the
+/// interpreter.
+type NativeFunction = unsafe extern "C" fn(*mut ObjectPointer) -> usize;
+
+/// The machine code of a CompiledCode.
+pub struct NativeCode {
+    memory: ExecutableMemory,
+
+    /// The offsets of the machine code of every instruction.
+    entries: Vec<Option<u32>>,
+}
+
+impl NativeCode {
+    /// Compiles a CompiledCode to machine code.
+    ///
+    /// None is returned if none of the instructions are supported.
+    pub fn compile(
+        state: &State,
+        module: &Module,
+        code: &CompiledCode,
+    ) -> Option<NativeCode> {
+        let output = Compiler::new(state, module, code).compile()?;
+
+        ExecutableMemory::new(&output.code)
+            .ok()
+            .map(|memory| NativeCode {
+                memory,
+                entries: output.entries,
+            })
+    }
+
+    /// Returns the machine code to run for an instruction, if any.
+    pub fn entry(&self, index: usize) -> Option<NativeFunction> {
+        self.entries
+            .get(index)
+            .and_then(|entry| *entry)
+            .map(|offset| unsafe {
+                mem::transmute::<*const u8, NativeFunction>(
+                    self.memory.as_ptr().add(offset as usize),
+                )
+            })
+    }
+
+    /// Runs the machine code for an instruction, starting with the given
+    /// registers.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `index` is the index of an instruction in
+    /// the CompiledCode this machine code was compiled from, and that
+    /// `registers` points to a valid, writable array containing all the
+    /// registers used by that CompiledCode.
+    pub unsafe fn run(
+        &self,
+        index: usize,
+        registers: *mut ObjectPointer,
+    ) -> Option<usize> {
+        self.entry(index).map(|function| function(registers))
+    }
+}
+
+/// The JIT state of a single CompiledCode.
+pub struct JitState {
+    /// The number of times the CompiledCode has been executed, up until it's
+    /// compiled.
+    executions: AtomicUsize,
+
+    /// The compiled machine code, or a NULL pointer if the code isn't compiled
+    /// (yet).
+    native: AtomicPtr<NativeCode>,
+}
+
+impl JitState {
+    pub fn new() -> Self {
+        JitState {
+            executions: AtomicUsize::new(0),
+            native: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn native(&self) -> Option<&NativeCode> {
+        unsafe { self.native.load(Ordering::Acquire).as_ref() }
+    }
+
+    /// Records an execution, returning true if the code should be compiled.
+    ///
+    /// This only returns true once, so only a single thread compiles the code.
+    fn record_execution(&self, threshold: usize) -> bool {
+        threshold > 0
+            && self.executions.fetch_add(1, Ordering::AcqRel) + 1 == threshold
+    }
+
+    fn set_native(&self, native: NativeCode) {
+        self.native
+            .store(Box::into_raw(Box::new(native)), Ordering::Release);
+    }
+}
+
+impl Default for JitState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for JitState {
+    fn drop(&mut self) {
+        let native = self.native.load(Ordering::Acquire);
+
+        if !native.is_null() {
+            unsafe {
+                drop(Box::from_raw(native));
+            }
+        }
+    }
+}
+
+/// Runs machine code for the instruction at the given index, if possible.
+///
+/// The return value is the index of the instruction the interpreter should
+/// execute next. If no machine code is available, this is the index that was
+/// given.
+#[inline(always)]
+pub fn enter(
+    state: &State,
+    context: &mut ExecutionContext,
+    index: usize,
+) -> usize {
+    let jit = &context.code.jit;
+
+    if let Some(native) = jit.native() {
+        let registers = context.registers.values.as_mut_ptr();
+
+        return unsafe { native.run(index, registers) }.unwrap_or(index);
+    }
+
+    // Coverage is recorded by the interpreter, so we don't compile code while
+    // recording coverage.
+    if index == 0
+        && state.coverage.is_none()
+        && jit.record_execution(state.config.jit_threshold)
+    {
+        if let Some(native) =
+            NativeCode::compile(state, &context.module, &context.code)
+        {
+            jit.set_native(native);
+        }
+    }
+
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::optimiser::COMPARE_SMALLER;
+    use crate::vm::instruction::{Instruction, Opcode};
+    use crate::vm::state::{RcState, State};
+
+    fn state() -> RcState {
+        State::with_rc(Config::new(), &[])
+    }
+
+    fn instruction(opcode: Opcode, arguments: &[u16]) -> Instruction {
+        let mut args = [0; 6];
+
+        args[..arguments.len()].copy_from_slice(arguments);
+        Instruction::new(opcode, args, 1)
+    }
+
+    fn code(state: &State, instructions: Vec<Instruction>) -> Module {
+        let name = state.intern_string("test".to_string());
+        let code = CompiledCode::new(name, name, 1, instructions);
+
+        Module::new(name, code, vec![ObjectPointer::integer(5)])
+    }
+
+    fn compile(state: &State, instructions: Vec<Instruction>) -> NativeCode {
+        let module = code(state, instructions);
+
+        NativeCode::compile(state, &module, &module.code()).unwrap()
+    }
+
+    fn run(
+        native: &NativeCode,
+        index: usize,
+        registers: &mut [ObjectPointer],
+    ) -> Option<usize> {
+        unsafe { native.run(index, registers.as_mut_ptr()) }
+    }
+
+    fn integers(values: &[i64]) -> Vec<ObjectPointer> {
+        values
+            .iter()
+            .map(|value| ObjectPointer::integer(*value))
+            .collect()
+    }
+
+    #[test]
+    fn test_set_literal() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::SetLiteral, &[0, 0]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(1));
+        assert!(registers[0] == ObjectPointer::integer(5));
+    }
+
+    #[test]
+    fn test_integer_add() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::IntegerAdd, &[0, 1, 2]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, -3, 5]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(1));
+        assert!(registers[0] == ObjectPointer::integer(2));
+    }
+
+    #[test]
+    fn test_integer_add_literal() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::IntegerAddLiteral, &[0, 1, 2, 0]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, 4, 0]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(1));
+        assert!(registers[0] == ObjectPointer::integer(9));
+        assert!(registers[2] == ObjectPointer::integer(5));
+    }
+
+    #[test]
+    fn test_integer_sub() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::IntegerSub, &[0, 1, 2]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, 3, 5]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(1));
+        assert!(registers[0] == ObjectPointer::integer(-2));
+    }
+
+    #[test]
+    fn test_integer_add_overflow() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::IntegerAdd, &[0, 1, 2]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let max = i64::MAX >> 1;
+        let mut registers = integers(&[0, max, max]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(0));
+        assert!(registers[0] == ObjectPointer::integer(0));
+    }
+
+    #[test]
+    fn test_integer_add_with_float() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::IntegerAdd, &[0, 1, 2]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, 1]);
+
+        registers.push(ObjectPointer::float(1.5));
+
+        assert_eq!(run(&native, 0, &mut registers), Some(0));
+        assert!(registers[0] == ObjectPointer::integer(0));
+    }
+
+    #[test]
+    fn test_integer_compare() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::IntegerSmaller, &[0, 1, 2]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, -1, 1]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(1));
+        assert!(registers[0] == state.true_object);
+
+        registers[1] = ObjectPointer::integer(2);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(1));
+        assert!(registers[0] == state.false_object);
+    }
+
+    #[test]
+    fn test_integer_compare_goto() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(
+                    Opcode::IntegerCompareGoto,
+                    &[0, 1, 2, 3, COMPARE_SMALLER, 1],
+                ),
+                instruction(Opcode::IntegerAdd, &[1, 1, 1]),
+                instruction(Opcode::Return, &[0]),
+                instruction(Opcode::IntegerAdd, &[2, 2, 2]),
+                instruction(Opcode::Return, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, 1, 2]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(4));
+        assert!(registers[0] == state.true_object);
+        assert!(registers[1] == ObjectPointer::integer(1));
+        assert!(registers[2] == ObjectPointer::integer(4));
+    }
+
+    #[test]
+    fn test_goto_if_false() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::GotoIfFalse, &[2, 0]),
+                instruction(Opcode::GetTrue, &[1]),
+                instruction(Opcode::Return, &[1]),
+            ],
+        );
+        let mut registers = vec![state.nil_object, ObjectPointer::integer(0)];
+
+        assert_eq!(run(&native, 0, &mut registers), Some(2));
+        assert!(registers[1] == ObjectPointer::integer(0));
+
+        registers[0] = ObjectPointer::integer(0);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(2));
+        assert!(registers[1] == state.true_object);
+    }
+
+    #[test]
+    fn test_goto_if_true() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::GotoIfTrue, &[2, 0]),
+                instruction(Opcode::GetNil, &[1]),
+                instruction(Opcode::Return, &[1]),
+            ],
+        );
+        let mut registers = vec![state.false_object, ObjectPointer::integer(0)];
+
+        assert_eq!(run(&native, 0, &mut registers), Some(2));
+        assert!(registers[1] == state.nil_object);
+
+        registers[0] = state.true_object;
+        registers[1] = ObjectPointer::integer(0);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(2));
+        assert!(registers[1] == ObjectPointer::integer(0));
+    }
+
+    #[test]
+    fn test_backward_jump() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::CopyRegister, &[0, 1]),
+                instruction(Opcode::Goto, &[0]),
+            ],
+        );
+        let mut registers = integers(&[0, 1]);
+
+        assert_eq!(run(&native, 0, &mut registers), Some(0));
+        assert!(registers[0] == ObjectPointer::integer(1));
+    }
+
+    #[test]
+    fn test_entry() {
+        let state = state();
+        let native = compile(
+            &state,
+            vec![
+                instruction(Opcode::Return, &[0]),
+                instruction(Opcode::GetNil, &[0]),
+            ],
+        );
+
+        assert!(native.entry(0).is_none());
+        assert!(native.entry(1).is_some());
+        assert!(native.entry(2).is_none());
+    }
+
+    #[test]
+    fn test_compile_without_supported_instructions() {
+        let state = state();
+        let module = code(&state, vec![instruction(Opcode::Return, &[0])]);
+
+        assert!(NativeCode::compile(&state, &module, &module.code()).is_none());
+    }
+
+    #[test]
+    fn test_jit_state_record_execution() {
+        let jit = JitState::new();
+
+        assert_eq!(jit.record_execution(2), false);
+        assert_eq!(jit.record_execution(2), true);
+        assert_eq!(jit.record_execution(2), false);
+        assert_eq!(jit.record_execution(0), false);
+    }
+}
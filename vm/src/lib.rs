@@ -4,6 +4,7 @@
 #![cfg_attr(feature = "cargo-clippy", allow(missing_safety_doc))]
 
 pub mod arc_without_weak;
+pub mod attributes;
 pub mod binding;
 pub mod block;
 pub mod broadcast;
@@ -13,7 +14,11 @@ pub mod chunk;
 pub mod closable;
 pub mod compiled_code;
 pub mod config;
+pub mod coverage;
+pub mod crash_report;
 pub mod date_time;
+pub mod decimal;
+pub mod deadlock_detector;
 pub mod deref_pointer;
 pub mod directories;
 pub mod duration;
@@ -27,7 +32,10 @@ pub mod global_scope;
 pub mod hasher;
 pub mod immix;
 pub mod immutable_string;
+pub mod inline_cache;
 pub mod integer_operations;
+#[cfg(feature = "jit")]
+pub mod jit;
 pub mod macros;
 pub mod mailbox;
 pub mod module;
@@ -37,15 +45,20 @@ pub mod numeric;
 pub mod object;
 pub mod object_pointer;
 pub mod object_value;
+pub mod optimiser;
 pub mod platform;
 pub mod process;
+pub mod process_registry;
 pub mod process_status;
 pub mod registers;
 pub mod runtime_error;
 pub mod runtime_panic;
+pub mod sandbox;
 pub mod scheduler;
+pub mod signals;
 pub mod slicing;
 pub mod socket;
+pub mod stack_dump;
 pub mod string_pool;
 pub mod tagged_pointer;
 pub mod vm;
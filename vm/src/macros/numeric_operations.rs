@@ -258,8 +258,7 @@ macro_rules! float_op {
         let arg = arg_ptr.float_value()?;
         let result = to_expr!(rec $op arg);
 
-        let obj = $process
-            .allocate(object_value::float(result), $state.float_prototype);
+        let obj = $process.allocate_f64(result, $state.float_prototype);
 
         $context.set_register(register, obj);
     }};
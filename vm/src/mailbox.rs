@@ -1,15 +1,24 @@
 use crate::object_pointer::{ObjectPointer, ObjectPointerPointer};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 pub struct Mailbox {
     /// The messages stored in this mailbox.
     messages: VecDeque<ObjectPointer>,
+
+    /// The messages to send when a timer expires, mapped to the IDs of the
+    /// timers.
+    timers: HashMap<usize, ObjectPointer>,
+
+    /// The reason another process gave when terminating this process.
+    termination_reason: ObjectPointer,
 }
 
 impl Mailbox {
     pub fn new() -> Self {
         Mailbox {
             messages: VecDeque::new(),
+            timers: HashMap::new(),
+            termination_reason: ObjectPointer::null(),
         }
     }
 
@@ -21,6 +30,30 @@ impl Mailbox {
         self.messages.pop_front()
     }
 
+    pub fn add_timer(&mut self, id: usize, message: ObjectPointer) {
+        self.timers.insert(id, message);
+    }
+
+    pub fn remove_timer(&mut self, id: usize) -> Option<ObjectPointer> {
+        self.timers.remove(&id)
+    }
+
+    pub fn timer_message(&self, id: usize) -> Option<ObjectPointer> {
+        self.timers.get(&id).cloned()
+    }
+
+    pub fn set_termination_reason(&mut self, reason: ObjectPointer) {
+        self.termination_reason = reason;
+    }
+
+    pub fn termination_reason(&self) -> Option<ObjectPointer> {
+        if self.termination_reason.is_null() {
+            None
+        } else {
+            Some(self.termination_reason)
+        }
+    }
+
     pub fn each_pointer<F>(&self, mut callback: F)
     where
         F: FnMut(ObjectPointerPointer),
@@ -28,11 +61,28 @@ impl Mailbox {
         for message in &self.messages {
             callback(message.pointer());
         }
+
+        for message in self.timers.values() {
+            callback(message.pointer());
+        }
+
+        if !self.termination_reason.is_null() {
+            callback(self.termination_reason.pointer());
+        }
     }
 
     pub fn has_messages(&self) -> bool {
         !self.messages.is_empty()
     }
+
+    /// Returns the number of messages that have yet to be received.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
 }
 
 #[cfg(test)]
@@ -68,6 +118,53 @@ mod tests {
         assert!(mailbox.receive() == Some(ObjectPointer::new(0x4 as _)));
     }
 
+    #[test]
+    fn test_add_timer() {
+        let mut mailbox = Mailbox::new();
+
+        mailbox.add_timer(1, ObjectPointer::integer(5));
+
+        assert!(mailbox.timer_message(1) == Some(ObjectPointer::integer(5)));
+        assert_eq!(mailbox.has_messages(), false);
+    }
+
+    #[test]
+    fn test_remove_timer() {
+        let mut mailbox = Mailbox::new();
+
+        mailbox.add_timer(1, ObjectPointer::integer(5));
+
+        assert!(mailbox.remove_timer(1) == Some(ObjectPointer::integer(5)));
+        assert!(mailbox.remove_timer(1).is_none());
+        assert!(mailbox.timer_message(1).is_none());
+    }
+
+    #[test]
+    fn test_each_pointer_with_timers() {
+        let mut mailbox = Mailbox::new();
+
+        mailbox.add_timer(1, ObjectPointer::new(0x1 as _));
+
+        let mut pointers = Vec::new();
+
+        mailbox.each_pointer(|ptr| pointers.push(ptr));
+
+        assert_eq!(pointers.len(), 1);
+    }
+
+    #[test]
+    fn test_termination_reason() {
+        let mut mailbox = Mailbox::new();
+
+        assert!(mailbox.termination_reason().is_none());
+
+        mailbox.set_termination_reason(ObjectPointer::integer(5));
+
+        assert!(
+            mailbox.termination_reason() == Some(ObjectPointer::integer(5))
+        );
+    }
+
     #[test]
     fn test_has_messagess() {
         let mut mailbox = Mailbox::new();
@@ -78,4 +175,17 @@ mod tests {
 
         assert!(mailbox.has_messages());
     }
+
+    #[test]
+    fn test_len() {
+        let mut mailbox = Mailbox::new();
+
+        assert_eq!(mailbox.len(), 0);
+        assert!(mailbox.is_empty());
+
+        mailbox.send(ObjectPointer::integer(5));
+
+        assert_eq!(mailbox.len(), 1);
+        assert_eq!(mailbox.is_empty(), false);
+    }
 }
@@ -63,6 +63,14 @@ impl Modules {
         self.map.values().copied().collect()
     }
 
+    /// Returns the names of all modules, sorted alphabetically.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.map.keys().cloned().collect();
+
+        names.sort();
+        names
+    }
+
     pub fn get_for_execution(
         &mut self,
         name: &str,
@@ -98,10 +98,14 @@ impl Worker {
             }
 
             for event in &events {
-                let process =
+                let process: RcProcess =
                     unsafe { ArcWithoutWeak::from_raw(event.key as *mut _) };
 
-                self.state.scheduler.schedule(process);
+                // The process may have been rescheduled already, for example
+                // when another process requested it to terminate.
+                if process.acquire_io_rescheduling_rights() {
+                    self.state.scheduler.schedule(process);
+                }
             }
 
             events.clear();
@@ -3,26 +3,16 @@
 //! The Object struct is used to represent an object created during runtime. It
 //! can be used to wrap native values (e.g. an integer or a string), look up
 //! methods, add attributes, etc.
-use ahash::AHashMap;
 use std::ops::Drop;
 use std::ptr;
 
+use crate::attributes::Attributes;
 use crate::object_pointer::{
     ObjectPointer, ObjectPointerPointer, RawObjectPointer,
 };
 use crate::object_value::ObjectValue;
 use crate::tagged_pointer::TaggedPointer;
 
-macro_rules! push_collection {
-    ($map:expr, $what:ident, $vec:expr) => {{
-        $vec.reserve($map.len());
-
-        for thing in $map.$what() {
-            $vec.push(*thing);
-        }
-    }};
-}
-
 /// The status of an object.
 #[derive(Eq, PartialEq, Debug)]
 pub enum ObjectStatus {
@@ -43,8 +33,6 @@ pub enum ObjectStatus {
     PendingMove,
 }
 
-pub type AttributesMap = AHashMap<ObjectPointer, ObjectPointer>;
-
 /// The bit to set for objects that are being forwarded.
 pub const PENDING_FORWARD_BIT: usize = 0;
 
@@ -63,7 +51,8 @@ pub struct Object {
     pub prototype: ObjectPointer,
 
     /// A pointer to the attributes of this object. Attributes are allocated
-    /// on-demand and default to a NULL pointer.
+    /// on-demand and default to a NULL pointer. See the `attributes` module for
+    /// more information on how attributes are stored.
     ///
     /// This pointer may be tagged to store extra information. The following
     /// lower bits can be set:
@@ -76,7 +65,7 @@ pub struct Object {
     ///
     /// Multiple bits can be set as well. For example, `101` would mean the
     /// object is remembered and being forwarded.
-    pub attributes: TaggedPointer<AttributesMap>,
+    pub attributes: TaggedPointer<Attributes>,
 
     /// A native Rust value (e.g. a String) that belongs to this object.
     pub value: ObjectValue,
@@ -139,8 +128,8 @@ impl Object {
         &mut self,
         name: ObjectPointer,
     ) -> Option<ObjectPointer> {
-        if let Some(map) = self.attributes_map_mut() {
-            map.remove(&name)
+        if let Some(attributes) = self.attributes_map_mut() {
+            attributes.remove(name)
         } else {
             None
         }
@@ -148,24 +137,20 @@ impl Object {
 
     /// Returns all the attributes available to this object.
     pub fn attributes(&self) -> Vec<ObjectPointer> {
-        let mut attributes = Vec::new();
-
-        if let Some(map) = self.attributes_map() {
-            push_collection!(map, values, attributes);
+        if let Some(attributes) = self.attributes_map() {
+            attributes.iter().map(|(_, value)| *value).collect()
+        } else {
+            Vec::new()
         }
-
-        attributes
     }
 
     /// Returns all the attribute names available to this object.
     pub fn attribute_names(&self) -> Vec<ObjectPointer> {
-        let mut attributes = Vec::new();
-
-        if let Some(map) = self.attributes_map() {
-            push_collection!(map, keys, attributes);
+        if let Some(attributes) = self.attributes_map() {
+            attributes.iter().map(|(name, _)| *name).collect()
+        } else {
+            Vec::new()
         }
-
-        attributes
     }
 
     /// Looks up an attribute in either the current object or a parent object.
@@ -214,23 +199,23 @@ impl Object {
         &self,
         name: ObjectPointer,
     ) -> Option<ObjectPointer> {
-        if let Some(map) = self.attributes_map() {
-            map.get(&name).cloned()
+        if let Some(attributes) = self.attributes_map() {
+            attributes.get(name)
         } else {
             None
         }
     }
 
     /// Returns an immutable reference to the attributes.
-    pub fn attributes_map(&self) -> Option<&AttributesMap> {
+    pub fn attributes_map(&self) -> Option<&Attributes> {
         self.attributes.as_ref()
     }
 
-    pub fn attributes_map_mut(&self) -> Option<&mut AttributesMap> {
+    pub fn attributes_map_mut(&self) -> Option<&mut Attributes> {
         self.attributes.as_mut()
     }
 
-    pub fn set_attributes_map(&mut self, attrs: AttributesMap) {
+    pub fn set_attributes_map(&mut self, attrs: Attributes) {
         self.attributes = TaggedPointer::new(Box::into_raw(Box::new(attrs)));
     }
 
@@ -242,10 +227,10 @@ impl Object {
             callback(self.prototype.pointer());
         }
 
-        if let Some(map) = self.attributes_map() {
+        if let Some(attributes) = self.attributes_map() {
             // Attribute keys are interned strings, which don't need to be
             // marked.
-            for (_, pointer) in map.iter() {
+            for (_, pointer) in attributes.iter() {
                 callback(pointer.pointer());
             }
         }
@@ -281,7 +266,7 @@ impl Object {
         // When taking over the attributes we want to automatically inherit the
         // "remembered" bit, but not the forwarding bits.
         let attrs = (self.attributes.raw as usize & !FORWARDING_MASK)
-            as *mut AttributesMap;
+            as *mut Attributes;
 
         new_obj.attributes = TaggedPointer::new(attrs);
 
@@ -321,7 +306,7 @@ impl Object {
         // one. This removes the need for checking both bits when determining if
         // forwarding is necessary.
         let new_attrs =
-            (pointer.raw.raw as usize | FORWARDING_MASK) as *mut AttributesMap;
+            (pointer.raw.raw as usize | FORWARDING_MASK) as *mut Attributes;
 
         self.attributes.atomic_store(new_attrs);
     }
@@ -386,7 +371,7 @@ impl Object {
     /// Allocates an attribute map if needed.
     fn allocate_attributes_map(&mut self) {
         if !self.has_attributes() {
-            self.set_attributes_map(AttributesMap::default());
+            self.set_attributes_map(Attributes::new());
         }
     }
 }
@@ -591,7 +576,7 @@ mod tests {
     #[test]
     fn test_object_attributes_map_set_map() {
         let mut obj = new_object();
-        let map = AttributesMap::default();
+        let map = Attributes::new();
 
         obj.set_attributes_map(map);
 
@@ -733,7 +718,7 @@ mod tests {
     #[test]
     fn test_object_take() {
         let mut obj = Object::new(ObjectValue::Float(10.0));
-        let map = AttributesMap::default();
+        let map = Attributes::new();
 
         obj.set_attributes_map(map);
 
@@ -761,7 +746,7 @@ mod tests {
     #[test]
     fn test_object_has_attributes() {
         let mut obj = Object::new(ObjectValue::Float(10.0));
-        let map = AttributesMap::default();
+        let map = Attributes::new();
 
         obj.set_attributes_map(map);
 
@@ -782,7 +767,7 @@ mod tests {
     #[test]
     fn test_object_has_attributes_remembered() {
         let mut obj = Object::new(ObjectValue::Float(10.0));
-        let map = AttributesMap::default();
+        let map = Attributes::new();
 
         obj.set_attributes_map(map);
         obj.mark_as_remembered();
@@ -4,6 +4,7 @@
 
 use num_bigint::BigInt;
 use num_traits::{ToPrimitive, Zero};
+use regex::Regex;
 use std::f32;
 use std::f64;
 use std::hash::{Hash, Hasher as HasherTrait};
@@ -21,6 +22,7 @@ use std::usize;
 use crate::arc_without_weak::ArcWithoutWeak;
 use crate::binding::RcBinding;
 use crate::block::Block;
+use crate::decimal::Decimal;
 use crate::ffi::{Library, Pointer, RcFunction};
 use crate::file::File;
 use crate::hasher::Hasher;
@@ -41,9 +43,9 @@ use crate::vm::state::RcState;
 macro_rules! def_value_getter {
     ($name: ident, $getter: ident, $as_type: ident, $ok_type: ty) => {
         pub fn $name(&self) -> Result<$ok_type, String> {
-            if self.is_tagged_integer() {
+            if self.is_tagged() {
                 Err(format!(
-                    "ObjectPointer::{}() called on a tagged integer",
+                    "ObjectPointer::{}() called on a tagged value",
                     stringify!($as_type)
                 ))
             } else {
@@ -88,6 +90,10 @@ pub struct ObjectPointer {
     ///
     /// * 00: the pointer is a regular pointer
     /// * 01: the pointer is a tagged integer
+    /// * 10: the pointer is a tagged float
+    ///
+    /// Since tagged integers use all but the lowest bit for their value, the
+    /// second bit may also be set for tagged integers.
     pub raw: TaggedPointer<Object>,
 }
 
@@ -107,6 +113,31 @@ unsafe impl Sync for ObjectPointerPointer {}
 /// The bit to set for tagged integers.
 pub const INTEGER_BIT: usize = 0;
 
+/// The bit to set for tagged floats.
+pub const FLOAT_BIT: usize = 1;
+
+/// The mask to use for checking if a pointer is tagged.
+const TAG_MASK: usize = 0b11;
+
+/// The number of bits to shift the bits of a tagged float by.
+const FLOAT_SHIFT: u64 = 2;
+
+/// The position of the exponent in the bits of a float, after rotating its
+/// bits so the sign is the lowest bit.
+const FLOAT_EXPONENT_SHIFT: u64 = 53;
+
+/// The offset to subtract from the exponent of a tagged float.
+///
+/// A float uses 11 bits for its exponent, but the tag bits only leave room for
+/// 9 bits. Exponents are stored relative to this offset, allowing tagged floats
+/// to use the biased exponents 768 up to and including 1278. This covers all
+/// values from roughly 1.7e-77 up to 1.1e77, which is enough for the vast
+/// majority of floats.
+const FLOAT_EXPONENT_OFFSET: u64 = 767;
+
+/// The largest biased exponent that can be stored in a tagged float.
+const MAX_FLOAT_EXPONENT: u64 = FLOAT_EXPONENT_OFFSET + 511;
+
 /// Returns the BlockHeader of the given pointer.
 fn block_header_of<'a>(
     pointer: RawObjectPointer,
@@ -141,6 +172,32 @@ impl ObjectPointer {
         Self::integer(i64::from(value))
     }
 
+    /// Creates a new tagged float.
+    ///
+    /// The value must not be too large for a tagged float, as determined by
+    /// `ObjectPointer::float_too_large()`.
+    ///
+    /// The bits of the float are rotated so the sign is stored in the lowest
+    /// bit, followed by the mantissa and the exponent. The exponent is then
+    /// stored relative to `FLOAT_EXPONENT_OFFSET`, freeing up the upper bits
+    /// for the tag bits. Positive and negative zero don't use an exponent, and
+    /// are stored as-is.
+    pub fn float(value: f64) -> ObjectPointer {
+        let rotated = value.to_bits().rotate_left(1);
+        let encoded = if rotated <= 1 {
+            rotated
+        } else {
+            rotated - (FLOAT_EXPONENT_OFFSET << FLOAT_EXPONENT_SHIFT)
+        };
+
+        ObjectPointer {
+            raw: TaggedPointer::with_bit(
+                (encoded << FLOAT_SHIFT) as usize as RawObjectPointer,
+                FLOAT_BIT,
+            ),
+        }
+    }
+
     /// Returns `true` if the given unsigned integer is too large for a tagged
     /// pointer.
     pub fn unsigned_integer_too_large(value: u64) -> bool {
@@ -158,6 +215,22 @@ impl ObjectPointer {
         value < MIN_INTEGER || value > MAX_INTEGER
     }
 
+    /// Returns `true` if the given float can't be stored as a tagged float.
+    ///
+    /// This is the case for NaN, infinity, and floats with an exponent that
+    /// is too large or too small.
+    pub fn float_too_large(value: f64) -> bool {
+        let rotated = value.to_bits().rotate_left(1);
+
+        if rotated <= 1 {
+            return false;
+        }
+
+        let exponent = rotated >> FLOAT_EXPONENT_SHIFT;
+
+        exponent <= FLOAT_EXPONENT_OFFSET || exponent > MAX_FLOAT_EXPONENT
+    }
+
     /// Creates a new null pointer.
     pub fn null() -> ObjectPointer {
         ObjectPointer {
@@ -244,26 +317,22 @@ impl ObjectPointer {
 
     /// Returns true if the current pointer points to a permanent object.
     pub fn is_permanent(&self) -> bool {
-        self.is_tagged_integer()
-            || self.block().bucket().unwrap().age == PERMANENT
+        self.is_tagged() || self.block().bucket().unwrap().age == PERMANENT
     }
 
     /// Returns true if the current pointer points to a mature object.
     pub fn is_mature(&self) -> bool {
-        !self.is_tagged_integer()
-            && self.block().bucket().unwrap().age == MATURE
+        !self.is_tagged() && self.block().bucket().unwrap().age == MATURE
     }
 
     /// Returns true if the current pointer points to a mailbox object.
     pub fn is_mailbox(&self) -> bool {
-        !self.is_tagged_integer()
-            && self.block().bucket().unwrap().age == MAILBOX
+        !self.is_tagged() && self.block().bucket().unwrap().age == MAILBOX
     }
 
     /// Returns true if the current pointer points to a young object.
     pub fn is_young(&self) -> bool {
-        !self.is_tagged_integer()
-            && self.block().bucket().unwrap().age <= YOUNG_MAX_AGE
+        !self.is_tagged() && self.block().bucket().unwrap().age <= YOUNG_MAX_AGE
     }
 
     /// Marks the current object and its line.
@@ -305,7 +374,7 @@ impl ObjectPointer {
     /// an object is marked while another thread is updating the pointer's
     /// address (e.g. after evacuating the underlying object).
     pub fn is_marked(&self) -> bool {
-        if self.is_tagged_integer() {
+        if self.is_tagged() {
             return true;
         }
 
@@ -344,7 +413,7 @@ impl ObjectPointer {
 
     /// Returns true if the object should be finalized.
     pub fn is_finalizable(&self) -> bool {
-        !self.is_tagged_integer() && self.get().is_finalizable()
+        !self.is_tagged() && self.get().is_finalizable()
     }
 
     /// Finalizes the underlying object, if needed.
@@ -383,6 +452,8 @@ impl ObjectPointer {
     ) -> Option<ObjectPointer> {
         if self.is_tagged_integer() {
             state.integer_prototype.get().lookup_attribute(name)
+        } else if self.is_tagged_float() {
+            state.float_prototype.get().lookup_attribute(name)
         } else {
             self.get().lookup_attribute(name)
         }
@@ -396,13 +467,15 @@ impl ObjectPointer {
     ) -> Option<ObjectPointer> {
         if self.is_tagged_integer() {
             state.integer_prototype.get().lookup_attribute_in_self(name)
+        } else if self.is_tagged_float() {
+            state.float_prototype.get().lookup_attribute_in_self(name)
         } else {
             self.get().lookup_attribute_in_self(name)
         }
     }
 
     pub fn attributes(&self) -> Vec<ObjectPointer> {
-        if self.is_tagged_integer() {
+        if self.is_tagged() {
             Vec::new()
         } else {
             self.get().attributes()
@@ -410,7 +483,7 @@ impl ObjectPointer {
     }
 
     pub fn attribute_names(&self) -> Vec<ObjectPointer> {
-        if self.is_tagged_integer() {
+        if self.is_tagged() {
             Vec::new()
         } else {
             self.get().attribute_names()
@@ -424,6 +497,8 @@ impl ObjectPointer {
     pub fn prototype(&self, state: &RcState) -> Option<ObjectPointer> {
         if self.is_tagged_integer() {
             Some(state.integer_prototype)
+        } else if self.is_tagged_float() {
+            Some(state.float_prototype)
         } else {
             self.get().prototype()
         }
@@ -434,12 +509,21 @@ impl ObjectPointer {
         ObjectPointerPointer::new(self)
     }
 
+    /// Returns true if the pointer is a tagged integer or a tagged float.
+    pub fn is_tagged(&self) -> bool {
+        self.raw.raw as usize & TAG_MASK != 0
+    }
+
     pub fn is_tagged_integer(&self) -> bool {
         self.raw.bit_is_set(INTEGER_BIT)
     }
 
+    pub fn is_tagged_float(&self) -> bool {
+        self.raw.raw as usize & TAG_MASK == 1 << FLOAT_BIT
+    }
+
     pub fn is_string(&self) -> bool {
-        if self.is_tagged_integer() {
+        if self.is_tagged() {
             false
         } else {
             self.get().value.is_string()
@@ -447,15 +531,27 @@ impl ObjectPointer {
     }
 
     pub fn is_interned_string(&self) -> bool {
-        if self.is_tagged_integer() {
+        if self.is_tagged() {
             false
         } else {
             self.get().value.is_interned_string()
         }
     }
 
+    pub fn is_file(&self) -> bool {
+        if self.is_tagged() {
+            false
+        } else {
+            self.get().value.is_file()
+        }
+    }
+
     pub fn is_integer(&self) -> bool {
-        self.is_tagged_integer() || self.get().value.is_integer()
+        if self.is_tagged() {
+            self.is_tagged_integer()
+        } else {
+            self.get().value.is_integer()
+        }
     }
 
     pub fn is_float(&self) -> bool {
@@ -463,7 +559,7 @@ impl ObjectPointer {
     }
 
     pub fn is_bigint(&self) -> bool {
-        if self.is_integer() {
+        if self.is_tagged() {
             false
         } else {
             self.get().value.is_bigint()
@@ -497,12 +593,15 @@ impl ObjectPointer {
     }
 
     pub fn is_immutable(&self) -> bool {
-        self.is_tagged_integer() || self.get().value.is_immutable()
+        self.is_tagged() || self.get().value.is_immutable()
     }
 
     pub fn integer_value(&self) -> Result<i64, String> {
         if self.is_tagged_integer() {
             Ok(self.raw.raw as i64 >> 1)
+        } else if self.is_tagged_float() {
+            Err("ObjectPointer::integer_value() called on a tagged float"
+                .to_string())
         } else if let Ok(num) = self.get().value.as_integer() {
             Ok(num)
         } else {
@@ -570,6 +669,8 @@ impl ObjectPointer {
     pub fn hash_object(&self, hasher: &mut Hasher) -> Result<(), String> {
         if self.is_tagged_integer() {
             hasher.write_integer(self.integer_value()?);
+        } else if self.is_tagged_float() {
+            hasher.write_float(self.float_value()?);
         } else {
             let value_ref = self.get();
 
@@ -577,6 +678,7 @@ impl ObjectPointer {
                 ObjectValue::Float(val) => hasher.write_float(val),
                 ObjectValue::Integer(val) => hasher.write_integer(val),
                 ObjectValue::BigInt(ref val) => hasher.write_bigint(val),
+                ObjectValue::Decimal(ref val) => hasher.write_decimal(val),
                 ObjectValue::String(ref val) => hasher.write_string(val),
                 ObjectValue::InternedString(ref val) => {
                     hasher.write_string(val)
@@ -590,7 +692,24 @@ impl ObjectPointer {
         Ok(())
     }
 
-    def_value_getter!(float_value, get, as_float, f64);
+    pub fn float_value(&self) -> Result<f64, String> {
+        if self.is_tagged_float() {
+            let encoded = self.raw.raw as usize as u64 >> FLOAT_SHIFT;
+            let rotated = if encoded <= 1 {
+                encoded
+            } else {
+                encoded + (FLOAT_EXPONENT_OFFSET << FLOAT_EXPONENT_SHIFT)
+            };
+
+            Ok(f64::from_bits(rotated.rotate_right(1)))
+        } else if self.is_tagged_integer() {
+            Err("ObjectPointer::as_float() called on a tagged value"
+                .to_string())
+        } else {
+            self.get().value.as_float()
+        }
+    }
+
     def_value_getter!(string_value, get, as_string, &ImmutableString);
 
     def_value_getter!(array_value, get, as_array, &Vec<ObjectPointer>);
@@ -607,6 +726,8 @@ impl ObjectPointer {
     def_value_getter!(block_value, get, as_block, &Block);
     def_value_getter!(binding_value, get, as_binding, RcBinding);
     def_value_getter!(bigint_value, get, as_bigint, &BigInt);
+    def_value_getter!(decimal_value, get, as_decimal, &Decimal);
+    def_value_getter!(regex_value, get, as_regex, &Regex);
     def_value_getter!(hasher_value_mut, get_mut, as_hasher_mut, &mut Hasher);
     def_value_getter!(hasher_value, get, as_hasher, &Hasher);
 
@@ -1146,6 +1267,60 @@ mod tests {
         assert_eq!(invalid.integer_value().unwrap(), MAX_INTEGER);
     }
 
+    #[test]
+    fn test_object_pointer_float_too_large() {
+        assert_eq!(ObjectPointer::float_too_large(f64::NAN), true);
+        assert_eq!(ObjectPointer::float_too_large(f64::INFINITY), true);
+        assert_eq!(ObjectPointer::float_too_large(f64::MAX), true);
+        assert_eq!(ObjectPointer::float_too_large(f64::MIN_POSITIVE), true);
+        assert_eq!(ObjectPointer::float_too_large(1.0e-100), true);
+        assert_eq!(ObjectPointer::float_too_large(0.0), false);
+        assert_eq!(ObjectPointer::float_too_large(-0.0), false);
+        assert_eq!(ObjectPointer::float_too_large(-1.5), false);
+        assert_eq!(ObjectPointer::float_too_large(1.0e70), false);
+        assert_eq!(ObjectPointer::float_too_large(1.0e-70), false);
+    }
+
+    #[test]
+    fn test_object_pointer_float_value() {
+        let floats = [0.0, -0.0, 1.0, -1.5, 0.1 + 0.2, 1.0e70, -1.0e-70];
+
+        for float in &floats {
+            let pointer = ObjectPointer::float(*float);
+
+            assert_eq!(
+                pointer.float_value().unwrap().to_bits(),
+                float.to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn test_object_pointer_is_tagged_float() {
+        let float = ObjectPointer::float(1.5);
+        let integer = ObjectPointer::integer(1);
+
+        assert!(float.is_tagged());
+        assert!(float.is_tagged_float());
+        assert!(float.is_float());
+        assert_eq!(float.is_tagged_integer(), false);
+        assert_eq!(float.is_integer(), false);
+        assert_eq!(float.is_bigint(), false);
+        assert!(float.integer_value().is_err());
+
+        assert!(integer.is_tagged());
+        assert_eq!(integer.is_tagged_float(), false);
+        assert_eq!(integer.is_float(), false);
+    }
+
+    #[test]
+    fn test_object_pointer_prototype_with_float() {
+        let state = State::with_rc(Config::new(), &[]);
+        let proto = ObjectPointer::float(1.5).prototype(&state);
+
+        assert!(proto == Some(state.float_prototype));
+    }
+
     #[test]
     fn test_object_pointer_lookup_attribute_with_integer() {
         let state = State::with_rc(Config::new(), &[]);
@@ -6,6 +6,7 @@
 use crate::arc_without_weak::ArcWithoutWeak;
 use crate::binding::RcBinding;
 use crate::block::Block;
+use crate::decimal::Decimal;
 use crate::ffi::{Library, Pointer, RcFunction};
 use crate::file::File;
 use crate::hasher::Hasher;
@@ -15,6 +16,7 @@ use crate::object_pointer::ObjectPointer;
 use crate::process::RcProcess;
 use crate::socket::Socket;
 use num_bigint::BigInt;
+use regex::Regex;
 use std::mem;
 
 /// Enum for storing different values in an Object.
@@ -42,6 +44,9 @@ pub enum ObjectValue {
     /// too small for a BigInt.
     Integer(i64),
 
+    /// An arbitrary precision decimal number.
+    Decimal(Box<Decimal>),
+
     /// A heap allocator hasher used for hashing objects.
     Hasher(Box<Hasher>),
 
@@ -65,6 +70,9 @@ pub enum ObjectValue {
 
     /// An Inko module.
     Module(ArcWithoutWeak<Module>),
+
+    /// A compiled regular expression.
+    Regex(Box<Regex>),
 }
 
 impl ObjectValue {
@@ -225,6 +233,20 @@ impl ObjectValue {
         }
     }
 
+    pub fn is_regex(&self) -> bool {
+        match *self {
+            ObjectValue::Regex(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_decimal(&self) -> bool {
+        match *self {
+            ObjectValue::Decimal(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn as_binding(&self) -> Result<RcBinding, String> {
         match *self {
             ObjectValue::Binding(ref val) => Ok(val.clone()),
@@ -243,6 +265,25 @@ impl ObjectValue {
         }
     }
 
+    pub fn as_regex(&self) -> Result<&Regex, String> {
+        match *self {
+            ObjectValue::Regex(ref val) => Ok(val),
+            _ => {
+                Err("ObjectValue::as_regex() called on a non Regex".to_string())
+            }
+        }
+    }
+
+    pub fn as_decimal(&self) -> Result<&Decimal, String> {
+        match *self {
+            ObjectValue::Decimal(ref val) => Ok(val),
+            _ => {
+                Err("ObjectValue::as_decimal() called on a non Decimal"
+                    .to_string())
+            }
+        }
+    }
+
     pub fn as_integer(&self) -> Result<i64, String> {
         match *self {
             ObjectValue::Integer(val) => Ok(val),
@@ -365,6 +406,7 @@ impl ObjectValue {
             | ObjectValue::Integer(_)
             | ObjectValue::String(_)
             | ObjectValue::BigInt(_)
+            | ObjectValue::Decimal(_)
             | ObjectValue::InternedString(_) => true,
             _ => false,
         }
@@ -381,6 +423,7 @@ impl ObjectValue {
             ObjectValue::Binding(_) => "Binding",
             ObjectValue::BigInt(_) => "BigInteger",
             ObjectValue::Integer(_) => "Integer",
+            ObjectValue::Decimal(_) => "Decimal",
             ObjectValue::Hasher(_) => "Hasher",
             ObjectValue::ByteArray(_) => "ByteArray",
             ObjectValue::Library(_) => "Library",
@@ -389,6 +432,7 @@ impl ObjectValue {
             ObjectValue::Process(_) => "Process",
             ObjectValue::Socket(_) => "Socket",
             ObjectValue::Module(_) => "Module",
+            ObjectValue::Regex(_) => "Regex",
         }
     }
 
@@ -446,6 +490,14 @@ pub fn integer(value: i64) -> ObjectValue {
     ObjectValue::Integer(value)
 }
 
+pub fn decimal(value: Decimal) -> ObjectValue {
+    ObjectValue::Decimal(Box::new(value))
+}
+
+pub fn regex(value: Regex) -> ObjectValue {
+    ObjectValue::Regex(Box::new(value))
+}
+
 pub fn hasher(value: Hasher) -> ObjectValue {
     ObjectValue::Hasher(Box::new(value))
 }
@@ -0,0 +1,406 @@
+//! Optimising the instructions of compiled code objects.
+//!
+//! After parsing a compiled code object, its instructions are optimised using a
+//! simple peephole optimiser. Common sequences of instructions are fused into a
+//! single instruction (known as a "superinstruction"), reducing the number of
+//! instructions the interpreter has to dispatch. Jumps to other jumps are
+//! replaced with jumps to their final target, and jumps to the next instruction
+//! are removed.
+//!
+//! Superinstructions write to all the registers written to by the instructions
+//! they replace. This means the remaining instructions don't need to be aware
+//! of any instructions being fused.
+//!
+//! Instructions are only fused when they originate from the same line, and when
+//! the second instruction isn't the target of a jump or the start or end of a
+//! catch table entry. Once all instructions are optimised, the jump targets and
+//! catch table entries are updated to use the new instruction positions.
+use crate::catch_table::CatchTable;
+use crate::vm::instruction::{Instruction, Opcode};
+use std::mem;
+
+/// The comparisons supported by the `IntegerCompareGoto` instruction.
+pub const COMPARE_SMALLER: u16 = 0;
+pub const COMPARE_GREATER: u16 = 1;
+pub const COMPARE_EQUALS: u16 = 2;
+pub const COMPARE_GREATER_OR_EQUAL: u16 = 3;
+pub const COMPARE_SMALLER_OR_EQUAL: u16 = 4;
+
+/// Optimises the instructions of a compiled code object.
+pub fn optimise(
+    instructions: &mut Vec<Instruction>,
+    catch_table: &mut CatchTable,
+) {
+    thread_jumps(instructions);
+
+    let targets = jump_targets(instructions, catch_table);
+    let mut old = mem::take(instructions).into_iter().enumerate().peekable();
+
+    // The (sorted) positions of all the instructions that have been removed.
+    let mut removed = Vec::new();
+
+    while let Some((index, instruction)) = old.next() {
+        if instruction.opcode == Opcode::Goto
+            && instruction.arg(0) as usize == index + 1
+        {
+            removed.push(index);
+            continue;
+        }
+
+        let fused = old.peek().and_then(|(next_index, next)| {
+            if targets[*next_index] {
+                None
+            } else {
+                fuse(&instruction, next)
+            }
+        });
+
+        if let Some(fused) = fused {
+            let (next_index, _) = old.next().unwrap();
+
+            removed.push(next_index);
+            instructions.push(fused);
+        } else {
+            instructions.push(instruction);
+        }
+    }
+
+    if removed.is_empty() {
+        return;
+    }
+
+    for instruction in instructions.iter_mut() {
+        if let Some(argument) = jump_argument(&instruction.opcode) {
+            let target = instruction.arg(argument) as usize;
+
+            instruction
+                .set_arg(argument, new_position(&removed, target) as u16);
+        }
+    }
+
+    for entry in &mut catch_table.entries {
+        entry.start = new_position(&removed, entry.start);
+        entry.end = new_position(&removed, entry.end);
+        entry.jump_to = new_position(&removed, entry.jump_to);
+    }
+}
+
+/// Replaces jumps to unconditional jumps with jumps to their final target.
+fn thread_jumps(instructions: &mut [Instruction]) {
+    for index in 0..instructions.len() {
+        let argument = match jump_argument(&instructions[index].opcode) {
+            Some(argument) => argument,
+            None => continue,
+        };
+
+        let mut target = instructions[index].arg(argument);
+
+        // The number of steps is limited, as jumps may form a cycle.
+        for _ in 0..instructions.len() {
+            match instructions.get(target as usize) {
+                Some(next) if next.opcode == Opcode::Goto => {
+                    target = next.arg(0);
+                }
+                _ => break,
+            }
+        }
+
+        instructions[index].set_arg(argument, target);
+    }
+}
+
+/// Returns a list of flags indicating what instruction positions we may jump
+/// to, or are used by the catch table.
+fn jump_targets(
+    instructions: &[Instruction],
+    catch_table: &CatchTable,
+) -> Vec<bool> {
+    // Positions may point to the end of the list of instructions, hence the +1.
+    let mut targets = vec![false; instructions.len() + 1];
+    let mut mark = |position: usize| {
+        if let Some(target) = targets.get_mut(position) {
+            *target = true;
+        }
+    };
+
+    for instruction in instructions {
+        if let Some(argument) = jump_argument(&instruction.opcode) {
+            mark(instruction.arg(argument) as usize);
+        }
+    }
+
+    for entry in &catch_table.entries {
+        mark(entry.start);
+        mark(entry.end);
+        mark(entry.jump_to);
+    }
+
+    targets
+}
+
+/// Returns the index of the argument that stores the jump target of an
+/// instruction.
+fn jump_argument(opcode: &Opcode) -> Option<usize> {
+    match opcode {
+        Opcode::Goto | Opcode::GotoIfTrue | Opcode::GotoIfFalse => Some(0),
+        Opcode::IntegerCompareGoto => Some(3),
+        _ => None,
+    }
+}
+
+/// Returns the new position of an instruction, based on the positions of all
+/// removed instructions.
+fn new_position(removed: &[usize], position: usize) -> usize {
+    match removed.binary_search(&position) {
+        Ok(amount) | Err(amount) => position - amount,
+    }
+}
+
+/// Returns the comparison to use for an `IntegerCompareGoto` instruction.
+fn comparison(opcode: &Opcode) -> Option<u16> {
+    match opcode {
+        Opcode::IntegerSmaller => Some(COMPARE_SMALLER),
+        Opcode::IntegerGreater => Some(COMPARE_GREATER),
+        Opcode::IntegerEquals => Some(COMPARE_EQUALS),
+        Opcode::IntegerGreaterOrEqual => Some(COMPARE_GREATER_OR_EQUAL),
+        Opcode::IntegerSmallerOrEqual => Some(COMPARE_SMALLER_OR_EQUAL),
+        _ => None,
+    }
+}
+
+/// Fuses two instructions into a single instruction, if possible.
+fn fuse(first: &Instruction, second: &Instruction) -> Option<Instruction> {
+    if first.line != second.line {
+        return None;
+    }
+
+    let (opcode, arguments) = match (&first.opcode, &second.opcode) {
+        (Opcode::GetLocal, Opcode::GetAttribute)
+            if second.arg(1) == first.arg(0) =>
+        {
+            // The arguments are the same as those of GetAttribute, followed by
+            // the index of the local variable. The inline cache is assigned
+            // after optimising the instructions.
+            (
+                Opcode::GetLocalAttribute,
+                [
+                    second.arg(0),
+                    first.arg(0),
+                    second.arg(2),
+                    0,
+                    first.arg(1),
+                    0,
+                ],
+            )
+        }
+        (Opcode::SetLiteral, Opcode::IntegerAdd)
+            if second.arg(2) == first.arg(0) =>
+        {
+            // The arguments are the same as those of IntegerAdd, followed by
+            // the index of the literal to store in the third register.
+            (
+                Opcode::IntegerAddLiteral,
+                [
+                    second.arg(0),
+                    second.arg(1),
+                    first.arg(0),
+                    first.arg(1),
+                    0,
+                    0,
+                ],
+            )
+        }
+        (_, Opcode::GotoIfTrue) | (_, Opcode::GotoIfFalse)
+            if second.arg(1) == first.arg(0) =>
+        {
+            let jump_if_true = if second.opcode == Opcode::GotoIfTrue {
+                1
+            } else {
+                0
+            };
+
+            // The arguments are the same as those of the comparison, followed
+            // by the jump target, the type of comparison, and a flag that
+            // indicates if we should jump when the comparison is true.
+            (
+                Opcode::IntegerCompareGoto,
+                [
+                    first.arg(0),
+                    first.arg(1),
+                    first.arg(2),
+                    second.arg(0),
+                    comparison(&first.opcode)?,
+                    jump_if_true,
+                ],
+            )
+        }
+        _ => return None,
+    };
+
+    Some(Instruction::new(opcode, arguments, first.line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catch_table::CatchEntry;
+
+    fn instruction(opcode: Opcode, arguments: [u16; 6]) -> Instruction {
+        Instruction::new(opcode, arguments, 1)
+    }
+
+    fn optimised(mut instructions: Vec<Instruction>) -> Vec<Instruction> {
+        optimise(&mut instructions, &mut CatchTable::new());
+        instructions
+    }
+
+    #[test]
+    fn test_fuse_get_local_and_get_attribute() {
+        let instructions = optimised(vec![
+            instruction(Opcode::GetLocal, [1, 2, 0, 0, 0, 0]),
+            instruction(Opcode::GetAttribute, [3, 1, 4, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].opcode, Opcode::GetLocalAttribute);
+        assert_eq!(instructions[0].arg(0), 3);
+        assert_eq!(instructions[0].arg(1), 1);
+        assert_eq!(instructions[0].arg(2), 4);
+        assert_eq!(instructions[0].arg(4), 2);
+    }
+
+    #[test]
+    fn test_fuse_set_literal_and_integer_add() {
+        let instructions = optimised(vec![
+            instruction(Opcode::SetLiteral, [1, 5, 0, 0, 0, 0]),
+            instruction(Opcode::IntegerAdd, [2, 0, 1, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].opcode, Opcode::IntegerAddLiteral);
+        assert_eq!(instructions[0].arg(0), 2);
+        assert_eq!(instructions[0].arg(1), 0);
+        assert_eq!(instructions[0].arg(2), 1);
+        assert_eq!(instructions[0].arg(3), 5);
+    }
+
+    #[test]
+    fn test_fuse_integer_comparison_and_goto() {
+        let instructions = optimised(vec![
+            instruction(Opcode::IntegerSmaller, [2, 0, 1, 0, 0, 0]),
+            instruction(Opcode::GotoIfFalse, [4, 2, 0, 0, 0, 0]),
+            instruction(Opcode::IntegerEquals, [2, 0, 1, 0, 0, 0]),
+            instruction(Opcode::GotoIfTrue, [0, 2, 0, 0, 0, 0]),
+            instruction(Opcode::Return, [0, 0, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 3);
+
+        assert_eq!(instructions[0].opcode, Opcode::IntegerCompareGoto);
+        assert_eq!(instructions[0].arg(0), 2);
+        assert_eq!(instructions[0].arg(3), 2);
+        assert_eq!(instructions[0].arg(4), COMPARE_SMALLER);
+        assert_eq!(instructions[0].arg(5), 0);
+
+        assert_eq!(instructions[1].opcode, Opcode::IntegerCompareGoto);
+        assert_eq!(instructions[1].arg(3), 0);
+        assert_eq!(instructions[1].arg(4), COMPARE_EQUALS);
+        assert_eq!(instructions[1].arg(5), 1);
+    }
+
+    #[test]
+    fn test_fuse_with_different_registers() {
+        let instructions = optimised(vec![
+            instruction(Opcode::GetLocal, [1, 2, 0, 0, 0, 0]),
+            instruction(Opcode::GetAttribute, [3, 5, 4, 0, 0, 0]),
+            instruction(Opcode::IntegerSmaller, [2, 0, 1, 0, 0, 0]),
+            instruction(Opcode::GotoIfTrue, [0, 3, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 4);
+    }
+
+    #[test]
+    fn test_fuse_with_different_lines() {
+        let instructions = optimised(vec![
+            Instruction::new(Opcode::GetLocal, [1, 2, 0, 0, 0, 0], 1),
+            Instruction::new(Opcode::GetAttribute, [3, 1, 4, 0, 0, 0], 2),
+        ]);
+
+        assert_eq!(instructions.len(), 2);
+    }
+
+    #[test]
+    fn test_fuse_with_jump_target() {
+        let instructions = optimised(vec![
+            instruction(Opcode::GetLocal, [1, 2, 0, 0, 0, 0]),
+            instruction(Opcode::GetAttribute, [3, 1, 4, 0, 0, 0]),
+            instruction(Opcode::GotoIfTrue, [1, 3, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0].opcode, Opcode::GetLocal);
+    }
+
+    #[test]
+    fn test_remove_goto_to_next_instruction() {
+        let instructions = optimised(vec![
+            instruction(Opcode::Goto, [1, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Return, [0, 0, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].opcode, Opcode::Return);
+    }
+
+    #[test]
+    fn test_thread_jumps() {
+        let instructions = optimised(vec![
+            instruction(Opcode::GotoIfTrue, [2, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Return, [0, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Goto, [4, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Goto, [2, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Return, [0, 0, 0, 0, 0, 0]),
+        ]);
+
+        // The last Goto jumps to the next instruction after threading the
+        // jumps, so it's removed.
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[0].arg(0), 3);
+        assert_eq!(instructions[2].arg(0), 3);
+    }
+
+    #[test]
+    fn test_thread_jumps_with_cycle() {
+        let instructions = optimised(vec![
+            instruction(Opcode::Goto, [1, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Goto, [0, 0, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].arg(0), 0);
+    }
+
+    #[test]
+    fn test_update_positions() {
+        let mut table = CatchTable::new();
+        let mut instructions = vec![
+            instruction(Opcode::GetLocal, [1, 2, 0, 0, 0, 0]),
+            instruction(Opcode::GetAttribute, [3, 1, 4, 0, 0, 0]),
+            instruction(Opcode::SetLiteral, [1, 5, 0, 0, 0, 0]),
+            instruction(Opcode::IntegerAdd, [2, 0, 1, 0, 0, 0]),
+            instruction(Opcode::Goto, [2, 0, 0, 0, 0, 0]),
+            instruction(Opcode::Return, [0, 0, 0, 0, 0, 0]),
+        ];
+
+        table.entries.push(CatchEntry::new(2, 5, 6));
+        optimise(&mut instructions, &mut table);
+
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[2].opcode, Opcode::Goto);
+        assert_eq!(instructions[2].arg(0), 1);
+        assert_eq!(table.entries[0].start, 1);
+        assert_eq!(table.entries[0].end, 3);
+        assert_eq!(table.entries[0].jump_to, 4);
+    }
+}
@@ -9,26 +9,51 @@ use crate::immix::local_allocator::LocalAllocator;
 use crate::mailbox::Mailbox;
 use crate::object_pointer::{ObjectPointer, ObjectPointerPointer};
 use crate::object_value;
-use crate::process_status::ProcessStatus;
+use crate::process_status::{ProcessState, ProcessStatus};
+use crate::scheduler::priority::{Prioritize, Priority};
 use crate::scheduler::timeouts::Timeout;
 use crate::tagged_pointer::{self, TaggedPointer};
 use crate::vm::state::State;
 use num_bigint::BigInt;
 use num_traits::FromPrimitive;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 use std::cell::UnsafeCell;
 use std::i64;
 use std::mem;
 use std::ops::Drop;
 use std::panic::RefUnwindSafe;
 use std::ptr;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{
+    AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+};
+use std::time::Duration;
 
 pub type RcProcess = ArcWithoutWeak<Process>;
 
 /// The bit that is set to mark a process as being suspended.
 const SUSPENDED_BIT: usize = 0;
 
+/// The bit that is set to mark a suspended process as waiting for a socket to
+/// become ready.
+const IO_BIT: usize = 1;
+
+/// The number of times suspended processes have been rescheduled.
+///
+/// This counter is shared by all processes, and is used to detect deadlocks:
+/// if the counter doesn't change while inspecting all processes, none of the
+/// inspected processes were rescheduled during the inspection.
+static WAKE_UPS: AtomicUsize = AtomicUsize::new(0);
+
+/// The process is running normally.
+const NOT_TERMINATING: u8 = 0;
+
+/// Another process requested the process to terminate, but the process has yet
+/// to start unwinding its stack.
+const TERMINATION_REQUESTED: u8 = 1;
+
+/// The process is running its deferred blocks before terminating.
+const TERMINATION_UNWINDING: u8 = 2;
+
 /// An enum describing what rights a thread was given when trying to reschedule
 /// a process.
 pub enum RescheduleRights {
@@ -59,6 +84,56 @@ impl RescheduleRights {
     }
 }
 
+/// A limit on the number of reductions a process can perform.
+pub struct ReductionBudget {
+    /// The number of reductions the process is allowed to perform.
+    pub reductions: u64,
+
+    /// The total number of reductions after which the budget is exceeded.
+    pub limit: u64,
+
+    /// The process to notify when the budget is exceeded.
+    ///
+    /// If no process is given, the process exceeding its budget panics.
+    pub watcher: Option<RcProcess>,
+}
+
+/// A snapshot of the state of a process.
+///
+/// The data of a process is gathered without suspending the process, meaning
+/// it may be out of date by the time it is used.
+pub struct ProcessInfo {
+    /// The unique identifier of the process.
+    pub identifier: usize,
+
+    /// The state the process is in.
+    pub state: ProcessState,
+
+    /// The number of messages that have yet to be received.
+    pub mailbox_length: usize,
+
+    /// The number of blocks in the young generation.
+    ///
+    /// This is only available for suspended processes.
+    pub young_blocks: Option<usize>,
+
+    /// The number of blocks in the mature generation.
+    ///
+    /// This is only available for suspended processes.
+    pub mature_blocks: Option<usize>,
+
+    /// The file path and line number of the code the process is executing.
+    ///
+    /// This is only available for suspended processes.
+    pub location: Option<(ObjectPointer, u16)>,
+
+    /// The total number of reductions performed.
+    pub reductions: u64,
+
+    /// The total time spent running the process.
+    pub cpu_time: Duration,
+}
+
 pub struct LocalData {
     /// The process-local memory allocator.
     pub allocator: LocalAllocator,
@@ -75,6 +150,13 @@ pub struct LocalData {
     /// The current execution context of this process.
     pub context: Box<ExecutionContext>,
 
+    /// The number of execution contexts of this process.
+    call_depth: usize,
+
+    /// The maximum number of execution contexts this process can create by
+    /// running blocks.
+    max_call_depth: usize,
+
     /// The ID of the thread this process is pinned to.
     pub thread_id: Option<u8>,
 
@@ -97,6 +179,41 @@ pub struct Process {
     /// If the process is waiting for a message.
     waiting_for_message: AtomicBool,
 
+    /// The scheduling priority of the process.
+    priority: AtomicU8,
+
+    /// The termination state of the process, used when another process
+    /// requests this process to terminate.
+    termination: AtomicU8,
+
+    /// A boolean indicating the process should print its stack trace the next
+    /// time it runs.
+    stack_dump_requested: AtomicBool,
+
+    /// The total number of reductions performed by the process.
+    reductions: AtomicU64,
+
+    /// The total time (in nanoseconds) spent running the process.
+    cpu_time: AtomicU64,
+
+    /// The total number of reductions after which the reduction budget is
+    /// exceeded, or `u64::MAX` if the process has no budget.
+    ///
+    /// This value is stored separately from the budget, allowing us to check
+    /// the budget without having to acquire a lock.
+    reduction_limit: AtomicU64,
+
+    /// The reduction budget of the process, if any.
+    reduction_budget: Mutex<Option<ReductionBudget>>,
+
+    /// A lock that is held while the process is running or being garbage
+    /// collected.
+    ///
+    /// Other threads hold this lock while inspecting the call stack or heap of
+    /// the process, preventing the process from being rescheduled and
+    /// modifying this data during the inspection.
+    running: Mutex<()>,
+
     /// A marker indicating if a process is suspened, optionally including the
     /// pointer to the timeout.
     ///
@@ -121,6 +238,12 @@ unsafe impl Send for LocalData {}
 unsafe impl Sync for Process {}
 impl RefUnwindSafe for Process {}
 
+impl Prioritize for RcProcess {
+    fn priority(&self) -> Priority {
+        Process::priority(self)
+    }
+}
+
 impl Process {
     pub fn with_rc(
         context: ExecutionContext,
@@ -130,6 +253,8 @@ impl Process {
         let local_data = LocalData {
             allocator: LocalAllocator::new(global_allocator, config),
             context: Box::new(context),
+            call_depth: 1,
+            max_call_depth: config.max_call_depth,
             panic_handler: ObjectPointer::null(),
             thread_id: None,
             mailbox: Mutex::new(Mailbox::new()),
@@ -140,6 +265,14 @@ impl Process {
         ArcWithoutWeak::new(Process {
             local_data: UnsafeCell::new(local_data),
             waiting_for_message: AtomicBool::new(false),
+            priority: AtomicU8::new(Priority::Normal as u8),
+            termination: AtomicU8::new(NOT_TERMINATING),
+            stack_dump_requested: AtomicBool::new(false),
+            reductions: AtomicU64::new(0),
+            cpu_time: AtomicU64::new(0),
+            reduction_limit: AtomicU64::new(u64::MAX),
+            reduction_budget: Mutex::new(None),
+            running: Mutex::new(()),
             suspended: TaggedPointer::null(),
         })
     }
@@ -178,6 +311,15 @@ impl Process {
         self.local_data().status.is_terminated()
     }
 
+    pub fn priority(&self) -> Priority {
+        Priority::from_u8(self.priority.load(Ordering::Acquire))
+            .unwrap_or(Priority::Normal)
+    }
+
+    pub fn set_priority(&self, priority: Priority) {
+        self.priority.store(priority as u8, Ordering::Release);
+    }
+
     pub fn thread_id(&self) -> Option<u8> {
         self.local_data().thread_id
     }
@@ -208,6 +350,82 @@ impl Process {
         self.suspended.atomic_store(tagged);
     }
 
+    /// Suspends the process until a socket it is waiting for becomes ready.
+    ///
+    /// Processes suspended this way can only be rescheduled using
+    /// `acquire_io_rescheduling_rights()`, ensuring that sending a message
+    /// doesn't reschedule the process before the socket is ready.
+    pub fn suspend_for_io(&self) {
+        let pointer = ptr::null_mut();
+        let tagged = tagged_pointer::with_bit(
+            tagged_pointer::with_bit(pointer, SUSPENDED_BIT),
+            IO_BIT,
+        );
+
+        self.suspended.atomic_store(tagged);
+    }
+
+    /// Acquires the lock that must be held while running the process.
+    pub fn lock_running(&self) -> MutexGuard<'_, ()> {
+        self.running.lock()
+    }
+
+    /// Acquires the lock that must be held while running the process, without
+    /// blocking.
+    ///
+    /// If the process is running, `None` is returned.
+    pub fn try_lock_running(&self) -> Option<MutexGuard<'_, ()>> {
+        self.running.try_lock()
+    }
+
+    /// Returns the number of execution contexts of this process.
+    pub fn call_depth(&self) -> usize {
+        self.local_data().call_depth
+    }
+
+    pub fn max_call_depth(&self) -> usize {
+        self.local_data().max_call_depth
+    }
+
+    /// Returns true if running another block would exceed the maximum call
+    /// depth.
+    pub fn call_depth_exceeded(&self) -> bool {
+        let local_data = self.local_data();
+
+        local_data.call_depth >= local_data.max_call_depth
+    }
+
+    /// Increases the maximum call depth, allowing a panic handler to run when
+    /// the call depth was exceeded.
+    pub fn increase_max_call_depth(&self, amount: usize) {
+        self.local_data_mut().max_call_depth += amount;
+    }
+
+    /// Returns the state this process is in.
+    pub fn state(&self) -> ProcessState {
+        if self.is_terminated() {
+            return ProcessState::Terminated;
+        }
+
+        let suspended = self.suspended.atomic_load();
+
+        if !suspended.is_null() {
+            if tagged_pointer::bit_is_set(suspended, IO_BIT) {
+                ProcessState::WaitingForIo
+            } else if self.is_waiting_for_message() {
+                ProcessState::WaitingForMessage
+            } else {
+                ProcessState::Suspended
+            }
+        } else if self.is_blocking() {
+            ProcessState::Blocking
+        } else if self.is_pinned() {
+            ProcessState::Pinned
+        } else {
+            ProcessState::Running
+        }
+    }
+
     pub fn is_suspended_with_timeout(
         &self,
         timeout: &ArcWithoutWeak<Timeout>,
@@ -221,9 +439,16 @@ impl Process {
     pub fn acquire_rescheduling_rights(&self) -> RescheduleRights {
         let current = self.suspended.atomic_load();
 
-        if current.is_null() {
-            RescheduleRights::Failed
-        } else if self.suspended.compare_and_swap(current, ptr::null_mut()) {
+        if current.is_null() || tagged_pointer::bit_is_set(current, IO_BIT) {
+            return RescheduleRights::Failed;
+        }
+
+        // The counter _must_ be incremented before the process is no longer
+        // suspended, otherwise the deadlock detector may not notice the
+        // process waking up.
+        WAKE_UPS.fetch_add(1, Ordering::SeqCst);
+
+        if self.suspended.compare_and_swap(current, ptr::null_mut()) {
             let untagged = tagged_pointer::untagged(current);
 
             if untagged.is_null() {
@@ -238,6 +463,89 @@ impl Process {
         }
     }
 
+    /// Attempts to acquire the rights to reschedule a process waiting for a
+    /// socket to become ready.
+    pub fn acquire_io_rescheduling_rights(&self) -> bool {
+        let current = self.suspended.atomic_load();
+
+        tagged_pointer::bit_is_set(current, IO_BIT)
+            && self.suspended.compare_and_swap(current, ptr::null_mut())
+    }
+
+    /// Requests the process to terminate, optionally using a reason.
+    ///
+    /// The returned boolean is `true` if this is the first request to
+    /// terminate the process.
+    pub fn request_termination(&self, reason_to_copy: ObjectPointer) -> bool {
+        let local_data = self.local_data_mut();
+        let mut mailbox = local_data.mailbox.lock();
+
+        if self.is_terminated() {
+            return false;
+        }
+
+        if self
+            .termination
+            .compare_exchange(
+                NOT_TERMINATING,
+                TERMINATION_REQUESTED,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        if !reason_to_copy.is_null() {
+            mailbox.set_termination_reason(
+                local_data.allocator.copy_object(reason_to_copy),
+            );
+        }
+
+        true
+    }
+
+    /// Returns true if another process requested this process to terminate.
+    pub fn is_terminating(&self) -> bool {
+        self.termination.load(Ordering::SeqCst) != NOT_TERMINATING
+    }
+
+    /// Returns true if the process has to be woken up so it can start
+    /// unwinding its stack.
+    pub fn is_termination_requested(&self) -> bool {
+        self.termination.load(Ordering::SeqCst) == TERMINATION_REQUESTED
+    }
+
+    /// Returns true if the process has to start unwinding its stack, marking
+    /// the process as unwinding in the process.
+    pub fn start_unwinding_for_termination(&self) -> bool {
+        self.termination
+            .compare_exchange(
+                TERMINATION_REQUESTED,
+                TERMINATION_UNWINDING,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .is_ok()
+    }
+
+    /// Requests the process to print its stack trace the next time it runs.
+    pub fn request_stack_dump(&self) {
+        self.stack_dump_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if a stack dump was requested, resetting the request.
+    pub fn take_stack_dump_request(&self) -> bool {
+        self.stack_dump_requested.load(Ordering::Acquire)
+            && self.stack_dump_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Returns the reason the process is terminating, if any.
+    pub fn termination_reason(&self) -> Option<ObjectPointer> {
+        self.local_data().mailbox.lock().termination_reason()
+    }
+
     #[cfg_attr(feature = "cargo-clippy", allow(mut_from_ref))]
     pub fn local_data_mut(&self) -> &mut LocalData {
         unsafe { &mut *self.local_data.get() }
@@ -255,6 +563,7 @@ impl Process {
         mem::swap(target, &mut boxed);
 
         target.set_parent(boxed);
+        local_data.call_depth += 1;
     }
 
     /// Pops an execution context.
@@ -266,6 +575,7 @@ impl Process {
 
         if let Some(parent) = local_data.context.parent.take() {
             local_data.context = parent;
+            local_data.call_depth -= 1;
 
             false
         } else {
@@ -297,6 +607,18 @@ impl Process {
         }
     }
 
+    pub fn allocate_f64(
+        &self,
+        value: f64,
+        prototype: ObjectPointer,
+    ) -> ObjectPointer {
+        if ObjectPointer::float_too_large(value) {
+            self.allocate(object_value::float(value), prototype)
+        } else {
+            ObjectPointer::float(value)
+        }
+    }
+
     pub fn allocate_u64(
         &self,
         value: u64,
@@ -381,6 +703,59 @@ impl Process {
         mailbox.send(local_data.allocator.copy_object(message_to_copy));
     }
 
+    /// Stores a copy of a message to send when the given timer expires.
+    pub fn add_timer_message(&self, id: usize, message_to_copy: ObjectPointer) {
+        let local_data = self.local_data_mut();
+        let mut mailbox = local_data.mailbox.lock();
+
+        if self.is_terminated() {
+            return;
+        }
+
+        mailbox
+            .add_timer(id, local_data.allocator.copy_object(message_to_copy));
+    }
+
+    /// Sends the message of an expired timer to this process.
+    ///
+    /// If `periodic` is true the message is kept around, and a copy is sent
+    /// instead. The returned boolean is `true` if a message was sent.
+    pub fn send_timer_message(&self, id: usize, periodic: bool) -> bool {
+        let local_data = self.local_data_mut();
+        let mut mailbox = local_data.mailbox.lock();
+
+        if self.is_terminated() {
+            return false;
+        }
+
+        let message = if periodic {
+            if let Some(message) = mailbox.timer_message(id) {
+                Some(local_data.allocator.copy_object(message))
+            } else {
+                None
+            }
+        } else {
+            mailbox.remove_timer(id)
+        };
+
+        if let Some(message) = message {
+            mailbox.send(message);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes the message of a timer, returning `true` if the timer still had
+    /// a message.
+    pub fn remove_timer_message(&self, id: usize) -> bool {
+        self.local_data_mut()
+            .mailbox
+            .lock()
+            .remove_timer(id)
+            .is_some()
+    }
+
     pub fn send_message_from_self(&self, message: ObjectPointer) {
         self.local_data_mut().mailbox.lock().send(message);
     }
@@ -533,6 +908,91 @@ impl Process {
         self.waiting_for_message.load(Ordering::Acquire)
     }
 
+    /// Returns true if the process is suspended until it receives a message,
+    /// without using a timeout.
+    pub fn is_waiting_for_message_without_timeout(&self) -> bool {
+        let current = self.suspended.atomic_load();
+
+        self.is_waiting_for_message()
+            && !current.is_null()
+            && !tagged_pointer::bit_is_set(current, IO_BIT)
+            && tagged_pointer::untagged(current).is_null()
+    }
+
+    /// Records a single reduction, returning `true` if the reduction budget of
+    /// the process has been exceeded.
+    pub fn reduce(&self) -> bool {
+        // Only the thread running the process updates this counter, so we
+        // don't need an atomic increment.
+        let reductions = self.reductions.load(Ordering::Relaxed) + 1;
+
+        self.reductions.store(reductions, Ordering::Relaxed);
+
+        reductions >= self.reduction_limit.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of reductions performed by this process.
+    pub fn reductions(&self) -> u64 {
+        self.reductions.load(Ordering::Relaxed)
+    }
+
+    /// Adds the time spent running the process to its total running time.
+    pub fn add_cpu_time(&self, duration: Duration) {
+        let nanos = duration.as_secs() * 1_000_000_000
+            + u64::from(duration.subsec_nanos());
+
+        self.cpu_time.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Returns the total time spent running this process.
+    pub fn cpu_time(&self) -> Duration {
+        Duration::from_nanos(self.cpu_time.load(Ordering::Relaxed))
+    }
+
+    /// Limits the number of reductions this process can perform from now on.
+    ///
+    /// Passing `None` as the budget removes any existing budget.
+    pub fn set_reduction_budget(
+        &self,
+        budget: Option<u64>,
+        watcher: Option<RcProcess>,
+    ) {
+        let mut current = self.reduction_budget.lock();
+
+        if let Some(budget) = budget {
+            let limit = self.reductions().saturating_add(budget);
+
+            *current = Some(ReductionBudget {
+                reductions: budget,
+                limit,
+                watcher,
+            });
+
+            self.reduction_limit.store(limit, Ordering::Relaxed);
+        } else {
+            *current = None;
+
+            self.reduction_limit.store(u64::MAX, Ordering::Relaxed);
+        }
+    }
+
+    /// Removes and returns the reduction budget, if it has been exceeded.
+    pub fn take_exceeded_reduction_budget(&self) -> Option<ReductionBudget> {
+        let mut current = self.reduction_budget.lock();
+        match current.as_ref() {
+            Some(budget) if self.reductions() >= budget.limit => {}
+            _ => return None,
+        }
+
+        self.reduction_limit.store(u64::MAX, Ordering::Relaxed);
+        current.take()
+    }
+
+    /// Returns the number of times suspended processes have been rescheduled.
+    pub fn wake_ups() -> usize {
+        WAKE_UPS.load(Ordering::SeqCst)
+    }
+
     pub fn set_result(&self, result: ObjectPointer) {
         self.local_data_mut().result = result;
     }
@@ -560,6 +1020,49 @@ impl RcProcess {
     pub fn identifier(&self) -> usize {
         self.as_ptr() as usize
     }
+
+    /// Returns a snapshot of the state of this process.
+    pub fn info(&self) -> ProcessInfo {
+        let local_data = self.local_data();
+
+        // Holding the running lock prevents the process from being rescheduled
+        // while we inspect it, such as when a timeout expires.
+        let running = self.try_lock_running();
+
+        // Other processes may allocate messages into the heap of this process,
+        // which requires the mailbox lock. Acquiring the lock here ensures we
+        // can safely count the blocks of a suspended process.
+        let mailbox = local_data.mailbox.lock();
+        let state = self.state();
+        let mut info = ProcessInfo {
+            identifier: self.identifier(),
+            state,
+            mailbox_length: mailbox.len(),
+            young_blocks: None,
+            mature_blocks: None,
+            location: None,
+            reductions: self.reductions(),
+            cpu_time: self.cpu_time(),
+        };
+
+        // A process that isn't suspended may be modifying its heap or call
+        // stack, so we can't safely inspect these.
+        if state.is_suspended() && running.is_some() {
+            let allocator = &local_data.allocator;
+            let young = allocator
+                .young_generation
+                .iter()
+                .map(|bucket| bucket.blocks.len())
+                .sum();
+
+            info.young_blocks = Some(young);
+            info.mature_blocks = Some(allocator.mature_generation.blocks.len());
+            info.location =
+                Some((local_data.context.file(), local_data.context.line()));
+        }
+
+        info
+    }
 }
 
 impl PartialEq for RcProcess {
@@ -580,6 +1083,7 @@ mod tests {
     use std::i32;
     use std::i64;
     use std::mem;
+    use std::time::Duration;
 
     #[test]
     fn test_contexts() {
@@ -662,6 +1166,229 @@ mod tests {
         assert!(process.receive_message().is_none());
     }
 
+    #[test]
+    fn test_send_timer_message() {
+        let (_machine, _block, process) = setup();
+
+        process.add_timer_message(1, ObjectPointer::integer(5));
+
+        assert!(process.send_timer_message(1, false));
+        assert_eq!(process.send_timer_message(1, false), false);
+        assert!(process.receive_message() == Some(ObjectPointer::integer(5)));
+    }
+
+    #[test]
+    fn test_send_timer_message_periodically() {
+        let (_machine, _block, process) = setup();
+
+        process.add_timer_message(1, ObjectPointer::integer(5));
+
+        assert!(process.send_timer_message(1, true));
+        assert!(process.send_timer_message(1, true));
+        assert!(process.receive_message() == Some(ObjectPointer::integer(5)));
+        assert!(process.receive_message() == Some(ObjectPointer::integer(5)));
+    }
+
+    #[test]
+    fn test_remove_timer_message() {
+        let (_machine, _block, process) = setup();
+
+        process.add_timer_message(1, ObjectPointer::integer(5));
+
+        assert!(process.remove_timer_message(1));
+        assert_eq!(process.remove_timer_message(1), false);
+        assert_eq!(process.send_timer_message(1, false), false);
+    }
+
+    #[test]
+    fn test_suspend_for_io() {
+        let (_machine, _block, process) = setup();
+
+        process.suspend_for_io();
+
+        assert_eq!(process.acquire_rescheduling_rights().are_acquired(), false);
+        assert!(process.acquire_io_rescheduling_rights());
+        assert_eq!(process.acquire_io_rescheduling_rights(), false);
+    }
+
+    #[test]
+    fn test_acquire_io_rescheduling_rights_without_waiting_for_io() {
+        let (_machine, _block, process) = setup();
+
+        process.suspend_without_timeout();
+
+        assert_eq!(process.acquire_io_rescheduling_rights(), false);
+        assert!(process.acquire_rescheduling_rights().are_acquired());
+    }
+
+    #[test]
+    fn test_request_termination() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.is_terminating(), false);
+        assert!(process.request_termination(ObjectPointer::integer(5)));
+        assert_eq!(process.request_termination(ObjectPointer::null()), false);
+        assert!(process.is_terminating());
+        assert!(
+            process.termination_reason() == Some(ObjectPointer::integer(5))
+        );
+    }
+
+    #[test]
+    fn test_request_termination_of_terminated_process() {
+        let (_machine, _block, process) = setup();
+
+        process.set_terminated();
+
+        assert_eq!(process.request_termination(ObjectPointer::null()), false);
+        assert_eq!(process.is_terminating(), false);
+    }
+
+    #[test]
+    fn test_start_unwinding_for_termination() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.start_unwinding_for_termination(), false);
+
+        process.request_termination(ObjectPointer::null());
+
+        assert!(process.is_termination_requested());
+        assert!(process.start_unwinding_for_termination());
+        assert_eq!(process.start_unwinding_for_termination(), false);
+        assert_eq!(process.is_termination_requested(), false);
+        assert!(process.is_terminating());
+        assert!(process.termination_reason().is_none());
+    }
+
+    #[test]
+    fn test_is_waiting_for_message_without_timeout() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.is_waiting_for_message_without_timeout(), false);
+
+        process.waiting_for_message();
+        process.suspend_with_timeout(Timeout::with_rc(Duration::from_secs(1)));
+
+        assert_eq!(process.is_waiting_for_message_without_timeout(), false);
+
+        process.suspend_without_timeout();
+
+        assert!(process.is_waiting_for_message_without_timeout());
+    }
+
+    #[test]
+    fn test_wake_ups() {
+        let (_machine, _block, process) = setup();
+        let before = Process::wake_ups();
+
+        process.suspend_without_timeout();
+        process.acquire_rescheduling_rights();
+
+        assert!(Process::wake_ups() > before);
+    }
+
+    #[test]
+    fn test_call_depth() {
+        let (_machine, block, process) = setup();
+
+        assert_eq!(process.call_depth(), 1);
+
+        process.push_context(ExecutionContext::from_block(&block));
+
+        assert_eq!(process.call_depth(), 2);
+
+        process.pop_context();
+        process.pop_context();
+
+        assert_eq!(process.call_depth(), 1);
+    }
+
+    #[test]
+    fn test_call_depth_exceeded() {
+        let (_machine, block, process) = setup();
+
+        process.local_data_mut().max_call_depth = 2;
+
+        assert_eq!(process.call_depth_exceeded(), false);
+
+        process.push_context(ExecutionContext::from_block(&block));
+
+        assert!(process.call_depth_exceeded());
+
+        process.increase_max_call_depth(1);
+
+        assert_eq!(process.call_depth_exceeded(), false);
+    }
+
+    #[test]
+    fn test_take_stack_dump_request() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.take_stack_dump_request(), false);
+
+        process.request_stack_dump();
+
+        assert!(process.take_stack_dump_request());
+        assert_eq!(process.take_stack_dump_request(), false);
+    }
+
+    #[test]
+    fn test_reduce() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.reduce(), false);
+        assert_eq!(process.reductions(), 1);
+    }
+
+    #[test]
+    fn test_add_cpu_time() {
+        let (_machine, _block, process) = setup();
+
+        process.add_cpu_time(Duration::from_millis(5));
+        process.add_cpu_time(Duration::from_secs(1));
+
+        assert_eq!(process.cpu_time(), Duration::from_millis(1005));
+    }
+
+    #[test]
+    fn test_set_reduction_budget() {
+        let (_machine, _block, process) = setup();
+
+        process.reduce();
+        process.set_reduction_budget(Some(2), None);
+
+        assert_eq!(process.reduce(), false);
+        assert!(process.reduce());
+
+        let budget = process.take_exceeded_reduction_budget().unwrap();
+
+        assert_eq!(budget.reductions, 2);
+        assert_eq!(budget.limit, 3);
+        assert!(budget.watcher.is_none());
+        assert_eq!(process.reduce(), false);
+    }
+
+    #[test]
+    fn test_set_reduction_budget_without_budget() {
+        let (_machine, _block, process) = setup();
+
+        process.set_reduction_budget(Some(1), None);
+        process.set_reduction_budget(None, None);
+
+        assert_eq!(process.reduce(), false);
+        assert!(process.take_exceeded_reduction_budget().is_none());
+    }
+
+    #[test]
+    fn test_take_exceeded_reduction_budget_with_remaining_budget() {
+        let (_machine, _block, process) = setup();
+
+        process.set_reduction_budget(Some(2), None);
+        process.reduce();
+
+        assert!(process.take_exceeded_reduction_budget().is_none());
+    }
+
     #[test]
     fn test_allocate_f64_as_i64_with_a_small_float() {
         let (machine, _block, process) = setup();
@@ -730,11 +1457,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_allocate_f64_with_a_small_float() {
+        let (machine, _block, process) = setup();
+        let result = process.allocate_f64(1.5, machine.state.float_prototype);
+
+        assert!(result.is_tagged_float());
+        assert_eq!(result.float_value().unwrap(), 1.5);
+    }
+
+    #[test]
+    fn test_allocate_f64_with_a_large_float() {
+        let (machine, _block, process) = setup();
+        let result =
+            process.allocate_f64(f64::MAX, machine.state.float_prototype);
+
+        assert_eq!(result.is_tagged_float(), false);
+        assert_eq!(result.float_value().unwrap(), f64::MAX);
+    }
+
     #[test]
     fn test_process_type_size() {
         // This test is put in place to ensure the type size doesn't change
         // unintentionally.
-        assert_eq!(mem::size_of::<Process>(), 368);
+        assert_eq!(mem::size_of::<Process>(), 504);
     }
 
     #[test]
@@ -752,6 +1498,17 @@ mod tests {
         assert!(process.thread_id().is_none());
     }
 
+    #[test]
+    fn test_process_set_priority() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.priority(), Priority::Normal);
+
+        process.set_priority(Priority::High);
+
+        assert_eq!(process.priority(), Priority::High);
+    }
+
     #[test]
     fn test_identifier() {
         let (_machine, _block, process) = setup();
@@ -759,6 +1516,88 @@ mod tests {
         assert!(process.identifier() > 0);
     }
 
+    #[test]
+    fn test_state() {
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(process.state(), ProcessState::Running);
+
+        process.set_thread_id(1);
+
+        assert_eq!(process.state(), ProcessState::Pinned);
+
+        process.unset_thread_id();
+        process.set_blocking(true);
+
+        assert_eq!(process.state(), ProcessState::Blocking);
+
+        process.set_blocking(false);
+        process.suspend_with_timeout(Timeout::with_rc(Duration::from_secs(1)));
+
+        assert_eq!(process.state(), ProcessState::Suspended);
+
+        process.waiting_for_message();
+
+        assert_eq!(process.state(), ProcessState::WaitingForMessage);
+
+        process.no_longer_waiting_for_message();
+        process.suspend_for_io();
+
+        assert_eq!(process.state(), ProcessState::WaitingForIo);
+
+        process.set_terminated();
+
+        assert_eq!(process.state(), ProcessState::Terminated);
+    }
+
+    #[test]
+    fn test_info_of_running_process() {
+        let (_machine, _block, process) = setup();
+
+        process.send_message_from_self(ObjectPointer::integer(5));
+
+        let info = process.info();
+
+        assert_eq!(info.identifier, process.identifier());
+        assert_eq!(info.state, ProcessState::Running);
+        assert_eq!(info.mailbox_length, 1);
+        assert!(info.young_blocks.is_none());
+        assert!(info.mature_blocks.is_none());
+        assert!(info.location.is_none());
+    }
+
+    #[test]
+    fn test_info_of_suspended_process() {
+        let (_machine, _block, process) = setup();
+
+        process.allocate_empty();
+        process.waiting_for_message();
+        process.suspend_without_timeout();
+
+        let info = process.info();
+
+        assert_eq!(info.state, ProcessState::WaitingForMessage);
+        assert_eq!(info.young_blocks, Some(1));
+        assert_eq!(info.mature_blocks, Some(0));
+        assert!(info.location.is_some());
+        assert_eq!(info.location.unwrap().1, 1);
+    }
+
+    #[test]
+    fn test_info_of_suspended_process_that_is_still_running() {
+        let (_machine, _block, process) = setup();
+
+        process.suspend_without_timeout();
+
+        let _running = process.lock_running();
+        let info = process.info();
+
+        assert_eq!(info.state, ProcessState::Suspended);
+        assert!(info.young_blocks.is_none());
+        assert!(info.mature_blocks.is_none());
+        assert!(info.location.is_none());
+    }
+
     #[test]
     fn test_each_global_pointer() {
         let (_machine, _block, process) = setup();
@@ -0,0 +1,71 @@
+//! Tracking of all processes that are alive.
+//!
+//! Processes are added to the registry when they are allocated, and removed
+//! when they terminate. This allows inspecting all processes of a running
+//! program, without having to obtain them from the scheduler.
+use crate::process::RcProcess;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// A collection of all processes that are alive, mapped to their identifiers.
+#[derive(Default)]
+pub struct ProcessRegistry {
+    processes: Mutex<HashMap<usize, RcProcess>>,
+}
+
+impl ProcessRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a newly allocated process to the registry.
+    pub fn add(&self, process: RcProcess) {
+        self.processes.lock().insert(process.identifier(), process);
+    }
+
+    /// Removes a terminated process from the registry.
+    pub fn remove(&self, process: &RcProcess) {
+        self.processes.lock().remove(&process.identifier());
+    }
+
+    /// Returns all processes that are alive.
+    pub fn processes(&self) -> Vec<RcProcess> {
+        self.processes.lock().values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.processes.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::test::setup;
+
+    #[test]
+    fn test_add() {
+        let (_machine, _block, process) = setup();
+        let registry = ProcessRegistry::new();
+
+        registry.add(process.clone());
+
+        assert_eq!(registry.len(), 1);
+        assert!(registry.processes()[0] == process);
+    }
+
+    #[test]
+    fn test_remove() {
+        let (_machine, _block, process) = setup();
+        let registry = ProcessRegistry::new();
+
+        registry.add(process.clone());
+        registry.remove(&process);
+
+        assert!(registry.is_empty());
+    }
+}
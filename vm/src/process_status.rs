@@ -72,6 +72,55 @@ impl ProcessStatus {
     }
 }
 
+/// The state of a process, as observed by other processes.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum ProcessState {
+    /// The process is running, or is scheduled to run.
+    Running = 0,
+
+    /// The process is waiting for a message, optionally using a timeout.
+    WaitingForMessage = 1,
+
+    /// The process is suspended until a timeout expires.
+    Suspended = 2,
+
+    /// The process is waiting for a socket to become ready.
+    WaitingForIo = 3,
+
+    /// The process is running on, or scheduled for, the blocking pool.
+    Blocking = 4,
+
+    /// The process is pinned to an OS thread.
+    Pinned = 5,
+
+    /// The process has terminated.
+    Terminated = 6,
+}
+
+impl ProcessState {
+    /// Returns true if the process is suspended, meaning it won't modify its
+    /// heap or call stack until it is rescheduled.
+    pub fn is_suspended(self) -> bool {
+        self == ProcessState::WaitingForMessage
+            || self == ProcessState::Suspended
+            || self == ProcessState::WaitingForIo
+    }
+
+    /// Returns a human readable description of the state.
+    pub fn description(self) -> &'static str {
+        match self {
+            ProcessState::Running => "running",
+            ProcessState::WaitingForMessage => "waiting for a message",
+            ProcessState::Suspended => "suspended",
+            ProcessState::WaitingForIo => "waiting for IO",
+            ProcessState::Blocking => "performing a blocking operation",
+            ProcessState::Pinned => "pinned to a thread",
+            ProcessState::Terminated => "terminated",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,4 +170,24 @@ mod tests {
 
         assert!(status.is_terminated());
     }
+
+    #[test]
+    fn test_process_state_is_suspended() {
+        assert!(ProcessState::WaitingForMessage.is_suspended());
+        assert!(ProcessState::Suspended.is_suspended());
+        assert!(ProcessState::WaitingForIo.is_suspended());
+        assert_eq!(ProcessState::Running.is_suspended(), false);
+        assert_eq!(ProcessState::Blocking.is_suspended(), false);
+        assert_eq!(ProcessState::Pinned.is_suspended(), false);
+        assert_eq!(ProcessState::Terminated.is_suspended(), false);
+    }
+
+    #[test]
+    fn test_process_state_description() {
+        assert_eq!(ProcessState::Running.description(), "running");
+        assert_eq!(
+            ProcessState::WaitingForMessage.description(),
+            "waiting for a message"
+        );
+    }
 }
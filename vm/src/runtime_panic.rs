@@ -1,32 +1,275 @@
+use crate::execution_context::ExecutionContext;
 use crate::process::RcProcess;
+use crate::process_status::ProcessState;
+use std::str::FromStr;
+
+/// The number of outer most frames to show in a truncated stack trace.
+const STACKTRACE_HEAD_FRAMES: usize = 10;
+
+/// The number of inner most frames to show in a truncated stack trace.
+const STACKTRACE_TAIL_FRAMES: usize = 20;
+
+/// The maximum number of frames to show before truncating a stack trace.
+const MAX_STACKTRACE_FRAMES: usize =
+    STACKTRACE_HEAD_FRAMES + STACKTRACE_TAIL_FRAMES;
+
+/// The format to use for printing panics and stack traces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanicFormat {
+    /// A format meant to be read by humans.
+    Text,
+
+    /// A JSON object on a single line, meant to be parsed by other programs.
+    Json,
+}
+
+impl FromStr for PanicFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(PanicFormat::Text),
+            "json" => Ok(PanicFormat::Json),
+            _ => Err(format!("{} is not a valid panic format", value)),
+        }
+    }
+}
+
+/// The call frames to include in a stack trace.
+struct Stacktrace<'a> {
+    /// The frames to include and their indexes, with the most recent call
+    /// coming last.
+    frames: Vec<(usize, &'a ExecutionContext)>,
+
+    /// The number of frames that were omitted.
+    omitted: usize,
+}
+
+impl<'a> Stacktrace<'a> {
+    fn new(process: &'a RcProcess) -> Self {
+        let mut contexts: Vec<_> = process.context().contexts().collect();
+
+        contexts.reverse();
+
+        let total = contexts.len();
+
+        // Deeply nested calls (e.g. when exceeding the maximum call depth)
+        // would produce a huge trace, so we only show the outer and inner most
+        // frames.
+        let omitted = total.saturating_sub(MAX_STACKTRACE_FRAMES);
+        let frames = contexts
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                omitted == 0
+                    || *index < STACKTRACE_HEAD_FRAMES
+                    || *index >= total - STACKTRACE_TAIL_FRAMES
+            })
+            .collect();
+
+        Stacktrace { frames, omitted }
+    }
+
+    fn to_text(&self) -> String {
+        let mut buffer = String::new();
+
+        buffer.push_str("Stack trace (the most recent call comes last):");
+
+        for (index, context) in &self.frames {
+            if self.omitted > 0
+                && *index == STACKTRACE_HEAD_FRAMES + self.omitted
+            {
+                buffer.push_str(&format!(
+                    "\n  ... {} frames omitted",
+                    self.omitted
+                ));
+            }
+
+            buffer.push_str(&format!(
+                "\n  {}: \"{}\" line {}, in \"{}\"",
+                index,
+                context.code.file.string_value().unwrap(),
+                context.line().to_string(),
+                context.code.name.string_value().unwrap()
+            ));
+        }
+
+        buffer
+    }
+
+    fn to_json(&self) -> String {
+        let frames: Vec<_> = self
+            .frames
+            .iter()
+            .map(|(index, context)| {
+                format!(
+                    "{{\"index\":{},\"file\":{},\"line\":{},\"name\":{}}}",
+                    index,
+                    json_string(context.code.file.string_value().unwrap()),
+                    context.line(),
+                    json_string(context.code.name.string_value().unwrap())
+                )
+            })
+            .collect();
+
+        format!(
+            "\"frames\":[{}],\"omitted_frames\":{}",
+            frames.join(","),
+            self.omitted
+        )
+    }
+}
+
+/// Returns a string as a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut buffer = String::with_capacity(value.len() + 2);
+
+    buffer.push('"');
+
+    for character in value.chars() {
+        match character {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                buffer.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => buffer.push(c),
+        }
+    }
+
+    buffer.push('"');
+    buffer
+}
+
+/// Returns a runtime panic, formatted for printing.
+fn format_panic(
+    process: &RcProcess,
+    message: &str,
+    format: PanicFormat,
+) -> String {
+    let trace = Stacktrace::new(process);
+
+    match format {
+        PanicFormat::Text => format!(
+            "{}\nProcess {:#x} panicked: {}",
+            trace.to_text(),
+            process.identifier(),
+            message
+        ),
+        PanicFormat::Json => format!(
+            "{{\"process\":\"{:#x}\",\"message\":{},{}}}",
+            process.identifier(),
+            json_string(message),
+            trace.to_json()
+        ),
+    }
+}
+
+/// Returns the stack trace and state of a process, formatted for printing.
+fn format_stack(
+    process: &RcProcess,
+    state: ProcessState,
+    format: PanicFormat,
+) -> String {
+    let trace = Stacktrace::new(process);
+
+    match format {
+        PanicFormat::Text => format!(
+            "{}\nProcess {:#x} is {}",
+            trace.to_text(),
+            process.identifier(),
+            state.description()
+        ),
+        PanicFormat::Json => format!(
+            "{{\"process\":\"{:#x}\",\"state\":{},{}}}",
+            process.identifier(),
+            json_string(state.description()),
+            trace.to_json()
+        ),
+    }
+}
 
 /// Prints a runtime panic to STDERR.
-pub fn display_panic(process: &RcProcess, message: &str) {
-    let mut frames = Vec::new();
-    let mut buffer = String::new();
+pub fn display_panic(process: &RcProcess, message: &str, format: PanicFormat) {
+    eprintln!("{}", format_panic(process, message, format));
+}
 
-    for context in process.context().contexts() {
-        frames.push(format!(
-            "\"{}\" line {}, in \"{}\"",
-            context.code.file.string_value().unwrap(),
-            context.line().to_string(),
-            context.code.name.string_value().unwrap()
-        ));
+/// Prints the stack trace and state of a process to STDERR.
+///
+/// The process must not be modifying its call stack while this function runs.
+/// When displaying the stack of another process, the caller must hold the
+/// running lock of that process (see `Process::try_lock_running()`), as
+/// otherwise the process may be rescheduled during the inspection.
+pub fn display_stack(
+    process: &RcProcess,
+    state: ProcessState,
+    format: PanicFormat,
+) {
+    eprintln!("{}", format_stack(process, state, format));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::test::setup;
+
+    #[test]
+    fn test_panic_format_from_str() {
+        assert_eq!("text".parse::<PanicFormat>(), Ok(PanicFormat::Text));
+        assert_eq!("json".parse::<PanicFormat>(), Ok(PanicFormat::Json));
+        assert!("yaml".parse::<PanicFormat>().is_err());
     }
 
-    frames.reverse();
+    #[test]
+    fn test_json_string() {
+        assert_eq!(
+            json_string("a\"b\\c\nd\u{1}"),
+            "\"a\\\"b\\\\c\\nd\\u0001\""
+        );
+    }
 
-    buffer.push_str("Stack trace (the most recent call comes last):");
+    #[test]
+    fn test_format_panic_as_text() {
+        let (_machine, _block, process) = setup();
+        let output = format_panic(&process, "oops", PanicFormat::Text);
 
-    for (index, line) in frames.iter().enumerate() {
-        buffer.push_str(&format!("\n  {}: {}", index, line));
+        assert!(output.starts_with("Stack trace"));
+        assert!(output.ends_with("panicked: oops"));
     }
 
-    buffer.push_str(&format!(
-        "\nProcess {:#x} panicked: {}",
-        process.identifier(),
-        message
-    ));
+    #[test]
+    fn test_format_panic_as_json() {
+        let (_machine, _block, process) = setup();
+        let output = format_panic(&process, "oops", PanicFormat::Json);
 
-    eprintln!("{}", buffer);
+        assert_eq!(
+            output,
+            format!(
+                "{{\"process\":\"{:#x}\",\"message\":\"oops\",\
+                 \"frames\":[{{\"index\":0,\"file\":\"a\",\"line\":1,\
+                 \"name\":\"a\"}}],\"omitted_frames\":0}}",
+                process.identifier()
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_stack_with_many_frames() {
+        let (_machine, block, process) = setup();
+
+        for _ in 0..MAX_STACKTRACE_FRAMES {
+            process.push_context(ExecutionContext::from_block(&block));
+        }
+
+        let output =
+            format_stack(&process, ProcessState::Running, PanicFormat::Text);
+
+        assert!(output.contains("\n  9: "));
+        assert!(output.contains("\n  ... 1 frames omitted\n  11: "));
+        assert!(output.contains("\n  30: "));
+        assert_eq!(output.contains("\n  10: "), false);
+    }
 }
@@ -0,0 +1,511 @@
+//! Restricting the operations a program can perform.
+//!
+//! A sandbox denies entire families of operations, such as using the FFI or
+//! creating sockets. This makes it possible to run code that isn't trusted,
+//! such as third-party plugins, without giving it full access to the system.
+//!
+//! Writing to files and directory operations that are denied can still be
+//! allowed for specific paths, and denied socket operations can be allowed for
+//! specific addresses. Paths are resolved before checking them, preventing the
+//! use of `..` or symbolic links to escape an allowed directory.
+//!
+//! Paths are resolved when checking them, and the operation itself resolves
+//! the path again. If a program (or another program) replaces part of the path
+//! with a symbolic link in between these two steps, the operation may use a
+//! path outside of the allowed directories. The path allowlist should thus
+//! only contain directories that the program can't modify in such a way, or
+//! that it doesn't share with untrusted programs.
+//!
+//! Denied operations that can fail at runtime, such as opening a file, throw an
+//! error. Other operations, such as using the FFI, result in a panic.
+use std::env;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A family of operations that can be denied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Capability {
+    /// Loading libraries and using pointers using the FFI.
+    Ffi = 0b1,
+
+    /// Opening files for writing, removing files, and copying files.
+    FileWrite = 0b10,
+
+    /// Creating, removing, and listing directories.
+    Directories = 0b100,
+
+    /// Setting and removing environment variables, and changing the working
+    /// directory.
+    Environment = 0b1000,
+
+    /// Creating sockets.
+    Sockets = 0b1_0000,
+
+    /// Terminating the program using an exit status.
+    Exit = 0b10_0000,
+}
+
+impl Capability {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let capability = match name {
+            "ffi" => Capability::Ffi,
+            "file-write" => Capability::FileWrite,
+            "directories" => Capability::Directories,
+            "environment" => Capability::Environment,
+            "sockets" => Capability::Sockets,
+            "exit" => Capability::Exit,
+            _ => return None,
+        };
+
+        Some(capability)
+    }
+
+    /// Returns a description of the operations of this capability, used in
+    /// error messages.
+    pub fn description(self) -> &'static str {
+        match self {
+            Capability::Ffi => "using the FFI",
+            Capability::FileWrite => "writing to files",
+            Capability::Directories => "directory operations",
+            Capability::Environment => "modifying the environment",
+            Capability::Sockets => "using sockets",
+            Capability::Exit => "exiting the program",
+        }
+    }
+}
+
+/// A set of capabilities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn all() -> Self {
+        Capabilities(0b11_1111)
+    }
+
+    pub fn insert(&mut self, capability: Capability) {
+        self.0 |= capability as u8;
+    }
+
+    pub fn contains(self, capability: Capability) -> bool {
+        self.0 & capability as u8 != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl FromStr for Capabilities {
+    type Err = String;
+
+    /// Parses a comma separated list of capability names, such as
+    /// "ffi,sockets". The name "all" includes all capabilities.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut capabilities = Capabilities::new();
+
+        for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if name == "all" {
+                capabilities = Capabilities::all();
+                continue;
+            }
+
+            let capability = Capability::from_name(name)
+                .ok_or_else(|| format!("{} is not a valid capability", name))?;
+
+            capabilities.insert(capability);
+        }
+
+        Ok(capabilities)
+    }
+}
+
+/// A list of paths that can be used, even when their capability is denied.
+#[derive(Clone, Debug, Default)]
+pub struct PathAllowlist(Vec<PathBuf>);
+
+impl PathAllowlist {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        PathAllowlist(paths)
+    }
+
+    /// Returns true if the path resides in one of the allowed paths.
+    pub fn allows(&self, path: &str) -> bool {
+        let path = if let Some(path) = resolve(Path::new(path)) {
+            path
+        } else {
+            return false;
+        };
+
+        self.0
+            .iter()
+            .filter_map(|allowed| resolve(allowed))
+            .any(|allowed| path.starts_with(allowed))
+    }
+}
+
+impl FromStr for PathAllowlist {
+    type Err = String;
+
+    /// Parses a list of paths, separated the same way as the PATH variable.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let paths = env::split_paths(value)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect();
+
+        Ok(PathAllowlist(paths))
+    }
+}
+
+/// An address that can be used, optionally limited to a single port.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllowedAddress {
+    address: String,
+    port: Option<u16>,
+}
+
+impl AllowedAddress {
+    pub fn new(address: String, port: Option<u16>) -> Self {
+        AllowedAddress { address, port }
+    }
+
+    fn allows(&self, address: &str, port: u16) -> bool {
+        if matches!(self.port, Some(allowed) if allowed != port) {
+            return false;
+        }
+
+        // IP addresses are compared after parsing them, so different notations
+        // of the same address (e.g. "::1" and "0:0:0:0:0:0:0:1") are treated
+        // the same.
+        match (self.address.parse::<IpAddr>(), address.parse::<IpAddr>()) {
+            (Ok(allowed), Ok(address)) => allowed == address,
+            _ => self.address == address,
+        }
+    }
+}
+
+impl FromStr for AllowedAddress {
+    type Err = String;
+
+    /// Parses an address, optionally followed by a port.
+    ///
+    /// IPv6 addresses that include a port must be wrapped in square brackets
+    /// (e.g. "[::1]:80"), as the port would otherwise be ambiguous.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("{} is not a valid address", value);
+        let parse_port =
+            |port: &str| port.parse::<u16>().map_err(|_| invalid());
+
+        if let Some(rest) = value.strip_prefix('[') {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            let address = &rest[..end];
+            let port = match &rest[end + 1..] {
+                "" => None,
+                suffix => Some(parse_port(
+                    suffix.strip_prefix(':').ok_or_else(invalid)?,
+                )?),
+            };
+
+            if address.parse::<IpAddr>().is_err() {
+                return Err(invalid());
+            }
+
+            return Ok(AllowedAddress::new(address.to_string(), port));
+        }
+
+        // An address containing multiple colons is an IPv6 address without a
+        // port.
+        match value.find(':') {
+            Some(index) if value.rfind(':') == Some(index) => {
+                Ok(AllowedAddress::new(
+                    value[..index].to_string(),
+                    Some(parse_port(&value[index + 1..])?),
+                ))
+            }
+            Some(_) if value.parse::<IpAddr>().is_err() => Err(invalid()),
+            _ => Ok(AllowedAddress::new(value.to_string(), None)),
+        }
+    }
+}
+
+/// A list of socket addresses that can be used, even when sockets are denied.
+///
+/// Entries are either an address (e.g. "127.0.0.1" or "::1") allowing all
+/// ports, or an address and port (e.g. "127.0.0.1:8080" or "[::1]:8080").
+#[derive(Clone, Debug, Default)]
+pub struct AddressAllowlist(Vec<AllowedAddress>);
+
+impl AddressAllowlist {
+    pub fn new(addresses: Vec<AllowedAddress>) -> Self {
+        AddressAllowlist(addresses)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn allows(&self, address: &str, port: u16) -> bool {
+        self.0.iter().any(|allowed| allowed.allows(address, port))
+    }
+}
+
+impl FromStr for AddressAllowlist {
+    type Err = String;
+
+    /// Parses a comma separated list of addresses.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let addresses = value
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok(AddressAllowlist(addresses))
+    }
+}
+
+/// The operations a program isn't allowed to perform.
+#[derive(Clone, Debug, Default)]
+pub struct Sandbox {
+    /// The capabilities that are denied.
+    pub denied: Capabilities,
+
+    /// The paths that can be used, even when writing to files or directory
+    /// operations are denied.
+    pub paths: PathAllowlist,
+
+    /// The addresses that can be used, even when sockets are denied.
+    pub addresses: AddressAllowlist,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an error if the capability is denied.
+    pub fn check(&self, capability: Capability) -> Result<(), String> {
+        if self.denied.contains(capability) {
+            return Err(denied(capability));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if the capability is denied for the given path.
+    pub fn check_path(
+        &self,
+        capability: Capability,
+        path: &str,
+    ) -> Result<(), String> {
+        if !self.denied.contains(capability) || self.paths.allows(path) {
+            return Ok(());
+        }
+
+        Err(format!("{} ({})", denied(capability), path))
+    }
+
+    /// Returns an error if sockets can't be created.
+    ///
+    /// Sockets can be created if they are allowed, or if there is at least one
+    /// address that can be used.
+    pub fn check_socket(&self) -> Result<(), String> {
+        if self.denied.contains(Capability::Sockets)
+            && self.addresses.is_empty()
+        {
+            return Err(denied(Capability::Sockets));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if the address can't be used by a socket.
+    pub fn check_address(
+        &self,
+        address: &str,
+        port: u16,
+    ) -> Result<(), String> {
+        if !self.denied.contains(Capability::Sockets)
+            || self.addresses.allows(address, port)
+        {
+            return Ok(());
+        }
+
+        Err(format!(
+            "{} ({}:{})",
+            denied(Capability::Sockets),
+            address,
+            port
+        ))
+    }
+}
+
+fn denied(capability: Capability) -> String {
+    format!("The sandbox does not allow {}", capability.description())
+}
+
+/// Resolves a path to an absolute path, without any symbolic links.
+///
+/// Paths that don't exist (yet) are resolved using their parent directory, as
+/// files and directories that are about to be created don't exist.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if let Ok(path) = fs::canonicalize(path) {
+        return Some(path);
+    }
+
+    let name = path.file_name()?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    resolve(parent).map(|parent| parent.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> String {
+        fs::canonicalize(env::temp_dir())
+            .unwrap()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_capabilities_from_str() {
+        let caps = "ffi, sockets".parse::<Capabilities>().unwrap();
+
+        assert!(caps.contains(Capability::Ffi));
+        assert!(caps.contains(Capability::Sockets));
+        assert_eq!(caps.contains(Capability::Exit), false);
+    }
+
+    #[test]
+    fn test_capabilities_from_str_with_all() {
+        let caps = "all".parse::<Capabilities>().unwrap();
+
+        assert_eq!(caps, Capabilities::all());
+    }
+
+    #[test]
+    fn test_capabilities_from_str_with_invalid_name() {
+        assert!("ffi,foo".parse::<Capabilities>().is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let mut sandbox = Sandbox::new();
+
+        assert!(sandbox.check(Capability::Exit).is_ok());
+
+        sandbox.denied.insert(Capability::Exit);
+
+        assert_eq!(
+            sandbox.check(Capability::Exit),
+            Err("The sandbox does not allow exiting the program".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_path() {
+        let temp = temp_dir();
+        let mut sandbox = Sandbox::new();
+
+        sandbox.denied.insert(Capability::FileWrite);
+        sandbox.paths = PathAllowlist::new(vec![PathBuf::from(&temp)]);
+
+        let inside = format!("{}/inko-sandbox-test", temp);
+        let outside = format!("{}/../inko-sandbox-test", temp);
+
+        assert!(sandbox.check_path(Capability::FileWrite, &inside).is_ok());
+        assert!(sandbox.check_path(Capability::FileWrite, &outside).is_err());
+        assert!(sandbox
+            .check_path(Capability::Directories, &outside)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_socket() {
+        let mut sandbox = Sandbox::new();
+
+        assert!(sandbox.check_socket().is_ok());
+
+        sandbox.denied.insert(Capability::Sockets);
+
+        assert!(sandbox.check_socket().is_err());
+
+        sandbox.addresses = AddressAllowlist::new(vec![AllowedAddress::new(
+            "::1".to_string(),
+            None,
+        )]);
+
+        assert!(sandbox.check_socket().is_ok());
+    }
+
+    #[test]
+    fn test_check_address() {
+        let mut sandbox = Sandbox::new();
+
+        sandbox.denied.insert(Capability::Sockets);
+        sandbox.addresses = "127.0.0.1:80, ::1".parse().unwrap();
+
+        assert!(sandbox.check_address("127.0.0.1", 80).is_ok());
+        assert!(sandbox.check_address("127.0.0.1", 81).is_err());
+        assert!(sandbox.check_address("::1", 81).is_ok());
+        assert!(sandbox.check_address("10.0.0.1", 80).is_err());
+    }
+
+    #[test]
+    fn test_check_address_with_ipv6_port() {
+        let mut sandbox = Sandbox::new();
+
+        sandbox.denied.insert(Capability::Sockets);
+        sandbox.addresses = "[::1]:80".parse().unwrap();
+
+        assert!(sandbox.check_address("::1", 80).is_ok());
+        assert!(sandbox.check_address("0:0:0:0:0:0:0:1", 80).is_ok());
+        assert!(sandbox.check_address("::1", 81).is_err());
+    }
+
+    #[test]
+    fn test_allowed_address_from_str() {
+        assert_eq!(
+            "127.0.0.1".parse::<AllowedAddress>(),
+            Ok(AllowedAddress::new("127.0.0.1".to_string(), None))
+        );
+
+        assert_eq!(
+            "localhost:80".parse::<AllowedAddress>(),
+            Ok(AllowedAddress::new("localhost".to_string(), Some(80)))
+        );
+
+        assert_eq!(
+            "::1:80".parse::<AllowedAddress>(),
+            Ok(AllowedAddress::new("::1:80".to_string(), None))
+        );
+
+        assert_eq!(
+            "[::1]:80".parse::<AllowedAddress>(),
+            Ok(AllowedAddress::new("::1".to_string(), Some(80)))
+        );
+    }
+
+    #[test]
+    fn test_allowed_address_from_str_invalid() {
+        assert!("127.0.0.1:foo".parse::<AllowedAddress>().is_err());
+        assert!("[::1".parse::<AllowedAddress>().is_err());
+        assert!("[::1]80".parse::<AllowedAddress>().is_err());
+        assert!("[foo]:80".parse::<AllowedAddress>().is_err());
+        assert!("foo::bar".parse::<AllowedAddress>().is_err());
+    }
+}
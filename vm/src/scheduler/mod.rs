@@ -2,6 +2,7 @@
 pub mod join_list;
 pub mod park_group;
 pub mod pool_state;
+pub mod priority;
 pub mod process_pool;
 pub mod process_scheduler;
 pub mod process_worker;
@@ -1,9 +1,10 @@
 //! State management of a thread pool.
 use crate::scheduler::park_group::ParkGroup;
-use crate::scheduler::queue::{Queue, RcQueue};
+use crate::scheduler::priority::{Prioritize, Priority, LEVELS};
+use crate::scheduler::queue::{Queue, RcQueue, FAIRNESS_INTERVAL};
 use crossbeam_deque::{Injector, Steal};
 use std::iter;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 /// The maximum number of threads a single pool allows.
 const MAX_THREADS: usize = 255;
@@ -17,14 +18,19 @@ pub struct PoolState<T: Send> {
     /// A boolean indicating if the scheduler is alive, or should shut down.
     alive: AtomicBool,
 
-    /// The global queue on which new jobs will be scheduled,
-    global_queue: Injector<T>,
+    /// The global queues on which new jobs will be scheduled, one for every
+    /// priority.
+    global_queues: [Injector<T>; LEVELS],
+
+    /// The number of jobs popped from the global queues, used for determining
+    /// when to prefer lower priority jobs.
+    global_pops: AtomicUsize,
 
     /// Used for parking and unparking worker threads.
     park_group: ParkGroup,
 }
 
-impl<T: Send> PoolState<T> {
+impl<T: Send + Prioritize> PoolState<T> {
     /// Creates a new state for the given number worker threads.
     ///
     /// Threads are not started by this method, and instead must be started
@@ -39,14 +45,15 @@ impl<T: Send> PoolState<T> {
         PoolState {
             alive: AtomicBool::new(true),
             queues,
-            global_queue: Injector::new(),
+            global_queues: [Injector::new(), Injector::new(), Injector::new()],
+            global_pops: AtomicUsize::new(0),
             park_group: ParkGroup::new(),
         }
     }
 
     /// Schedules a new job onto the global queue.
     pub fn push_global(&self, value: T) {
-        self.global_queue.push(value);
+        self.global_queues[value.priority().index()].push(value);
         self.park_group.notify_one();
     }
 
@@ -62,23 +69,30 @@ impl<T: Send> PoolState<T> {
         self.park_group.notify_all();
     }
 
-    /// Pops a value off the global queue.
+    /// Pops a value off the global queues, starting with the highest
+    /// priority.
     ///
-    /// This method will block the calling thread until a value is available.
+    /// Similar to `Queue::pop()`, every `FAIRNESS_INTERVAL` pops we start with
+    /// the lowest priority instead.
     pub fn pop_global(&self) -> Option<T> {
-        loop {
-            match self.global_queue.steal() {
-                Steal::Empty => {
-                    return None;
-                }
-                Steal::Retry => {}
-                Steal::Success(value) => {
-                    return Some(value);
-                }
-            }
+        let pops = self.global_pops.fetch_add(1, Ordering::Relaxed);
+
+        if pops % FAIRNESS_INTERVAL == FAIRNESS_INTERVAL - 1 {
+            self.global_queues.iter().find_map(Self::steal_from)
+        } else {
+            self.global_queues.iter().rev().find_map(Self::steal_from)
         }
     }
 
+    /// Pops a value off the global queues, but only if its priority is higher
+    /// than the given priority.
+    pub fn pop_global_above(&self, priority: Priority) -> Option<T> {
+        self.global_queues[priority.index() + 1..]
+            .iter()
+            .rev()
+            .find_map(Self::steal_from)
+    }
+
     pub fn is_alive(&self) -> bool {
         self.alive.load(Ordering::Acquire)
     }
@@ -103,7 +117,21 @@ impl<T: Send> PoolState<T> {
 
     /// Returns true if one or more jobs are present in the global queue.
     pub fn has_global_jobs(&self) -> bool {
-        !self.global_queue.is_empty()
+        self.global_queues.iter().any(|queue| !queue.is_empty())
+    }
+
+    fn steal_from(queue: &Injector<T>) -> Option<T> {
+        loop {
+            match queue.steal() {
+                Steal::Empty => {
+                    return None;
+                }
+                Steal::Retry => {}
+                Steal::Success(value) => {
+                    return Some(value);
+                }
+            }
+        }
     }
 }
 
@@ -114,9 +142,18 @@ mod tests {
     use std::mem;
     use std::thread;
 
+    #[derive(Eq, PartialEq, Debug)]
+    struct Job(Priority);
+
+    impl Prioritize for Job {
+        fn priority(&self) -> Priority {
+            self.0
+        }
+    }
+
     #[test]
     fn test_memory_size() {
-        assert_eq!(mem::size_of::<PoolState<()>>(), 384);
+        assert_eq!(mem::size_of::<PoolState<()>>(), 896);
     }
 
     #[test]
@@ -139,7 +176,11 @@ mod tests {
 
         state.push_global(10);
 
-        assert_eq!(state.global_queue.is_empty(), false);
+        assert!(state.has_global_jobs());
+        assert_eq!(
+            state.global_queues[Priority::Normal.index()].is_empty(),
+            false
+        );
     }
 
     #[test]
@@ -152,6 +193,51 @@ mod tests {
         assert_eq!(state.pop_global(), None);
     }
 
+    #[test]
+    fn test_pop_global_with_priorities() {
+        let state = PoolState::new(1);
+
+        state.push_global(Job(Priority::Low));
+        state.push_global(Job(Priority::Normal));
+        state.push_global(Job(Priority::High));
+
+        assert_eq!(state.pop_global(), Some(Job(Priority::High)));
+        assert_eq!(state.pop_global(), Some(Job(Priority::Normal)));
+        assert_eq!(state.pop_global(), Some(Job(Priority::Low)));
+        assert_eq!(state.pop_global(), None);
+    }
+
+    #[test]
+    fn test_pop_global_with_fairness() {
+        let state = PoolState::new(1);
+
+        for _ in 0..FAIRNESS_INTERVAL {
+            state.push_global(Job(Priority::High));
+        }
+
+        state.push_global(Job(Priority::Low));
+
+        for _ in 0..(FAIRNESS_INTERVAL - 1) {
+            assert_eq!(state.pop_global(), Some(Job(Priority::High)));
+        }
+
+        assert_eq!(state.pop_global(), Some(Job(Priority::Low)));
+    }
+
+    #[test]
+    fn test_pop_global_above() {
+        let state = PoolState::new(1);
+
+        state.push_global(Job(Priority::Normal));
+
+        assert_eq!(state.pop_global_above(Priority::Normal), None);
+        assert_eq!(state.pop_global_above(Priority::High), None);
+        assert_eq!(
+            state.pop_global_above(Priority::Low),
+            Some(Job(Priority::Normal))
+        );
+    }
+
     #[test]
     fn test_terminate() {
         let state: PoolState<()> = PoolState::new(4);
@@ -0,0 +1,90 @@
+//! Scheduling priorities of jobs.
+
+/// The number of priority levels supported by the scheduler.
+pub const LEVELS: usize = 3;
+
+/// The priority of a job.
+///
+/// Jobs with a higher priority are performed before jobs with a lower
+/// priority, but lower priority jobs are still performed periodically to
+/// prevent them from starving.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum Priority {
+    Low = 0,
+    Normal = 1,
+    High = 2,
+}
+
+impl Priority {
+    /// Returns the priority for the given integer, if any.
+    pub fn from_u8(value: u8) -> Option<Priority> {
+        match value {
+            0 => Some(Priority::Low),
+            1 => Some(Priority::Normal),
+            2 => Some(Priority::High),
+            _ => None,
+        }
+    }
+
+    /// Returns the index of the queue to use for jobs using this priority.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the number of reductions a process with this priority can
+    /// perform, based on the default number of reductions.
+    pub fn reductions(self, reductions: usize) -> usize {
+        match self {
+            Priority::Low => reductions / 2,
+            Priority::Normal => reductions,
+            Priority::High => reductions.saturating_mul(2),
+        }
+    }
+}
+
+/// A type that can be scheduled using a priority.
+pub trait Prioritize {
+    /// Returns the priority to use when scheduling a job.
+    fn priority(&self) -> Priority {
+        Priority::Normal
+    }
+}
+
+#[cfg(test)]
+impl Prioritize for () {}
+
+#[cfg(test)]
+impl Prioritize for i32 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_u8() {
+        assert_eq!(Priority::from_u8(0), Some(Priority::Low));
+        assert_eq!(Priority::from_u8(1), Some(Priority::Normal));
+        assert_eq!(Priority::from_u8(2), Some(Priority::High));
+        assert_eq!(Priority::from_u8(3), None);
+    }
+
+    #[test]
+    fn test_index() {
+        assert_eq!(Priority::Low.index(), 0);
+        assert_eq!(Priority::Normal.index(), 1);
+        assert_eq!(Priority::High.index(), 2);
+    }
+
+    #[test]
+    fn test_reductions() {
+        assert_eq!(Priority::Low.reductions(1000), 500);
+        assert_eq!(Priority::Normal.reductions(1000), 1000);
+        assert_eq!(Priority::High.reductions(1000), 2000);
+    }
+
+    #[test]
+    fn test_default_priority() {
+        assert_eq!(().priority(), Priority::Normal);
+    }
+}
@@ -207,6 +207,12 @@ impl Worker<RcProcess> for ProcessWorker {
         // needed.
         let machine = unsafe { &mut *self.machine.get() };
 
+        // Another thread may reschedule the process before we finish running
+        // it, such as when it suspends itself. The lock ensures it doesn't run
+        // on multiple threads at once, and that other threads don't inspect it
+        // while it's running.
+        let _running = job.lock_running();
+
         machine.run_with_error_handling(self, &job);
     }
 }
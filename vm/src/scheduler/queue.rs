@@ -1,21 +1,35 @@
 //! Efficient work stealing queues.
 use crate::arc_without_weak::ArcWithoutWeak;
+use crate::scheduler::priority::{Prioritize, Priority, LEVELS};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use crossbeam_deque::{Steal, Stealer, Worker};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+/// The number of jobs to pop before giving the lowest priority jobs a chance
+/// to run first.
+pub const FAIRNESS_INTERVAL: usize = 16;
+
 /// A Queue can be used to store, pop, and steal jobs to perform. Threads that
 /// own a Queue can push jobs into the queue with minimal overhead, while other
 /// threads can push jobs into the queue using a Multiple Producer Multiple
 /// Consumer (MPMC) channel.
+///
+/// Jobs are stored in a separate deque per priority. Jobs with a higher
+/// priority are popped first, but every `FAIRNESS_INTERVAL` pops the queue
+/// starts with the lowest priority, preventing lower priority jobs from being
+/// starved.
 pub struct Queue<T: Send> {
-    /// The worker side of the deque, used for producing new jobs. This
-    /// structure _can only_ be used by the thread that owns this queue.
-    worker: Worker<T>,
+    /// The worker sides of the deques, used for producing new jobs. These
+    /// structures _can only_ be used by the thread that owns this queue.
+    workers: [Worker<T>; LEVELS],
+
+    /// The stealing sides of the deques. Other workers may steal jobs from
+    /// this queue using these stealers.
+    stealers: [Stealer<T>; LEVELS],
 
-    /// The stealing side of the deque. Other workers may still jobs from this
-    /// queue using this stealer.
-    stealer: Stealer<T>,
+    /// The number of jobs popped from this queue, used for determining when to
+    /// prefer lower priority jobs.
+    pops: AtomicUsize,
 
     /// The number of pending jobs that were scheduled externally.
     pending_external: AtomicUsize,
@@ -31,16 +45,24 @@ pub struct Queue<T: Send> {
 
 pub type RcQueue<T> = ArcWithoutWeak<Queue<T>>;
 
-impl<T: Send> Queue<T> {
+impl<T: Send + Prioritize> Queue<T> {
     pub fn new() -> Self {
-        let worker = Worker::new_fifo();
-        let stealer = worker.stealer();
+        let workers =
+            [Worker::new_fifo(), Worker::new_fifo(), Worker::new_fifo()];
+
+        let stealers = [
+            workers[0].stealer(),
+            workers[1].stealer(),
+            workers[2].stealer(),
+        ];
+
         let (sender, receiver) = unbounded();
 
         Queue {
-            stealer,
+            stealers,
+            pops: AtomicUsize::new(0),
             pending_external: AtomicUsize::new(0),
-            worker,
+            workers,
             sender,
             receiver,
         }
@@ -68,7 +90,7 @@ impl<T: Send> Queue<T> {
     ///
     /// This method can only be used by the thread that owns the queue.
     pub fn push_internal(&self, value: T) {
-        self.worker.push(value);
+        self.workers[value.priority().index()].push(value);
     }
 
     /// Pushes a job onto the shared channel.
@@ -82,9 +104,23 @@ impl<T: Send> Queue<T> {
             .expect("Attempted to schedule a job onto a queue that is dropped");
     }
 
-    /// Pops a value from the worker.
+    /// Pops a value from the workers, starting with the highest priority.
     pub fn pop(&self) -> Option<T> {
-        self.worker.pop()
+        let pops = self.pops.fetch_add(1, Ordering::Relaxed);
+
+        if pops % FAIRNESS_INTERVAL == FAIRNESS_INTERVAL - 1 {
+            self.workers.iter().find_map(|worker| worker.pop())
+        } else {
+            self.workers.iter().rev().find_map(|worker| worker.pop())
+        }
+    }
+
+    /// Returns the highest priority of the jobs stored in our local workers.
+    pub fn highest_priority(&self) -> Option<Priority> {
+        self.workers
+            .iter()
+            .rposition(|worker| !worker.is_empty())
+            .and_then(|index| Priority::from_u8(index as u8))
     }
 
     /// Steal one or more jobs and push them into the given queue.
@@ -93,15 +129,21 @@ impl<T: Send> Queue<T> {
     /// boolean will be `true` if one or more jobs were stolen, `false`
     /// otherwise.
     pub fn steal_into(&self, queue: &Self) -> bool {
-        loop {
-            match self.stealer.steal_batch(&queue.worker) {
-                Steal::Empty => return false,
-                Steal::Success(_) => {
-                    return true;
-                }
-                _ => {}
-            };
+        for (stealer, worker) in
+            self.stealers.iter().zip(queue.workers.iter()).rev()
+        {
+            loop {
+                match stealer.steal_batch(worker) {
+                    Steal::Empty => break,
+                    Steal::Success(_) => {
+                        return true;
+                    }
+                    _ => {}
+                };
+            }
         }
+
+        false
     }
 
     /// Pops a job from the public channel, without first moving it to the
@@ -137,7 +179,7 @@ impl<T: Send> Queue<T> {
         for job in self.receiver.try_iter().take(remaining) {
             received += 1;
 
-            self.worker.push(job);
+            self.push_internal(job);
         }
 
         self.pending_external.fetch_sub(received, Ordering::Release);
@@ -145,9 +187,9 @@ impl<T: Send> Queue<T> {
         received > 0
     }
 
-    /// Returns true if there are one or more jobs stored in our local worker.
+    /// Returns true if there are one or more jobs stored in our local workers.
     pub fn has_local_jobs(&self) -> bool {
-        !self.worker.is_empty()
+        self.workers.iter().any(|worker| !worker.is_empty())
     }
 
     /// Returns true if there are one or more jobs stored in the external queue.
@@ -159,6 +201,16 @@ impl<T: Send> Queue<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scheduler::priority::Priority;
+
+    #[derive(Eq, PartialEq, Debug)]
+    struct Job(Priority);
+
+    impl Prioritize for Job {
+        fn priority(&self) -> Priority {
+            self.0
+        }
+    }
 
     #[test]
     fn test_push_internal() {
@@ -166,7 +218,7 @@ mod tests {
 
         queue.push_internal(10);
 
-        assert_eq!(queue.worker.is_empty(), false);
+        assert_eq!(queue.workers[Priority::Normal.index()].is_empty(), false);
     }
 
     #[test]
@@ -273,4 +325,76 @@ mod tests {
 
         assert!(queue.has_external_jobs());
     }
+
+    #[test]
+    fn test_highest_priority() {
+        let queue = Queue::new();
+
+        assert_eq!(queue.highest_priority(), None);
+
+        queue.push_internal(Job(Priority::Low));
+
+        assert_eq!(queue.highest_priority(), Some(Priority::Low));
+
+        queue.push_internal(Job(Priority::High));
+
+        assert_eq!(queue.highest_priority(), Some(Priority::High));
+    }
+
+    #[test]
+    fn test_pop_with_priorities() {
+        let queue = Queue::new();
+
+        queue.push_internal(Job(Priority::Low));
+        queue.push_internal(Job(Priority::Normal));
+        queue.push_internal(Job(Priority::High));
+
+        assert_eq!(queue.pop(), Some(Job(Priority::High)));
+        assert_eq!(queue.pop(), Some(Job(Priority::Normal)));
+        assert_eq!(queue.pop(), Some(Job(Priority::Low)));
+    }
+
+    #[test]
+    fn test_pop_does_not_starve_low_priority_jobs() {
+        let queue = Queue::new();
+
+        queue.push_internal(Job(Priority::Low));
+
+        for _ in 0..FAIRNESS_INTERVAL {
+            queue.push_internal(Job(Priority::High));
+        }
+
+        for _ in 0..(FAIRNESS_INTERVAL - 1) {
+            assert_eq!(queue.pop(), Some(Job(Priority::High)));
+        }
+
+        assert_eq!(queue.pop(), Some(Job(Priority::Low)));
+        assert_eq!(queue.pop(), Some(Job(Priority::High)));
+    }
+
+    #[test]
+    fn test_steal_with_priorities() {
+        let queue1 = Queue::new();
+        let queue2 = Queue::new();
+
+        queue1.push_internal(Job(Priority::Low));
+        queue1.push_internal(Job(Priority::High));
+
+        assert!(queue1.steal_into(&queue2));
+        assert_eq!(queue2.pop(), Some(Job(Priority::High)));
+        assert_eq!(queue2.pop(), None);
+        assert_eq!(queue1.pop(), Some(Job(Priority::Low)));
+    }
+
+    #[test]
+    fn test_move_external_jobs_with_priorities() {
+        let queue = Queue::new();
+
+        queue.push_external(Job(Priority::Low));
+        queue.push_external(Job(Priority::High));
+
+        assert!(queue.move_external_jobs());
+        assert_eq!(queue.pop(), Some(Job(Priority::High)));
+        assert_eq!(queue.pop(), Some(Job(Priority::Low)));
+    }
 }
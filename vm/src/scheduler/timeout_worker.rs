@@ -1,10 +1,14 @@
-//! Rescheduling of processes with expired timeouts.
+//! Rescheduling of processes with expired timeouts, and expiring of timers.
 use crate::arc_without_weak::ArcWithoutWeak;
+use crate::object_pointer::ObjectPointer;
 use crate::process::RcProcess;
 use crate::scheduler::process_scheduler::ProcessScheduler;
-use crate::scheduler::timeouts::{Timeout, Timeouts};
+use crate::scheduler::timeouts::{RcTimer, Timeout, Timeouts, Timer};
+use crate::vm::instructions::process::reschedule_for_message;
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use parking_lot::Mutex;
 use std::cell::UnsafeCell;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
@@ -17,11 +21,12 @@ const MAX_MESSAGES_PER_ITERATION: usize = 64;
 
 enum Message {
     Suspend(RcProcess, ArcWithoutWeak<Timeout>),
+    Timer(RcTimer, ArcWithoutWeak<Timeout>),
     Terminate,
 }
 
 struct Inner {
-    /// The processes suspended with timeouts.
+    /// The processes suspended with timeouts, and the timers to expire.
     timeouts: Timeouts,
 
     /// The receiving half of the channel used for suspending processes.
@@ -32,7 +37,7 @@ struct Inner {
 }
 
 /// A TimeoutWorker is tasked with rescheduling processes when their timeouts
-/// expire.
+/// expire, and sending the messages of timers when these expire.
 ///
 /// Processes are suspended by sending messages via a channel, removing the need
 /// for heavyweight locking.
@@ -60,6 +65,13 @@ pub struct TimeoutWorker {
     /// The number of timeouts that have been invalidated by sending a message
     /// to the process, before the timeout expired.
     expired: AtomicUsize,
+
+    /// The timers that have yet to expire or be cancelled, mapped to their
+    /// IDs.
+    timers: Mutex<HashMap<usize, RcTimer>>,
+
+    /// The ID to assign to the next timer.
+    next_timer_id: AtomicUsize,
 }
 
 unsafe impl Sync for TimeoutWorker {}
@@ -78,6 +90,8 @@ impl TimeoutWorker {
         TimeoutWorker {
             inner: UnsafeCell::new(inner),
             expired: AtomicUsize::new(0),
+            timers: Mutex::new(HashMap::new()),
+            next_timer_id: AtomicUsize::new(0),
             sender,
         }
     }
@@ -92,6 +106,51 @@ impl TimeoutWorker {
             .expect("Failed to suspend because the channel was closed");
     }
 
+    /// Starts a timer that sends a message to a process after a delay.
+    ///
+    /// If an interval is given, the message is sent every time the interval
+    /// expires, until the timer is cancelled. The returned value is the ID of
+    /// the timer.
+    pub fn start_timer(
+        &self,
+        process: RcProcess,
+        message: ObjectPointer,
+        delay: Duration,
+        interval: Option<Duration>,
+    ) -> usize {
+        let id = self.next_timer_id.fetch_add(1, Ordering::AcqRel);
+        let timer = Timer::with_rc(id, process.clone(), interval);
+
+        process.add_timer_message(id, message);
+        self.timers.lock().insert(id, timer.clone());
+
+        self.sender
+            .send(Message::Timer(timer, Timeout::with_rc(delay)))
+            .expect("Failed to start a timer because the channel was closed");
+
+        id
+    }
+
+    /// Cancels a timer.
+    ///
+    /// The returned boolean is `true` if the timer was cancelled before its
+    /// message was sent.
+    pub fn cancel_timer(&self, id: usize) -> bool {
+        if let Some(timer) = self.timers.lock().remove(&id) {
+            timer.cancel();
+            self.increase_expired_timeouts();
+
+            timer.process.remove_timer_message(id)
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if there are timers that have yet to expire.
+    pub fn has_timers(&self) -> bool {
+        !self.timers.lock().is_empty()
+    }
+
     pub fn terminate(&self) {
         self.sender
             .send(Message::Terminate)
@@ -133,16 +192,57 @@ impl TimeoutWorker {
         scheduler: &ProcessScheduler,
     ) -> Option<Duration> {
         let inner = self.inner_mut();
-        let (expired, time_until_expiration) =
-            inner.timeouts.processes_to_reschedule();
+        let (expired, timers, mut time_until_expiration) =
+            inner.timeouts.expired_entries();
 
         for process in expired {
             scheduler.schedule(process);
         }
 
+        for timer in timers {
+            if let Some(interval) = self.expire_timer(scheduler, timer) {
+                time_until_expiration = Some(
+                    time_until_expiration
+                        .map_or(interval, |current| current.min(interval)),
+                );
+            }
+        }
+
         time_until_expiration
     }
 
+    /// Sends the message of an expired timer to its process.
+    ///
+    /// Periodic timers are scheduled again, in which case the interval of the
+    /// timer is returned.
+    fn expire_timer(
+        &self,
+        scheduler: &ProcessScheduler,
+        timer: RcTimer,
+    ) -> Option<Duration> {
+        let sent = timer
+            .process
+            .send_timer_message(timer.id, timer.is_periodic());
+
+        if sent {
+            reschedule_for_message(scheduler, self, &timer.process);
+        }
+
+        match timer.interval {
+            Some(interval) if sent => {
+                self.inner_mut()
+                    .timeouts
+                    .insert_timer(timer, Timeout::with_rc(interval));
+
+                Some(interval)
+            }
+            _ => {
+                self.timers.lock().remove(&timer.id);
+                None
+            }
+        }
+    }
+
     fn handle_pending_messages(&self) {
         for message in self
             .inner_mut()
@@ -177,6 +277,9 @@ impl TimeoutWorker {
             Message::Suspend(process, timeout) => {
                 inner.timeouts.insert(process, timeout);
             }
+            Message::Timer(timer, timeout) => {
+                inner.timeouts.insert_timer(timer, timeout);
+            }
             Message::Terminate => {
                 inner.alive = false;
             }
@@ -245,6 +348,135 @@ mod tests {
         assert!(process.acquire_rescheduling_rights().are_acquired());
     }
 
+    #[test]
+    fn test_start_timer() {
+        let worker = TimeoutWorker::new();
+        let (_machine, _block, process) = setup();
+        let id = worker.start_timer(
+            process.clone(),
+            ObjectPointer::integer(5),
+            Duration::from_secs(1),
+            None,
+        );
+
+        assert!(worker.inner().receiver.recv().is_ok());
+        assert!(worker.timers.lock().contains_key(&id));
+        assert_ne!(
+            worker.start_timer(
+                process,
+                ObjectPointer::integer(5),
+                Duration::from_secs(1),
+                None
+            ),
+            id
+        );
+    }
+
+    #[test]
+    fn test_cancel_timer() {
+        let worker = TimeoutWorker::new();
+        let (_machine, _block, process) = setup();
+        let id = worker.start_timer(
+            process.clone(),
+            ObjectPointer::integer(5),
+            Duration::from_secs(1),
+            None,
+        );
+
+        assert!(worker.cancel_timer(id));
+        assert_eq!(worker.cancel_timer(id), false);
+        assert_eq!(worker.expired.load(Ordering::Acquire), 1);
+        assert_eq!(process.send_timer_message(id, false), false);
+    }
+
+    #[test]
+    fn test_has_timers() {
+        let worker = TimeoutWorker::new();
+        let (_machine, _block, process) = setup();
+
+        assert_eq!(worker.has_timers(), false);
+
+        let id = worker.start_timer(
+            process,
+            ObjectPointer::integer(5),
+            Duration::from_secs(1),
+            None,
+        );
+
+        assert!(worker.has_timers());
+
+        worker.cancel_timer(id);
+
+        assert_eq!(worker.has_timers(), false);
+    }
+
+    #[test]
+    fn test_reschedule_expired_processes_with_expired_timer() {
+        let worker = TimeoutWorker::new();
+        let scheduler = ProcessScheduler::new(1, 1);
+        let (_machine, _block, process) = setup();
+        let id = worker.start_timer(
+            process.clone(),
+            ObjectPointer::integer(5),
+            Duration::from_secs(0),
+            None,
+        );
+
+        process.suspend_without_timeout();
+        worker.wait_for_message();
+
+        assert!(worker.reschedule_expired_processes(&scheduler).is_none());
+        assert!(
+            scheduler.primary_pool.state.pop_global() == Some(process.clone())
+        );
+        assert!(process.receive_message() == Some(ObjectPointer::integer(5)));
+        assert_eq!(worker.timers.lock().contains_key(&id), false);
+        assert_eq!(worker.inner().timeouts.len(), 0);
+    }
+
+    #[test]
+    fn test_reschedule_expired_processes_with_periodic_timer() {
+        let worker = TimeoutWorker::new();
+        let scheduler = ProcessScheduler::new(1, 1);
+        let (_machine, _block, process) = setup();
+        let id = worker.start_timer(
+            process.clone(),
+            ObjectPointer::integer(5),
+            Duration::from_secs(0),
+            Some(Duration::from_secs(5)),
+        );
+
+        worker.wait_for_message();
+
+        let expiration = worker.reschedule_expired_processes(&scheduler);
+
+        assert!(expiration.is_some());
+        assert!(expiration.unwrap() <= Duration::from_secs(5));
+        assert!(process.receive_message() == Some(ObjectPointer::integer(5)));
+        assert!(worker.timers.lock().contains_key(&id));
+        assert_eq!(worker.inner().timeouts.len(), 1);
+    }
+
+    #[test]
+    fn test_reschedule_expired_processes_with_cancelled_timer() {
+        let worker = TimeoutWorker::new();
+        let scheduler = ProcessScheduler::new(1, 1);
+        let (_machine, _block, process) = setup();
+        let id = worker.start_timer(
+            process.clone(),
+            ObjectPointer::integer(5),
+            Duration::from_secs(0),
+            None,
+        );
+
+        worker.wait_for_message();
+        worker.cancel_timer(id);
+        worker.reschedule_expired_processes(&scheduler);
+
+        assert!(process.receive_message().is_none());
+        assert_eq!(worker.inner().timeouts.len(), 0);
+    }
+
     #[test]
     fn test_terminate() {
         let worker = TimeoutWorker::new();
@@ -1,8 +1,9 @@
-//! Processes suspended with a timeout.
+//! Processes suspended with a timeout, and timers sending messages.
 use crate::arc_without_weak::ArcWithoutWeak;
 use crate::process::RcProcess;
 use std::cmp;
 use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 /// A process that should be resumed after a certain point in time.
@@ -33,27 +34,108 @@ impl Timeout {
     }
 }
 
-/// A Timeout and a Process to store in the timeout heap.
+/// A timer that sends a message to a process once it expires.
+///
+/// The message itself is stored in the mailbox of the receiving process, so it
+/// is traced by the garbage collector of that process.
+pub struct Timer {
+    /// The unique ID of this timer.
+    pub id: usize,
+
+    /// The process to send the message to.
+    pub process: RcProcess,
+
+    /// The interval at which to send the message, if the timer is periodic.
+    pub interval: Option<Duration>,
+
+    /// A boolean indicating if the timer was cancelled.
+    cancelled: AtomicBool,
+}
+
+pub type RcTimer = ArcWithoutWeak<Timer>;
+
+impl Timer {
+    pub fn new(
+        id: usize,
+        process: RcProcess,
+        interval: Option<Duration>,
+    ) -> Self {
+        Timer {
+            id,
+            process,
+            interval,
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_rc(
+        id: usize,
+        process: RcProcess,
+        interval: Option<Duration>,
+    ) -> RcTimer {
+        ArcWithoutWeak::new(Self::new(id, process, interval))
+    }
+
+    pub fn is_periodic(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// The value to resume or expire when a timeout expires.
+enum Target {
+    Process(RcProcess),
+    Timer(RcTimer),
+}
+
+/// A Timeout and a Process or Timer to store in the timeout heap.
 ///
 /// Since the Timeout is also stored in a process we can't also store a Process
 /// in a Timeout, as this would result in cyclic references. To work around
 /// this, we store the two values in this separate TimeoutEntry structure.
 struct TimeoutEntry {
     timeout: ArcWithoutWeak<Timeout>,
-    process: RcProcess,
+    target: Target,
 }
 
 impl TimeoutEntry {
     pub fn new(process: RcProcess, timeout: ArcWithoutWeak<Timeout>) -> Self {
-        TimeoutEntry { process, timeout }
+        TimeoutEntry {
+            target: Target::Process(process),
+            timeout,
+        }
+    }
+
+    pub fn timer(timer: RcTimer, timeout: ArcWithoutWeak<Timeout>) -> Self {
+        TimeoutEntry {
+            target: Target::Timer(timer),
+            timeout,
+        }
     }
 
     fn is_valid(&self) -> bool {
-        self.process.is_suspended_with_timeout(&self.timeout)
+        match self.target {
+            Target::Process(ref process) => {
+                process.is_suspended_with_timeout(&self.timeout)
+            }
+            Target::Timer(ref timer) => !timer.is_cancelled(),
+        }
     }
 
     fn acquire_rescheduling_rights(&self) -> bool {
-        self.process.acquire_rescheduling_rights().are_acquired()
+        match self.target {
+            Target::Process(ref process) => {
+                process.acquire_rescheduling_rights().are_acquired()
+            }
+            Target::Timer(_) => false,
+        }
     }
 }
 
@@ -75,16 +157,27 @@ impl Ord for TimeoutEntry {
     }
 }
 
+impl PartialEq for Target {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Target::Process(ref a), Target::Process(ref b)) => a == b,
+            (Target::Timer(ref a), Target::Timer(ref b)) => a.id == b.id,
+            _ => false,
+        }
+    }
+}
+
 impl PartialEq for TimeoutEntry {
     fn eq(&self, other: &Self) -> bool {
         self.timeout.resume_after == other.timeout.resume_after
-            && self.process == other.process
+            && self.target == other.target
     }
 }
 
 impl Eq for TimeoutEntry {}
 
-/// A collection of processes that are waiting with a timeout.
+/// A collection of processes that are waiting with a timeout, and timers that
+/// have yet to expire.
 ///
 /// This structure uses a binary heap for two reasons:
 ///
@@ -97,7 +190,8 @@ impl Eq for TimeoutEntry {}
 /// timeouts at the cost of potentially keeping invalidated entries around in
 /// the heap for a while.
 pub struct Timeouts {
-    /// The timeouts of all processes, sorted from shortest to longest.
+    /// The timeouts of all processes and timers, sorted from shortest to
+    /// longest.
     timeouts: BinaryHeap<TimeoutEntry>,
 }
 
@@ -117,6 +211,14 @@ impl Timeouts {
         self.timeouts.push(TimeoutEntry::new(process, timeout));
     }
 
+    pub fn insert_timer(
+        &mut self,
+        timer: RcTimer,
+        timeout: ArcWithoutWeak<Timeout>,
+    ) {
+        self.timeouts.push(TimeoutEntry::timer(timer, timeout));
+    }
+
     pub fn len(&self) -> usize {
         self.timeouts.len()
     }
@@ -141,10 +243,11 @@ impl Timeouts {
         removed
     }
 
-    pub fn processes_to_reschedule(
+    pub fn expired_entries(
         &mut self,
-    ) -> (Vec<RcProcess>, Option<Duration>) {
+    ) -> (Vec<RcProcess>, Vec<RcTimer>, Option<Duration>) {
         let mut reschedule = Vec::new();
+        let mut timers = Vec::new();
         let mut time_until_expiration = None;
 
         while let Some(entry) = self.timeouts.pop() {
@@ -162,12 +265,17 @@ impl Timeouts {
                 break;
             }
 
-            if entry.acquire_rescheduling_rights() {
-                reschedule.push(entry.process);
+            match entry.target {
+                Target::Process(ref process) => {
+                    if entry.acquire_rescheduling_rights() {
+                        reschedule.push(process.clone());
+                    }
+                }
+                Target::Timer(timer) => timers.push(timer),
             }
         }
 
-        (reschedule, time_until_expiration)
+        (reschedule, timers, time_until_expiration)
     }
 }
 
@@ -215,6 +323,33 @@ mod tests {
         }
     }
 
+    mod timer {
+        use super::*;
+        use crate::vm::test::setup;
+
+        #[test]
+        fn test_is_periodic() {
+            let (_machine, _block, process) = setup();
+            let timer1 = Timer::new(1, process.clone(), None);
+            let timer2 = Timer::new(2, process, Some(Duration::from_secs(1)));
+
+            assert_eq!(timer1.is_periodic(), false);
+            assert!(timer2.is_periodic());
+        }
+
+        #[test]
+        fn test_cancel() {
+            let (_machine, _block, process) = setup();
+            let timer = Timer::new(1, process, None);
+
+            assert_eq!(timer.is_cancelled(), false);
+
+            timer.cancel();
+
+            assert!(timer.is_cancelled());
+        }
+    }
+
     mod timeout_entry {
         use super::*;
         use crate::vm::test::setup;
@@ -233,6 +368,20 @@ mod tests {
             assert!(entry.is_valid());
         }
 
+        #[test]
+        fn test_valid_with_timer() {
+            let (_machine, _block, process) = setup();
+            let timeout = Timeout::with_rc(Duration::from_secs(1));
+            let timer = Timer::with_rc(1, process, None);
+            let entry = TimeoutEntry::timer(timer.clone(), timeout);
+
+            assert!(entry.is_valid());
+
+            timer.cancel();
+
+            assert_eq!(entry.is_valid(), false);
+        }
+
         #[test]
         fn test_acquire_rescheduling_rights() {
             let (_machine, _block, process) = setup();
@@ -353,21 +502,21 @@ mod tests {
         }
 
         #[test]
-        fn test_processes_to_reschedule_with_invalid_entries() {
+        fn test_expired_entries_with_invalid_entries() {
             let (_machine, _block, process) = setup();
             let mut timeouts = Timeouts::new();
             let timeout = Timeout::with_rc(Duration::from_secs(10));
 
             timeouts.insert(process, timeout);
 
-            let (reschedule, expiration) = timeouts.processes_to_reschedule();
+            let (reschedule, _, expiration) = timeouts.expired_entries();
 
             assert!(reschedule.is_empty());
             assert!(expiration.is_none());
         }
 
         #[test]
-        fn test_processes_to_reschedule_with_remaining_time() {
+        fn test_expired_entries_with_remaining_time() {
             let (_machine, _block, process) = setup();
             let mut timeouts = Timeouts::new();
             let timeout = Timeout::with_rc(Duration::from_secs(10));
@@ -375,7 +524,7 @@ mod tests {
             process.suspend_with_timeout(timeout.clone());
             timeouts.insert(process.clone(), timeout);
 
-            let (reschedule, expiration) = timeouts.processes_to_reschedule();
+            let (reschedule, _, expiration) = timeouts.expired_entries();
 
             assert!(reschedule.is_empty());
             assert!(expiration.is_some());
@@ -383,7 +532,7 @@ mod tests {
         }
 
         #[test]
-        fn test_processes_to_reschedule_with_entries_to_reschedule() {
+        fn test_expired_entries_with_entries_to_reschedule() {
             let (_machine, _block, process) = setup();
             let mut timeouts = Timeouts::new();
             let timeout = Timeout::with_rc(Duration::from_secs(0));
@@ -391,10 +540,42 @@ mod tests {
             process.suspend_with_timeout(timeout.clone());
             timeouts.insert(process.clone(), timeout);
 
-            let (reschedule, expiration) = timeouts.processes_to_reschedule();
+            let (reschedule, _, expiration) = timeouts.expired_entries();
 
             assert!(reschedule == vec![process]);
             assert!(expiration.is_none());
         }
+
+        #[test]
+        fn test_expired_entries_with_expired_timers() {
+            let (_machine, _block, process) = setup();
+            let mut timeouts = Timeouts::new();
+            let timeout = Timeout::with_rc(Duration::from_secs(0));
+            let timer = Timer::with_rc(1, process, None);
+
+            timeouts.insert_timer(timer, timeout);
+
+            let (reschedule, timers, expiration) = timeouts.expired_entries();
+
+            assert!(reschedule.is_empty());
+            assert_eq!(timers.len(), 1);
+            assert_eq!(timers[0].id, 1);
+            assert!(expiration.is_none());
+        }
+
+        #[test]
+        fn test_expired_entries_with_cancelled_timers() {
+            let (_machine, _block, process) = setup();
+            let mut timeouts = Timeouts::new();
+            let timeout = Timeout::with_rc(Duration::from_secs(0));
+            let timer = Timer::with_rc(1, process, None);
+
+            timer.cancel();
+            timeouts.insert_timer(timer, timeout);
+
+            let (_, timers, _) = timeouts.expired_entries();
+
+            assert!(timers.is_empty());
+        }
     }
 }
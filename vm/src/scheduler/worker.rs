@@ -1,8 +1,9 @@
 use crate::scheduler::pool_state::PoolState;
+use crate::scheduler::priority::Prioritize;
 use crate::scheduler::queue::RcQueue;
 
 /// A trait providing the basic building blocks of a worker thread.
-pub trait Worker<T: Send> {
+pub trait Worker<T: Send + Prioritize> {
     /// Processes the given job.
     fn process_job(&mut self, job: T);
 
@@ -55,7 +56,16 @@ pub trait Worker<T: Send> {
                 return true;
             }
 
-            if let Some(job) = self.queue().pop() {
+            // Global jobs with a higher priority than our local jobs are
+            // performed first, otherwise they would have to wait until all
+            // local jobs have been performed.
+            let job = self
+                .queue()
+                .highest_priority()
+                .and_then(|priority| self.state().pop_global_above(priority))
+                .or_else(|| self.queue().pop());
+
+            if let Some(job) = job {
                 self.process_job(job);
             } else {
                 return false;
@@ -87,3 +97,77 @@ pub trait Worker<T: Send> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::priority::Priority;
+
+    #[derive(Eq, PartialEq, Debug, Clone, Copy)]
+    struct Job(Priority);
+
+    impl Prioritize for Job {
+        fn priority(&self) -> Priority {
+            self.0
+        }
+    }
+
+    struct TestWorker {
+        state: PoolState<Job>,
+        queue: RcQueue<Job>,
+        performed: Vec<Job>,
+    }
+
+    impl Worker<Job> for TestWorker {
+        fn process_job(&mut self, job: Job) {
+            self.performed.push(job);
+        }
+
+        fn state(&self) -> &PoolState<Job> {
+            &self.state
+        }
+
+        fn queue(&self) -> &RcQueue<Job> {
+            &self.queue
+        }
+    }
+
+    fn worker() -> TestWorker {
+        let state = PoolState::new(1);
+        let queue = state.queues[0].clone();
+
+        TestWorker {
+            state,
+            queue,
+            performed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_run_global_jobs_with_a_higher_priority_first() {
+        let mut worker = worker();
+
+        worker.queue.push_internal(Job(Priority::Low));
+        worker.queue.push_internal(Job(Priority::Low));
+        worker.state.push_global(Job(Priority::High));
+        worker.run();
+
+        assert_eq!(
+            worker.performed,
+            vec![Job(Priority::High), Job(Priority::Low), Job(Priority::Low)]
+        );
+    }
+
+    #[test]
+    fn test_run_local_jobs_before_global_jobs_with_the_same_priority() {
+        let mut worker = worker();
+
+        worker.queue.push_internal(Job(Priority::Normal));
+        worker.state.push_global(Job(Priority::Normal));
+        worker.run();
+
+        assert_eq!(worker.performed.len(), 2);
+        assert!(worker.state.pop_global().is_none());
+        assert_eq!(worker.queue.has_local_jobs(), false);
+    }
+}
@@ -0,0 +1,596 @@
+//! Delivering of Unix signals to processes.
+//!
+//! Processes can subscribe to a set of signals. When a signal is received, a
+//! message is sent to every subscribed process. The message is the (platform
+//! independent) ID of the signal, such as `SIGNAL_TERMINATE`.
+//!
+//! Signal handlers are only installed when a process subscribes to a signal,
+//! and are removed when all processes unsubscribed or terminated. This ensures
+//! that signals nobody subscribed to retain their default behaviour.
+//!
+//! Signal handlers are very limited in what they can do. Our handler only
+//! writes the signal number to a pipe (the "self-pipe trick"). A dedicated
+//! thread reads from this pipe, and sends the messages to the subscribed
+//! processes.
+//!
+//! The same thread also handles the signal used for printing the stack traces
+//! of all processes, as configured using `Config::stack_dump_signal`.
+use crate::object_pointer::ObjectPointer;
+use crate::process::RcProcess;
+use crate::runtime_error::RuntimeError;
+use crate::stack_dump;
+use crate::vm::instructions::process::attempt_to_reschedule_process;
+use crate::vm::state::RcState;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+#[cfg(unix)]
+use {
+    nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag},
+    nix::sys::signal::{
+        sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal,
+    },
+    nix::unistd::{close, pipe, read, write},
+    std::convert::TryFrom,
+    std::os::raw::c_int,
+    std::os::unix::io::RawFd,
+    std::sync::atomic::{AtomicBool, AtomicI32, Ordering},
+};
+
+/// The ID of the `SIGHUP` signal.
+pub const SIGNAL_HANGUP: i64 = 0;
+
+/// The ID of the `SIGINT` signal.
+pub const SIGNAL_INTERRUPT: i64 = 1;
+
+/// The ID of the `SIGTERM` signal.
+pub const SIGNAL_TERMINATE: i64 = 2;
+
+/// The ID of the `SIGCHLD` signal.
+pub const SIGNAL_CHILD: i64 = 3;
+
+/// The ID of the `SIGUSR1` signal.
+pub const SIGNAL_USER1: i64 = 4;
+
+/// The ID of the `SIGUSR2` signal.
+pub const SIGNAL_USER2: i64 = 5;
+
+/// The ID of the `SIGWINCH` signal.
+pub const SIGNAL_WINDOW_CHANGE: i64 = 6;
+
+/// The byte written to the pipe to terminate the worker thread.
+///
+/// No signal uses number 0, so we can safely use it for this purpose.
+#[cfg(unix)]
+const TERMINATE_BYTE: u8 = 0;
+
+/// The file descriptor of the pipe our signal handler writes to.
+///
+/// Signal handlers can't take any arguments, so we have to store this in a
+/// static.
+#[cfg(unix)]
+static PIPE_WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
+#[cfg(unix)]
+extern "C" fn handle_signal(signal: c_int) {
+    let fd = PIPE_WRITE_FD.load(Ordering::Acquire);
+
+    if fd >= 0 {
+        // The write end of the pipe is non-blocking. If the pipe is full we
+        // drop the signal, instead of blocking the thread the handler runs on.
+        let _ = write(fd, &[signal as u8]);
+    }
+}
+
+/// Converts a signal ID to a platform specific signal.
+#[cfg(unix)]
+fn signal_for_id(id: i64) -> Result<Signal, RuntimeError> {
+    let signal = match id {
+        SIGNAL_HANGUP => Signal::SIGHUP,
+        SIGNAL_INTERRUPT => Signal::SIGINT,
+        SIGNAL_TERMINATE => Signal::SIGTERM,
+        SIGNAL_CHILD => Signal::SIGCHLD,
+        SIGNAL_USER1 => Signal::SIGUSR1,
+        SIGNAL_USER2 => Signal::SIGUSR2,
+        SIGNAL_WINDOW_CHANGE => Signal::SIGWINCH,
+        _ => {
+            return Err(RuntimeError::Panic(format!(
+                "{} is not a valid signal",
+                id
+            )))
+        }
+    };
+
+    Ok(signal)
+}
+
+/// Converts a platform specific signal number to a signal ID.
+#[cfg(unix)]
+fn id_for_signal(signal: c_int) -> Option<i64> {
+    let id = match Signal::try_from(signal).ok()? {
+        Signal::SIGHUP => SIGNAL_HANGUP,
+        Signal::SIGINT => SIGNAL_INTERRUPT,
+        Signal::SIGTERM => SIGNAL_TERMINATE,
+        Signal::SIGCHLD => SIGNAL_CHILD,
+        Signal::SIGUSR1 => SIGNAL_USER1,
+        Signal::SIGUSR2 => SIGNAL_USER2,
+        Signal::SIGWINCH => SIGNAL_WINDOW_CHANGE,
+        _ => return None,
+    };
+
+    Some(id)
+}
+
+#[cfg(unix)]
+fn set_handler(
+    signal: Signal,
+    handler: SigHandler,
+) -> Result<(), RuntimeError> {
+    let action = SigAction::new(handler, SaFlags::SA_RESTART, SigSet::empty());
+
+    unsafe { sigaction(signal, &action) }
+        .map(|_| ())
+        .map_err(|err| RuntimeError::Panic(err.to_string()))
+}
+
+/// A signal received by the signals worker.
+#[derive(Eq, PartialEq, Debug)]
+enum Received {
+    /// A signal processes can subscribe to, using the ID of the signal.
+    Signal(i64),
+
+    /// The signal used for printing the stack traces of all processes.
+    StackDump,
+}
+
+/// The processes subscribed to signals, and the pipe used to deliver them.
+pub struct Signals {
+    /// The processes subscribed to every signal, using the signal IDs as the
+    /// keys.
+    subscribers: Mutex<HashMap<i64, Vec<RcProcess>>>,
+
+    /// The read and write ends of the pipe used by the signal handler.
+    #[cfg(unix)]
+    pipe: (RawFd, RawFd),
+
+    /// The number of the signal used for stack dumps, or 0 if stack dumps are
+    /// disabled.
+    #[cfg(unix)]
+    stack_dump_signal: AtomicI32,
+
+    /// A boolean indicating if the signals worker should terminate.
+    #[cfg(unix)]
+    terminated: AtomicBool,
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Signals {
+    /// Returns true if any processes are subscribed to a signal.
+    pub fn has_subscribers(&self) -> bool {
+        self.subscribers.lock().values().any(|processes| {
+            processes.iter().any(|process| !process.is_terminated())
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Signals {
+    pub fn new() -> Self {
+        let (read_fd, write_fd) =
+            pipe().expect("Failed to set up the signals pipe");
+
+        for fd in &[read_fd, write_fd] {
+            fcntl(*fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))
+                .expect("Failed to set FD_CLOEXEC for the signals pipe");
+        }
+
+        fcntl(write_fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))
+            .expect("Failed to set O_NONBLOCK for the signals pipe");
+
+        Signals {
+            subscribers: Mutex::new(HashMap::new()),
+            pipe: (read_fd, write_fd),
+            stack_dump_signal: AtomicI32::new(0),
+            terminated: AtomicBool::new(false),
+        }
+    }
+
+    /// Installs the signal handler for printing stack dumps upon receiving the
+    /// given signal.
+    pub fn enable_stack_dumps(&self, number: i32) -> Result<(), RuntimeError> {
+        let signal = Signal::try_from(number)
+            .map_err(|err| RuntimeError::Panic(err.to_string()))?;
+
+        PIPE_WRITE_FD.store(self.pipe.1, Ordering::Release);
+        set_handler(signal, SigHandler::Handler(handle_signal))?;
+        self.stack_dump_signal.store(number, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Subscribes a process to a signal.
+    ///
+    /// Subscribing a process to the same signal multiple times has no effect.
+    pub fn subscribe(
+        &self,
+        process: &RcProcess,
+        id: i64,
+    ) -> Result<(), RuntimeError> {
+        let signal = signal_for_id(id)?;
+        let mut subscribers = self.subscribers.lock();
+        let processes = subscribers.entry(id).or_default();
+
+        if processes.contains(process) {
+            return Ok(());
+        }
+
+        if processes.is_empty() {
+            PIPE_WRITE_FD.store(self.pipe.1, Ordering::Release);
+            set_handler(signal, SigHandler::Handler(handle_signal))?;
+        }
+
+        processes.push(process.clone());
+        Ok(())
+    }
+
+    /// Unsubscribes a process from a signal.
+    ///
+    /// Once no processes are subscribed to the signal, its default behaviour
+    /// is restored.
+    pub fn unsubscribe(
+        &self,
+        process: &RcProcess,
+        id: i64,
+    ) -> Result<(), RuntimeError> {
+        signal_for_id(id)?;
+
+        let mut subscribers = self.subscribers.lock();
+
+        if let Some(processes) = subscribers.get_mut(&id) {
+            if processes.is_empty() {
+                return Ok(());
+            }
+
+            processes.retain(|p| p != process && !p.is_terminated());
+            self.restore_default_if_unused(id, processes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unsubscribes a process from all signals.
+    ///
+    /// This is called when a process terminates, ensuring the signal handlers
+    /// are removed even if the process didn't unsubscribe itself.
+    pub fn unsubscribe_all(&self, process: &RcProcess) {
+        let mut subscribers = self.subscribers.lock();
+
+        for (id, processes) in subscribers.iter_mut() {
+            if processes.is_empty() {
+                continue;
+            }
+
+            processes.retain(|p| p != process && !p.is_terminated());
+
+            // The process is terminating, so there's nobody to report an error
+            // to. Since the signal IDs are valid, this won't fail in practice.
+            let _ = self.restore_default_if_unused(*id, processes);
+        }
+    }
+
+    /// Sends a signal's ID to all processes subscribed to it.
+    pub fn deliver(&self, state: &RcState, id: i64) {
+        let mut subscribers = self.subscribers.lock();
+
+        if let Some(processes) = subscribers.get_mut(&id) {
+            // Processes that terminated without unsubscribing are removed
+            // here, as there's no point in sending them any messages.
+            processes.retain(|process| !process.is_terminated());
+
+            let _ = self.restore_default_if_unused(id, processes);
+
+            for process in processes.iter() {
+                process.send_message_from_external_process(
+                    ObjectPointer::integer(id),
+                );
+
+                attempt_to_reschedule_process(state, process);
+            }
+        }
+    }
+
+    /// Blocks the current thread until a signal is received.
+    ///
+    /// `None` is returned when the signals worker should terminate.
+    fn wait(&self) -> Option<Received> {
+        let mut buffer = [0];
+
+        loop {
+            match read(self.pipe.0, &mut buffer) {
+                Ok(0) => return None,
+                Ok(_) if buffer[0] == TERMINATE_BYTE => return None,
+                Ok(_) if self.terminated.load(Ordering::Acquire) => {
+                    return None;
+                }
+                Ok(_) => {
+                    let number = c_int::from(buffer[0]);
+
+                    if number == self.stack_dump_signal.load(Ordering::Acquire)
+                    {
+                        return Some(Received::StackDump);
+                    }
+
+                    if let Some(id) = id_for_signal(number) {
+                        return Some(Received::Signal(id));
+                    }
+                }
+                Err(nix::Error::Sys(nix::errno::Errno::EINTR)) => {}
+                Err(err) => panic!("Failed to wait for signals: {}", err),
+            }
+        }
+    }
+
+    pub fn terminate(&self) {
+        self.terminated.store(true, Ordering::Release);
+
+        match write(self.pipe.1, &[TERMINATE_BYTE]) {
+            // If the pipe is full the worker still has signals to read, after
+            // which it will notice it has to terminate.
+            Ok(_) | Err(nix::Error::Sys(nix::errno::Errno::EAGAIN)) => {}
+            Err(err) => {
+                panic!(
+                    "Failed to notify the signals worker to terminate: {}",
+                    err
+                )
+            }
+        }
+    }
+
+    /// Restores the default behaviour of a signal, if no processes are
+    /// subscribed to it.
+    fn restore_default_if_unused(
+        &self,
+        id: i64,
+        processes: &[RcProcess],
+    ) -> Result<(), RuntimeError> {
+        let signal = signal_for_id(id)?;
+
+        // The handler for stack dumps must remain installed.
+        if processes.is_empty() && !self.is_stack_dump_signal(signal) {
+            set_handler(signal, SigHandler::SigDfl)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_stack_dump_signal(&self, signal: Signal) -> bool {
+        signal as c_int == self.stack_dump_signal.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Signals {
+    fn drop(&mut self) {
+        let _ = PIPE_WRITE_FD.compare_exchange(
+            self.pipe.1,
+            -1,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+
+        let _ = close(self.pipe.0);
+        let _ = close(self.pipe.1);
+    }
+}
+
+#[cfg(not(unix))]
+impl Signals {
+    pub fn new() -> Self {
+        Signals {
+            subscribers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn subscribe(
+        &self,
+        _process: &RcProcess,
+        _id: i64,
+    ) -> Result<(), RuntimeError> {
+        Err(RuntimeError::Panic(
+            "Signals aren't supported on this platform".to_string(),
+        ))
+    }
+
+    pub fn unsubscribe(
+        &self,
+        _process: &RcProcess,
+        _id: i64,
+    ) -> Result<(), RuntimeError> {
+        Err(RuntimeError::Panic(
+            "Signals aren't supported on this platform".to_string(),
+        ))
+    }
+
+    pub fn enable_stack_dumps(&self, _number: i32) -> Result<(), RuntimeError> {
+        Err(RuntimeError::Panic(
+            "Signals aren't supported on this platform".to_string(),
+        ))
+    }
+
+    pub fn unsubscribe_all(&self, _process: &RcProcess) {}
+
+    pub fn deliver(&self, _state: &RcState, _id: i64) {}
+
+    fn wait(&self) -> Option<Received> {
+        None
+    }
+
+    pub fn terminate(&self) {}
+}
+
+/// A thread that waits for signals and delivers them to processes.
+pub struct Worker {
+    state: RcState,
+}
+
+impl Worker {
+    pub fn new(state: RcState) -> Self {
+        Worker { state }
+    }
+
+    pub fn run(&self) {
+        let stack_dump_signal = self.state.config.stack_dump_signal;
+
+        // Similar to other configuration settings, invalid signals are
+        // ignored.
+        if stack_dump_signal > 0 {
+            let _ = self.state.signals.enable_stack_dumps(stack_dump_signal);
+        }
+
+        while let Some(received) = self.state.signals.wait() {
+            match received {
+                Received::Signal(id) => {
+                    self.state.signals.deliver(&self.state, id);
+                }
+                Received::StackDump => stack_dump::dump(&self.state),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::vm::test::setup;
+
+    #[test]
+    fn test_subscribe() {
+        let (machine, _block, process) = setup();
+        let signals = &machine.state.signals;
+
+        signals.subscribe(&process, SIGNAL_USER2).unwrap();
+        signals.subscribe(&process, SIGNAL_USER2).unwrap();
+
+        assert_eq!(signals.subscribers.lock()[&SIGNAL_USER2].len(), 1);
+
+        signals.unsubscribe(&process, SIGNAL_USER2).unwrap();
+    }
+
+    #[test]
+    fn test_subscribe_with_invalid_signal() {
+        let (machine, _block, process) = setup();
+
+        assert!(machine.state.signals.subscribe(&process, 42).is_err());
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let (machine, _block, process) = setup();
+        let signals = &machine.state.signals;
+
+        signals.subscribe(&process, SIGNAL_WINDOW_CHANGE).unwrap();
+        signals.unsubscribe(&process, SIGNAL_WINDOW_CHANGE).unwrap();
+
+        assert!(signals.subscribers.lock()[&SIGNAL_WINDOW_CHANGE].is_empty());
+    }
+
+    #[test]
+    fn test_unsubscribe_all() {
+        let (machine, _block, process) = setup();
+        let signals = &machine.state.signals;
+
+        signals.subscribe(&process, SIGNAL_USER1).unwrap();
+        signals.subscribe(&process, SIGNAL_USER2).unwrap();
+        signals.unsubscribe_all(&process);
+
+        let subscribers = signals.subscribers.lock();
+
+        assert!(subscribers[&SIGNAL_USER1].is_empty());
+        assert!(subscribers[&SIGNAL_USER2].is_empty());
+    }
+
+    #[test]
+    fn test_deliver_with_terminated_process() {
+        let (machine, _block, process) = setup();
+        let signals = &machine.state.signals;
+
+        signals.subscribe(&process, SIGNAL_USER1).unwrap();
+        process.set_terminated();
+        signals.deliver(&machine.state, SIGNAL_USER1);
+
+        assert!(signals.subscribers.lock()[&SIGNAL_USER1].is_empty());
+        assert_eq!(signals.has_subscribers(), false);
+    }
+
+    #[test]
+    fn test_deliver() {
+        let (machine, _block, process) = setup();
+
+        machine.state.signals.deliver(&machine.state, SIGNAL_USER1);
+
+        assert!(process.receive_message().is_none());
+
+        machine
+            .state
+            .signals
+            .subscribe(&process, SIGNAL_USER1)
+            .unwrap();
+
+        machine.state.signals.deliver(&machine.state, SIGNAL_USER1);
+
+        let message = process.receive_message().unwrap();
+
+        assert_eq!(message.integer_value().unwrap(), SIGNAL_USER1);
+
+        machine
+            .state
+            .signals
+            .unsubscribe(&process, SIGNAL_USER1)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_wait() {
+        let (machine, _block, _process) = setup();
+        let signals = &machine.state.signals;
+
+        write(signals.pipe.1, &[Signal::SIGHUP as u8]).unwrap();
+
+        assert_eq!(signals.wait(), Some(Received::Signal(SIGNAL_HANGUP)));
+
+        signals.terminate();
+
+        assert_eq!(signals.wait(), None);
+    }
+
+    #[test]
+    fn test_wait_for_stack_dump() {
+        let (machine, _block, _process) = setup();
+        let signals = &machine.state.signals;
+
+        signals
+            .stack_dump_signal
+            .store(Signal::SIGQUIT as i32, Ordering::Release);
+
+        write(signals.pipe.1, &[Signal::SIGQUIT as u8]).unwrap();
+
+        assert_eq!(signals.wait(), Some(Received::StackDump));
+    }
+
+    #[test]
+    fn test_terminate_with_full_pipe() {
+        let (machine, _block, _process) = setup();
+        let signals = &machine.state.signals;
+
+        while write(signals.pipe.1, &[Signal::SIGHUP as u8]).is_ok() {}
+
+        signals.terminate();
+
+        assert_eq!(signals.wait(), None);
+    }
+}
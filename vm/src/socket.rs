@@ -8,6 +8,7 @@ use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
 use crate::socket::socket_address::SocketAddress;
 use socket2::{Domain, SockAddr, Socket as RawSocket, Type};
+use std::fs;
 use std::io;
 use std::io::Read;
 use std::net::Ipv4Addr;
@@ -17,7 +18,26 @@ use std::slice;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(unix)]
-use nix::errno::Errno::{EINPROGRESS, EISCONN};
+use {
+    crate::file::{READ, READ_WRITE, WRITE},
+    nix::errno::Errno::{UnknownErrno, EINPROGRESS, EISCONN},
+    nix::fcntl::{fcntl, FcntlArg, OFlag},
+    nix::sys::socket::{
+        recvmsg, sendmsg, ControlMessage, ControlMessageOwned, MsgFlags,
+    },
+    nix::sys::stat::{fstat, SFlag},
+    nix::sys::uio::{writev, IoVec},
+    std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use nix::sys::{sendfile::sendfile, socket::getsockopt, socket::sockopt};
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+use {
+    std::cmp::min,
+    std::io::{Seek, SeekFrom},
+};
 
 #[cfg(windows)]
 use winapi::shared::winerror::{
@@ -72,6 +92,22 @@ macro_rules! socket_duration_getter {
     };
 }
 
+/// The maximum number of buffers to write in a single vectored write.
+///
+/// POSIX only guarantees support for at least 16 buffers (`_XOPEN_IOV_MAX`),
+/// but all platforms we support allow at least 1024 (`IOV_MAX`).
+const MAX_VECTORED_BUFFERS: usize = 1024;
+
+/// The maximum number of bytes to send when copying a file to a socket without
+/// the use of `sendfile()`.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SEND_FILE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The maximum number of file descriptors to send or receive in a single
+/// message.
+#[cfg(unix)]
+pub const MAX_TRANSFER_HANDLES: usize = 16;
+
 const DOMAIN_IPV4: u8 = 0;
 const DOMAIN_IPV6: u8 = 1;
 const DOMAIN_UNIX: u8 = 2;
@@ -139,6 +175,56 @@ fn socket_output_slice(buffer: &mut Vec<u8>, bytes: usize) -> &mut [u8] {
     unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr().add(len), bytes) }
 }
 
+/// Converts an error produced by the nix crate into an IO error.
+#[cfg(unix)]
+fn nix_to_io_error(error: nix::Error) -> io::Error {
+    io::Error::from(error.as_errno().unwrap_or(UnknownErrno))
+}
+
+/// Returns the flags to use when receiving file descriptors.
+///
+/// Where supported we set the close-on-exec flag for received descriptors, just
+/// like the standard library does for descriptors it creates.
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn receive_handles_flags() -> MsgFlags {
+    MsgFlags::MSG_CMSG_CLOEXEC
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))
+))]
+fn receive_handles_flags() -> MsgFlags {
+    MsgFlags::empty()
+}
+
+/// Returns the path of a file descriptor, if it can be determined.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn file_descriptor_path(fd: RawFd) -> String {
+    fs::read_link(format!("/proc/self/fd/{}", fd))
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| String::new())
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn file_descriptor_path(_fd: RawFd) -> String {
+    String::new()
+}
+
 fn update_buffer_length_and_capacity(buffer: &mut Vec<u8>, read: usize) {
     unsafe {
         buffer.set_len(buffer.len() + read);
@@ -147,6 +233,61 @@ fn update_buffer_length_and_capacity(buffer: &mut Vec<u8>, read: usize) {
     buffer.shrink_to_fit();
 }
 
+/// A file descriptor received over a Unix socket.
+#[cfg(unix)]
+pub enum ReceivedHandle {
+    /// A socket, made non-blocking upon receiving it.
+    Socket(Socket),
+
+    /// A file, its open mode (e.g. `file::READ`), and its path.
+    File(fs::File, i64, String),
+}
+
+#[cfg(unix)]
+impl ReceivedHandle {
+    /// Wraps a raw file descriptor received over a socket.
+    ///
+    /// The descriptor is owned by the returned value, and will be closed when
+    /// the value is dropped.
+    fn from_raw_fd(fd: RawFd) -> Result<Self, RuntimeError> {
+        // The descriptor is wrapped first, ensuring it's closed if any of the
+        // operations below fail.
+        let file = unsafe { fs::File::from_raw_fd(fd) };
+        let kind = SFlag::from_bits_truncate(
+            fstat(fd).map_err(nix_to_io_error)?.st_mode,
+        ) & SFlag::S_IFMT;
+
+        if kind == SFlag::S_IFSOCK {
+            let socket = unsafe { RawSocket::from_raw_fd(file.into_raw_fd()) };
+
+            // We only check the address family, as the address of a Unix
+            // socket may be unnamed.
+            let unix = socket.local_addr()?.family()
+                == nix::libc::AF_UNIX as nix::libc::sa_family_t;
+
+            socket.set_nonblocking(true)?;
+
+            return Ok(ReceivedHandle::Socket(Socket {
+                inner: ClosableSocket::new(socket),
+                registered: AtomicBool::new(false),
+                unix,
+            }));
+        }
+
+        let flags = OFlag::from_bits_truncate(
+            fcntl(fd, FcntlArg::F_GETFL).map_err(nix_to_io_error)?,
+        );
+
+        let mode = match flags & OFlag::O_ACCMODE {
+            OFlag::O_WRONLY => WRITE,
+            OFlag::O_RDWR => READ_WRITE,
+            _ => READ,
+        };
+
+        Ok(ReceivedHandle::File(file, mode, file_descriptor_path(fd)))
+    }
+}
+
 /// A nonblocking socket that can be registered with a `NetworkPoller`.
 pub struct Socket {
     /// The raw socket.
@@ -344,6 +485,187 @@ impl Socket {
         Ok(self.inner.send_to(buffer, &sockaddr)?)
     }
 
+    /// Writes multiple buffers to the socket using a single system call.
+    ///
+    /// Like a regular write, this may only write part of the input. The
+    /// returned value is the total number of bytes written, across all
+    /// buffers.
+    #[cfg(unix)]
+    pub fn write_vectored(
+        &self,
+        buffers: &[&[u8]],
+    ) -> Result<usize, RuntimeError> {
+        let iovecs: Vec<IoVec<&[u8]>> = buffers
+            .iter()
+            .take(MAX_VECTORED_BUFFERS)
+            .map(|buffer| IoVec::from_slice(buffer))
+            .collect();
+
+        writev(self.inner.as_raw_fd(), &iovecs)
+            .map_err(|err| nix_to_io_error(err).into())
+    }
+
+    #[cfg(not(unix))]
+    pub fn write_vectored(
+        &self,
+        buffers: &[&[u8]],
+    ) -> Result<usize, RuntimeError> {
+        let bytes = buffers.iter().take(MAX_VECTORED_BUFFERS).fold(
+            Vec::new(),
+            |mut bytes, buffer| {
+                bytes.extend_from_slice(buffer);
+                bytes
+            },
+        );
+
+        Ok(self.inner.send(&bytes)?)
+    }
+
+    /// Sends up to `bytes` bytes from a file to the socket, starting at the
+    /// current position of the file.
+    ///
+    /// The data is copied by the kernel, without it ever being copied into an
+    /// Inko object. The file position is advanced by the number of bytes sent.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_file(
+        &self,
+        file: &mut fs::File,
+        bytes: usize,
+    ) -> Result<usize, RuntimeError> {
+        sendfile(self.inner.as_raw_fd(), file.as_raw_fd(), None, bytes)
+            .map_err(|err| nix_to_io_error(err).into())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn send_file(
+        &self,
+        file: &mut fs::File,
+        bytes: usize,
+    ) -> Result<usize, RuntimeError> {
+        let mut buffer = vec![0; min(bytes, SEND_FILE_BUFFER_SIZE)];
+        let read = file.read(&mut buffer)?;
+
+        if read == 0 {
+            return Ok(0);
+        }
+
+        // The socket is non-blocking, so we may not be able to send all (or
+        // any) of the data we read. Any data not sent is "pushed back" into
+        // the file by moving the cursor back, allowing a retry to pick up
+        // where we left off.
+        let sent = match self.inner.send(&buffer[0..read]) {
+            Ok(sent) => sent,
+            Err(err) => {
+                file.seek(SeekFrom::Current(-(read as i64)))?;
+
+                return Err(err.into());
+            }
+        };
+
+        if sent < read {
+            file.seek(SeekFrom::Current(-((read - sent) as i64)))?;
+        }
+
+        Ok(sent)
+    }
+
+    /// Returns the process ID, user ID, and group ID of the peer of a Unix
+    /// socket.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn peer_credentials(&self) -> Result<(i64, i64, i64), RuntimeError> {
+        let creds =
+            getsockopt(self.inner.as_raw_fd(), sockopt::PeerCredentials)
+                .map_err(nix_to_io_error)?;
+
+        Ok((
+            i64::from(creds.pid()),
+            i64::from(creds.uid()),
+            i64::from(creds.gid()),
+        ))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn peer_credentials(&self) -> Result<(i64, i64, i64), RuntimeError> {
+        Err(RuntimeError::Exception(
+            "Retrieving peer credentials isn't supported on this platform"
+                .to_string(),
+        ))
+    }
+
+    /// Sends one or more file descriptors to the peer of a Unix socket.
+    ///
+    /// The descriptors are sent as `SCM_RIGHTS` ancillary data, along with a
+    /// single byte of regular data; some platforms don't support sending
+    /// ancillary data without any regular data.
+    #[cfg(unix)]
+    pub fn send_handles(&self, fds: &[RawFd]) -> Result<usize, RuntimeError> {
+        if fds.len() > MAX_TRANSFER_HANDLES {
+            return Err(RuntimeError::Panic(format!(
+                "At most {} handles can be sent at once, but {} were given",
+                MAX_TRANSFER_HANDLES,
+                fds.len()
+            )));
+        }
+
+        let data = [0];
+        let iov = [IoVec::from_slice(&data)];
+        let cmsgs = [ControlMessage::ScmRights(fds)];
+
+        sendmsg(
+            self.inner.as_raw_fd(),
+            &iov,
+            &cmsgs,
+            MsgFlags::empty(),
+            None,
+        )
+        .map_err(|err| nix_to_io_error(err).into())
+    }
+
+    /// Receives file descriptors sent using `Socket::send_handles()`.
+    ///
+    /// If the peer sent regular data instead of file descriptors, a single
+    /// byte of this data is consumed and an empty list is returned.
+    ///
+    /// If the peer sent more descriptors than we can receive at once, the
+    /// kernel discards the excess descriptors and an error is returned.
+    #[cfg(unix)]
+    pub fn receive_handles(&self) -> Result<Vec<ReceivedHandle>, RuntimeError> {
+        let mut data = [0];
+        let iov = [IoVec::from_mut_slice(&mut data)];
+        let mut cmsg_buffer = nix::cmsg_space!([RawFd; MAX_TRANSFER_HANDLES]);
+
+        let msg = recvmsg(
+            self.inner.as_raw_fd(),
+            &iov,
+            Some(&mut cmsg_buffer),
+            receive_handles_flags(),
+        )
+        .map_err(nix_to_io_error)?;
+
+        let mut fds = Vec::new();
+
+        for cmsg in msg.cmsgs() {
+            if let ControlMessageOwned::ScmRights(mut received) = cmsg {
+                fds.append(&mut received);
+            }
+        }
+
+        // Every descriptor is wrapped before returning any errors, ensuring
+        // that none of them are leaked.
+        let handles: Vec<_> =
+            fds.into_iter().map(ReceivedHandle::from_raw_fd).collect();
+
+        if msg.flags.contains(MsgFlags::MSG_CTRUNC) {
+            return Err(RuntimeError::Exception(format!(
+                "The received handles were truncated, as at most {} handles \
+                 can be received at once",
+                MAX_TRANSFER_HANDLES
+            )));
+        }
+
+        handles.into_iter().collect()
+    }
+
     pub fn local_address(&self) -> Result<(String, i64), RuntimeError> {
         let sockaddr = self.inner.local_addr()?;
 
@@ -448,6 +770,13 @@ impl Socket {
     }
 }
 
+#[cfg(unix)]
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
 impl io::Write for Socket {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.inner.write(buf)
@@ -479,6 +808,20 @@ impl Clone for Socket {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::env::temp_dir;
+    use std::net::UdpSocket;
+
+    #[cfg(unix)]
+    fn unix_socket_pair() -> (Socket, Socket) {
+        let (a, b) = std::os::unix::net::UnixStream::pair().unwrap();
+        let wrap = |stream| Socket {
+            inner: ClosableSocket::new(RawSocket::from(stream)),
+            registered: AtomicBool::new(false),
+            unix: true,
+        };
+
+        (wrap(a), wrap(b))
+    }
 
     #[test]
     fn test_clone() {
@@ -491,4 +834,117 @@ mod tests {
         assert_eq!(socket2.registered.load(Ordering::Acquire), false);
         assert_eq!(socket2.unix, false);
     }
+
+    #[test]
+    fn test_write_vectored() {
+        let output = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = output.local_addr().unwrap().port();
+        let socket = Socket::new(0, 1).unwrap();
+        let mut buffer = [0; 6];
+
+        socket.connect("127.0.0.1", port).unwrap();
+
+        assert_eq!(socket.write_vectored(&[b"foo", b"bar"]).unwrap(), 6);
+
+        output.recv(&mut buffer).unwrap();
+
+        assert_eq!(&buffer, b"foobar");
+    }
+
+    #[test]
+    fn test_send_file() {
+        let output = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = output.local_addr().unwrap().port();
+        let socket = Socket::new(0, 1).unwrap();
+        let path = temp_dir().join("inko_test_socket_send_file");
+        let mut buffer = [0; 3];
+
+        fs::write(&path, b"foobar").unwrap();
+
+        let mut file = fs::File::open(&path).unwrap();
+
+        socket.connect("127.0.0.1", port).unwrap();
+
+        assert_eq!(socket.send_file(&mut file, 3).unwrap(), 3);
+
+        output.recv(&mut buffer).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(&buffer, b"foo");
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn test_peer_credentials() {
+        let (socket, _peer) = unix_socket_pair();
+        let (pid, _, _) = socket.peer_credentials().unwrap();
+
+        assert_eq!(pid, i64::from(std::process::id()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_send_and_receive_handles() {
+        let (sender, receiver) = unix_socket_pair();
+        let (to_send, _peer) = unix_socket_pair();
+        let path = temp_dir().join("inko_test_socket_send_handles");
+
+        fs::write(&path, b"foo").unwrap();
+
+        let file = fs::File::open(&path).unwrap();
+
+        assert_eq!(
+            sender
+                .send_handles(&[to_send.as_raw_fd(), file.as_raw_fd()])
+                .unwrap(),
+            1
+        );
+
+        let handles = receiver.receive_handles().unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(handles.len(), 2);
+
+        match &handles[0] {
+            ReceivedHandle::Socket(socket) => assert!(socket.is_unix()),
+            _ => panic!("Expected a socket"),
+        }
+
+        match &handles[1] {
+            ReceivedHandle::File(_, mode, _) => assert_eq!(*mode, READ),
+            _ => panic!("Expected a file"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_receive_handles_without_handles() {
+        let (sender, receiver) = unix_socket_pair();
+
+        sender.inner.send(b"a").unwrap();
+
+        assert!(receiver.receive_handles().unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_receive_handles_with_too_many_handles() {
+        let (sender, receiver) = unix_socket_pair();
+        let fds = [sender.inner.as_raw_fd(); MAX_TRANSFER_HANDLES + 4];
+        let data = [0];
+        let iov = [IoVec::from_slice(&data)];
+        let cmsgs = [ControlMessage::ScmRights(&fds)];
+
+        sendmsg(
+            sender.inner.as_raw_fd(),
+            &iov,
+            &cmsgs,
+            MsgFlags::empty(),
+            None,
+        )
+        .unwrap();
+
+        assert!(receiver.receive_handles().is_err());
+    }
 }
@@ -0,0 +1,91 @@
+//! Printing the stack traces of all processes.
+//!
+//! Stack dumps are triggered by sending the VM a signal, SIGQUIT by default.
+//! Dumping the stack traces doesn't terminate the VM, making it possible to
+//! find out where the processes of a hanging program are stuck.
+//!
+//! The stack trace of a process can only be read when the process isn't
+//! running. The traces of suspended processes are printed right away, while
+//! holding their running lock so they can't be rescheduled in the mean time.
+//! Other processes are requested to print their own trace the next time they
+//! run.
+use crate::runtime_panic;
+use crate::vm::state::State;
+
+/// Prints the stack traces of all processes to STDERR.
+pub fn dump(state: &State) {
+    let processes = state.processes.processes();
+
+    eprintln!("Stack traces of {} processes:", processes.len());
+
+    for process in processes {
+        if let Some(_running) = process.try_lock_running() {
+            let process_state = process.state();
+
+            if process_state.is_suspended() {
+                runtime_panic::display_stack(
+                    &process,
+                    process_state,
+                    state.config.panic_format,
+                );
+                continue;
+            }
+        }
+
+        process.request_stack_dump();
+
+        // The process may have suspended itself before it could observe the
+        // request, in which case we print the stack trace ourselves.
+        if let Some(_running) = process.try_lock_running() {
+            let process_state = process.state();
+
+            if process_state.is_suspended() && process.take_stack_dump_request()
+            {
+                runtime_panic::display_stack(
+                    &process,
+                    process_state,
+                    state.config.panic_format,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::test::setup;
+
+    #[test]
+    fn test_dump_with_running_process() {
+        let (machine, _block, process) = setup();
+
+        dump(&machine.state);
+
+        assert!(process.take_stack_dump_request());
+    }
+
+    #[test]
+    fn test_dump_with_suspended_process() {
+        let (machine, _block, process) = setup();
+
+        process.suspend_without_timeout();
+        dump(&machine.state);
+
+        assert_eq!(process.take_stack_dump_request(), false);
+    }
+
+    #[test]
+    fn test_dump_with_suspended_process_that_is_still_running() {
+        let (machine, _block, process) = setup();
+
+        process.suspend_without_timeout();
+
+        let running = process.lock_running();
+
+        dump(&machine.state);
+        drop(running);
+
+        assert!(process.take_stack_dump_request());
+    }
+}
@@ -182,6 +182,80 @@ pub enum Opcode {
     GetAttributeInSelf,
     MoveResult,
     FilePath,
+    SocketWriteVectored,
+    SocketSendFile,
+    SocketPeerCredentials,
+    SocketSendHandles,
+    SocketReceiveHandles,
+    SignalSubscribe,
+    SignalUnsubscribe,
+    ProcessSetPriority,
+    ProcessPriority,
+    TimerStart,
+    TimerCancel,
+    ProcessTerminate,
+    ProcessTerminationReason,
+    ProcessList,
+    ProcessInfo,
+    ProcessSetReductionBudget,
+    GetLocalAttribute,
+    IntegerCompareGoto,
+    IntegerAddLiteral,
+    FloatSqrt,
+    FloatPow,
+    FloatExp,
+    FloatLn,
+    FloatLog2,
+    FloatLog10,
+    FloatSin,
+    FloatCos,
+    FloatTan,
+    FloatAsin,
+    FloatAcos,
+    FloatAtan,
+    FloatAtan2,
+    FloatFma,
+    IntegerPow,
+    IntegerGcd,
+    IntegerSqrt,
+    DecimalFromString,
+    DecimalToString,
+    DecimalAdd,
+    DecimalSub,
+    DecimalMul,
+    DecimalDiv,
+    DecimalRound,
+    DecimalScale,
+    DecimalSmaller,
+    DecimalGreater,
+    DecimalEquals,
+    DecimalGreaterOrEqual,
+    DecimalSmallerOrEqual,
+    ByteArraySlice,
+    ByteArrayCopyRange,
+    ByteArrayAppend,
+    ByteArrayFill,
+    ByteArrayFind,
+    ByteArrayReadInteger,
+    ByteArrayWriteInteger,
+    ByteArrayReadFloat,
+    ByteArrayWriteFloat,
+    StringFind,
+    StringFindLast,
+    StringStartsWith,
+    StringEndsWith,
+    StringSplit,
+    StringReplace,
+    StringTrim,
+    StringTrimStart,
+    StringTrimEnd,
+    StringCharacters,
+    StringGraphemes,
+    RegexCompile,
+    RegexMatch,
+    RegexFindAll,
+    RegexSplit,
+    RegexReplace,
 }
 
 /// A fixed-width VM instruction.
@@ -216,6 +290,11 @@ impl Instruction {
     pub fn arg(&self, index: usize) -> u16 {
         self.arguments[index]
     }
+
+    /// Sets the value of the given instruction argument.
+    pub fn set_arg(&mut self, index: usize, value: u16) {
+        self.arguments[index] = value;
+    }
 }
 
 #[cfg(test)]
@@ -234,6 +313,15 @@ mod tests2 {
         assert_eq!(ins.arg(0), 1);
     }
 
+    #[test]
+    fn test_set_arg() {
+        let mut ins = new_instruction();
+
+        ins.set_arg(3, 4);
+
+        assert_eq!(ins.arg(3), 4);
+    }
+
     #[test]
     fn test_type_size() {
         assert_eq!(size_of::<Instruction>(), 16);
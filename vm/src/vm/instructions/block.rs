@@ -21,6 +21,19 @@ macro_rules! prepare_block_arguments {
     }};
 }
 
+/// Returns an error if running another block would exceed the maximum call
+/// depth of the process.
+fn check_call_depth(process: &RcProcess) -> Result<(), String> {
+    if process.call_depth_exceeded() {
+        return Err(format!(
+            "The maximum call depth of {} has been exceeded",
+            process.max_call_depth()
+        ));
+    }
+
+    Ok(())
+}
+
 #[inline(always)]
 pub fn block_get_receiver(context: &ExecutionContext) -> ObjectPointer {
     *context.binding.receiver()
@@ -35,6 +48,9 @@ pub fn run_block(
     amount: u16,
 ) -> Result<(), String> {
     let block = block_ptr.block_value()?;
+
+    check_call_depth(process)?;
+
     let mut new_context = ExecutionContext::from_block(&block);
 
     prepare_block_arguments!(context, new_context, start_reg, amount);
@@ -53,6 +69,9 @@ pub fn run_block_with_receiver(
     amount: u16,
 ) -> Result<(), String> {
     let block = block_ptr.block_value()?;
+
+    check_call_depth(process)?;
+
     let mut new_context =
         ExecutionContext::from_block_with_receiver(&block, receiver_ptr);
 
@@ -6,6 +6,7 @@ use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
 use crate::slicing;
 use crate::vm::state::RcState;
+use memchr::{memchr, memmem};
 use std::u8;
 
 const MIN_BYTE: i64 = u8::MIN as i64;
@@ -140,6 +141,346 @@ pub fn byte_array_to_string(
     ))
 }
 
+#[inline(always)]
+pub fn byte_array_slice(
+    state: &RcState,
+    process: &RcProcess,
+    array_ptr: ObjectPointer,
+    start_ptr: ObjectPointer,
+    length_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let bytes = array_ptr.byte_array_value()?;
+    let (start, end) = slice_range(bytes, start_ptr, length_ptr)?;
+
+    Ok(process.allocate(
+        object_value::byte_array(bytes[start..end].to_vec()),
+        state.byte_array_prototype,
+    ))
+}
+
+#[inline(always)]
+pub fn byte_array_copy_range(
+    state: &RcState,
+    process: &RcProcess,
+    target_ptr: ObjectPointer,
+    offset_ptr: ObjectPointer,
+    source_ptr: ObjectPointer,
+    start_ptr: ObjectPointer,
+    length_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let source = source_ptr.byte_array_value()?;
+    let (start, end) = slice_range(source, start_ptr, length_ptr)?;
+
+    // The source and target may be the same ByteArray, so we copy the bytes
+    // before borrowing the target mutably.
+    let chunk = source[start..end].to_vec();
+    let target = target_ptr.byte_array_value_mut()?;
+    let offset = slicing::slice_index_to_usize(offset_ptr, target.len())?;
+
+    write_bytes(target, offset, &chunk)?;
+
+    Ok(process.allocate_usize(chunk.len(), state.integer_prototype))
+}
+
+#[inline(always)]
+pub fn byte_array_append(
+    target_ptr: ObjectPointer,
+    source_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    if target_ptr == source_ptr {
+        let target = target_ptr.byte_array_value_mut()?;
+        let copy = target.clone();
+
+        target.extend_from_slice(&copy);
+    } else {
+        let source = source_ptr.byte_array_value()?;
+
+        target_ptr.byte_array_value_mut()?.extend_from_slice(source);
+    }
+
+    Ok(target_ptr)
+}
+
+#[inline(always)]
+pub fn byte_array_fill(
+    array_ptr: ObjectPointer,
+    value_ptr: ObjectPointer,
+    start_ptr: ObjectPointer,
+    length_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let bytes = array_ptr.byte_array_value_mut()?;
+    let value = integer_to_byte(value_ptr)?;
+    let start = slicing::slice_index_to_usize(start_ptr, bytes.len())?;
+
+    if start > bytes.len() {
+        return Err(RuntimeError::out_of_bounds(start));
+    }
+
+    let (start, end) = slice_range(bytes, start_ptr, length_ptr)?;
+
+    bytes[start..end].fill(value);
+
+    Ok(array_ptr)
+}
+
+#[inline(always)]
+pub fn byte_array_find(
+    state: &RcState,
+    process: &RcProcess,
+    array_ptr: ObjectPointer,
+    needle_ptr: ObjectPointer,
+    start_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let bytes = array_ptr.byte_array_value()?;
+    let start = slicing::slice_index_to_usize(start_ptr, bytes.len())?;
+
+    if start > bytes.len() {
+        return Ok(state.nil_object);
+    }
+
+    let haystack = &bytes[start..];
+    let position = if let Ok(needle) = needle_ptr.byte_array_value() {
+        memmem::find(haystack, needle)
+    } else {
+        memchr(integer_to_byte(needle_ptr)?, haystack)
+    };
+
+    Ok(position.map_or(state.nil_object, |index| {
+        process.allocate_usize(start + index, state.integer_prototype)
+    }))
+}
+
+#[inline(always)]
+pub fn byte_array_read_integer(
+    state: &RcState,
+    process: &RcProcess,
+    array_ptr: ObjectPointer,
+    offset_ptr: ObjectPointer,
+    size_ptr: ObjectPointer,
+    signed_ptr: ObjectPointer,
+    big_endian_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let size = integer_size(size_ptr)?;
+    let chunk = read_bytes(array_ptr.byte_array_value()?, offset_ptr, size)?;
+    let mut buffer = [0_u8; 8];
+
+    let value = if big_endian_ptr == state.true_object {
+        buffer[8 - size..].copy_from_slice(chunk);
+        u64::from_be_bytes(buffer)
+    } else {
+        buffer[..size].copy_from_slice(chunk);
+        u64::from_le_bytes(buffer)
+    };
+
+    if signed_ptr == state.true_object {
+        // Shifting the value to the left and back again sign-extends it, as
+        // the right shift on an i64 is an arithmetic shift.
+        let shift = 64 - (size * 8);
+        let signed = ((value << shift) as i64) >> shift;
+
+        Ok(process.allocate_i64(signed, state.integer_prototype))
+    } else {
+        Ok(process.allocate_u64(value, state.integer_prototype))
+    }
+}
+
+#[inline(always)]
+pub fn byte_array_write_integer(
+    state: &RcState,
+    array_ptr: ObjectPointer,
+    offset_ptr: ObjectPointer,
+    value_ptr: ObjectPointer,
+    size_ptr: ObjectPointer,
+    big_endian_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let size = integer_size(size_ptr)?;
+    let value = integer_to_unsigned(value_ptr, size)?;
+
+    let chunk = if big_endian_ptr == state.true_object {
+        value.to_be_bytes()[8 - size..].to_vec()
+    } else {
+        value.to_le_bytes()[..size].to_vec()
+    };
+
+    let bytes = array_ptr.byte_array_value_mut()?;
+    let offset = slicing::slice_index_to_usize(offset_ptr, bytes.len())?;
+
+    write_bytes(bytes, offset, &chunk)?;
+
+    Ok(value_ptr)
+}
+
+#[inline(always)]
+pub fn byte_array_read_float(
+    state: &RcState,
+    process: &RcProcess,
+    array_ptr: ObjectPointer,
+    offset_ptr: ObjectPointer,
+    size_ptr: ObjectPointer,
+    big_endian_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let size = float_size(size_ptr)?;
+    let chunk = read_bytes(array_ptr.byte_array_value()?, offset_ptr, size)?;
+    let big_endian = big_endian_ptr == state.true_object;
+
+    let value = if size == 4 {
+        let mut buffer = [0_u8; 4];
+
+        buffer.copy_from_slice(chunk);
+
+        f64::from(if big_endian {
+            f32::from_be_bytes(buffer)
+        } else {
+            f32::from_le_bytes(buffer)
+        })
+    } else {
+        let mut buffer = [0_u8; 8];
+
+        buffer.copy_from_slice(chunk);
+
+        if big_endian {
+            f64::from_be_bytes(buffer)
+        } else {
+            f64::from_le_bytes(buffer)
+        }
+    };
+
+    Ok(process.allocate_f64(value, state.float_prototype))
+}
+
+#[inline(always)]
+pub fn byte_array_write_float(
+    state: &RcState,
+    array_ptr: ObjectPointer,
+    offset_ptr: ObjectPointer,
+    value_ptr: ObjectPointer,
+    size_ptr: ObjectPointer,
+    big_endian_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let size = float_size(size_ptr)?;
+    let value = value_ptr.float_value()?;
+    let big_endian = big_endian_ptr == state.true_object;
+
+    let chunk = match (size, big_endian) {
+        (4, true) => (value as f32).to_be_bytes().to_vec(),
+        (4, false) => (value as f32).to_le_bytes().to_vec(),
+        (_, true) => value.to_be_bytes().to_vec(),
+        (_, false) => value.to_le_bytes().to_vec(),
+    };
+
+    let bytes = array_ptr.byte_array_value_mut()?;
+    let offset = slicing::slice_index_to_usize(offset_ptr, bytes.len())?;
+
+    write_bytes(bytes, offset, &chunk)?;
+
+    Ok(value_ptr)
+}
+
+/// Returns the start and end index of a slice, limiting the end to the number
+/// of bytes available.
+fn slice_range(
+    bytes: &[u8],
+    start_ptr: ObjectPointer,
+    length_ptr: ObjectPointer,
+) -> Result<(usize, usize), String> {
+    let start =
+        slicing::slice_index_to_usize(start_ptr, bytes.len())?.min(bytes.len());
+    let end = start
+        .saturating_add(range_length(length_ptr)?)
+        .min(bytes.len());
+
+    Ok((start, end))
+}
+
+/// Returns the length of a range, treating negative lengths as an empty range.
+fn range_length(length_ptr: ObjectPointer) -> Result<usize, String> {
+    match length_ptr.integer_value() {
+        Ok(length) if length < 0 => Ok(0),
+        _ => length_ptr.usize_value(),
+    }
+}
+
+/// Returns `size` bytes starting at the given offset.
+fn read_bytes(
+    bytes: &[u8],
+    offset_ptr: ObjectPointer,
+    size: usize,
+) -> Result<&[u8], RuntimeError> {
+    let offset = slicing::slice_index_to_usize(offset_ptr, bytes.len())?;
+
+    match offset.checked_add(size) {
+        Some(end) if end <= bytes.len() => Ok(&bytes[offset..end]),
+        _ => Err(RuntimeError::out_of_bounds(offset)),
+    }
+}
+
+/// Writes the bytes of `chunk` starting at the given offset, overwriting
+/// existing bytes and growing the array if necessary.
+fn write_bytes(
+    bytes: &mut Vec<u8>,
+    offset: usize,
+    chunk: &[u8],
+) -> Result<(), RuntimeError> {
+    if offset > bytes.len() {
+        return Err(RuntimeError::out_of_bounds(offset));
+    }
+
+    let overwrite = (bytes.len() - offset).min(chunk.len());
+
+    bytes[offset..offset + overwrite].copy_from_slice(&chunk[..overwrite]);
+    bytes.extend_from_slice(&chunk[overwrite..]);
+
+    Ok(())
+}
+
+fn integer_size(pointer: ObjectPointer) -> Result<usize, String> {
+    match pointer.integer_value()? {
+        size @ 1 | size @ 2 | size @ 4 | size @ 8 => Ok(size as usize),
+        size => Err(format!("{} is not a valid integer size in bytes", size)),
+    }
+}
+
+fn float_size(pointer: ObjectPointer) -> Result<usize, String> {
+    match pointer.integer_value()? {
+        size @ 4 | size @ 8 => Ok(size as usize),
+        size => Err(format!("{} is not a valid float size in bytes", size)),
+    }
+}
+
+/// Converts an integer to its unsigned two's complement representation,
+/// checking if it fits in the given number of bytes.
+fn integer_to_unsigned(
+    pointer: ObjectPointer,
+    size: usize,
+) -> Result<u64, String> {
+    if pointer.is_bigint() {
+        // Only unsigned 64 bits integers may be too large for an i64.
+        return if size == 8 {
+            pointer.u64_value()
+        } else {
+            Err(format!(
+                "{} does not fit in {} bytes",
+                pointer.bigint_value()?,
+                size
+            ))
+        };
+    }
+
+    let value = pointer.integer_value()?;
+
+    if size < 8 {
+        let bits = size * 8;
+        let min = -(1_i64 << (bits - 1));
+        let max = (1_i64 << bits) - 1;
+
+        if value < min || value > max {
+            return Err(format!("{} does not fit in {} bytes", value, size));
+        }
+    }
+
+    Ok(value as u64)
+}
+
 fn integer_to_byte(pointer: ObjectPointer) -> Result<u8, String> {
     let value = pointer.integer_value()?;
 
@@ -0,0 +1,147 @@
+//! VM functions for working with arbitrary precision decimals.
+use crate::decimal::{Decimal, RoundingMode};
+use crate::object_pointer::ObjectPointer;
+use crate::object_value;
+use crate::process::RcProcess;
+use crate::runtime_error::RuntimeError;
+use crate::vm::state::RcState;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+#[inline(always)]
+pub fn decimal_from_string(
+    state: &RcState,
+    process: &RcProcess,
+    string: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let decimal = Decimal::parse(string.string_value()?.as_slice())
+        .map_err(RuntimeError::Exception)?;
+
+    Ok(allocate(state, process, decimal))
+}
+
+#[inline(always)]
+pub fn decimal_to_string(
+    state: &RcState,
+    process: &RcProcess,
+    decimal: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = decimal.decimal_value()?.to_string();
+
+    Ok(process.allocate(object_value::string(string), state.string_prototype))
+}
+
+#[inline(always)]
+pub fn decimal_add(
+    state: &RcState,
+    process: &RcProcess,
+    left: ObjectPointer,
+    right: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let result = left.decimal_value()?.add(right.decimal_value()?);
+
+    Ok(allocate(state, process, result))
+}
+
+#[inline(always)]
+pub fn decimal_sub(
+    state: &RcState,
+    process: &RcProcess,
+    left: ObjectPointer,
+    right: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let result = left.decimal_value()?.sub(right.decimal_value()?);
+
+    Ok(allocate(state, process, result))
+}
+
+#[inline(always)]
+pub fn decimal_mul(
+    state: &RcState,
+    process: &RcProcess,
+    left: ObjectPointer,
+    right: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let result = left.decimal_value()?.mul(right.decimal_value()?)?;
+
+    Ok(allocate(state, process, result))
+}
+
+#[inline(always)]
+pub fn decimal_div(
+    state: &RcState,
+    process: &RcProcess,
+    left: ObjectPointer,
+    right: ObjectPointer,
+    scale: ObjectPointer,
+    mode: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let result = left.decimal_value()?.div(
+        right.decimal_value()?,
+        scale_value(scale)?,
+        rounding_mode(mode)?,
+    )?;
+
+    Ok(allocate(state, process, result))
+}
+
+#[inline(always)]
+pub fn decimal_round(
+    state: &RcState,
+    process: &RcProcess,
+    decimal: ObjectPointer,
+    scale: ObjectPointer,
+    mode: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let result = decimal
+        .decimal_value()?
+        .round(scale_value(scale)?, rounding_mode(mode)?)?;
+
+    Ok(allocate(state, process, result))
+}
+
+#[inline(always)]
+pub fn decimal_scale(
+    state: &RcState,
+    process: &RcProcess,
+    decimal: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let scale = decimal.decimal_value()?.scale();
+
+    Ok(process.allocate_usize(scale as usize, state.integer_prototype))
+}
+
+#[inline(always)]
+pub fn decimal_compare(
+    state: &RcState,
+    left: ObjectPointer,
+    right: ObjectPointer,
+    matches: fn(Ordering) -> bool,
+) -> Result<ObjectPointer, String> {
+    let ordering = left.decimal_value()?.cmp(right.decimal_value()?);
+
+    if matches(ordering) {
+        Ok(state.true_object)
+    } else {
+        Ok(state.false_object)
+    }
+}
+
+fn allocate(
+    state: &RcState,
+    process: &RcProcess,
+    decimal: Decimal,
+) -> ObjectPointer {
+    process.allocate(object_value::decimal(decimal), state.decimal_prototype)
+}
+
+fn scale_value(pointer: ObjectPointer) -> Result<u32, String> {
+    let scale = pointer.integer_value()?;
+
+    u32::try_from(scale)
+        .map_err(|_| format!("{} is not a valid Decimal scale", scale))
+}
+
+fn rounding_mode(pointer: ObjectPointer) -> Result<RoundingMode, String> {
+    RoundingMode::from_integer(pointer.integer_value()?)
+}
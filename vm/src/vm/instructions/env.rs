@@ -5,6 +5,7 @@ use crate::object_value;
 use crate::platform;
 use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
+use crate::sandbox::Capability;
 use crate::vm::state::RcState;
 use std::env;
 
@@ -31,16 +32,24 @@ pub fn env_get(
 
 #[inline(always)]
 pub fn env_set(
+    state: &RcState,
     var_ptr: ObjectPointer,
     val_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, String> {
+    state.config.sandbox.check(Capability::Environment)?;
+
     env::set_var(var_ptr.string_value()?, val_ptr.string_value()?);
 
     Ok(val_ptr)
 }
 
 #[inline(always)]
-pub fn env_remove(var_ptr: ObjectPointer) -> Result<(), String> {
+pub fn env_remove(
+    state: &RcState,
+    var_ptr: ObjectPointer,
+) -> Result<(), String> {
+    state.config.sandbox.check(Capability::Environment)?;
+
     env::remove_var(var_ptr.string_value()?);
     Ok(())
 }
@@ -105,8 +114,15 @@ pub fn env_get_working_directory(
 
 #[inline(always)]
 pub fn env_set_working_directory(
+    state: &RcState,
     dir_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, RuntimeError> {
+    state
+        .config
+        .sandbox
+        .check(Capability::Environment)
+        .map_err(RuntimeError::Exception)?;
+
     let dir = dir_ptr.string_value()?;
 
     directories::set_working_directory(dir)?;
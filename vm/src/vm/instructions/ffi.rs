@@ -3,6 +3,7 @@ use crate::ffi;
 use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::process::RcProcess;
+use crate::sandbox::Capability;
 use crate::vm::state::RcState;
 
 #[inline(always)]
@@ -11,6 +12,10 @@ pub fn ffi_library_open(
     process: &RcProcess,
     names_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, String> {
+    // Functions and pointers can only be obtained using a library or an
+    // address, so denying these two operations denies the use of the FFI.
+    state.config.sandbox.check(Capability::Ffi)?;
+
     let names = names_ptr.array_value()?;
     let lib = ffi::Library::from_pointers(names)?;
 
@@ -113,6 +118,8 @@ pub fn ffi_pointer_from_address(
     process: &RcProcess,
     addr: ObjectPointer,
 ) -> Result<ObjectPointer, String> {
+    state.config.sandbox.check(Capability::Ffi)?;
+
     let result = process.allocate(
         object_value::pointer(unsafe { ffi::Pointer::from_address(addr)? }),
         state.ffi_pointer_prototype,
@@ -89,7 +89,7 @@ pub fn float_floor(
 ) -> Result<ObjectPointer, String> {
     let float = pointer.float_value()?.floor();
 
-    Ok(process.allocate(object_value::float(float), state.float_prototype))
+    Ok(process.allocate_f64(float, state.float_prototype))
 }
 
 #[inline(always)]
@@ -100,7 +100,7 @@ pub fn float_ceil(
 ) -> Result<ObjectPointer, String> {
     let float = pointer.float_value()?.ceil();
 
-    Ok(process.allocate(object_value::float(float), state.float_prototype))
+    Ok(process.allocate_f64(float, state.float_prototype))
 }
 
 #[inline(always)]
@@ -133,7 +133,7 @@ pub fn float_round(
         float
     };
 
-    Ok(process.allocate(object_value::float(result), state.float_prototype))
+    Ok(process.allocate_f64(result, state.float_prototype))
 }
 
 #[inline(always)]
@@ -146,3 +146,158 @@ pub fn float_to_bits(
 
     Ok(process.allocate_u64(bits, state.integer_prototype))
 }
+
+#[inline(always)]
+pub fn float_sqrt(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::sqrt)
+}
+
+#[inline(always)]
+pub fn float_pow(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+    exponent_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let float = pointer.float_value()?;
+    let exponent = exponent_ptr.float_value()?;
+
+    Ok(process.allocate_f64(float.powf(exponent), state.float_prototype))
+}
+
+#[inline(always)]
+pub fn float_exp(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::exp)
+}
+
+#[inline(always)]
+pub fn float_ln(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::ln)
+}
+
+#[inline(always)]
+pub fn float_log2(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::log2)
+}
+
+#[inline(always)]
+pub fn float_log10(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::log10)
+}
+
+#[inline(always)]
+pub fn float_sin(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::sin)
+}
+
+#[inline(always)]
+pub fn float_cos(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::cos)
+}
+
+#[inline(always)]
+pub fn float_tan(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::tan)
+}
+
+#[inline(always)]
+pub fn float_asin(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::asin)
+}
+
+#[inline(always)]
+pub fn float_acos(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::acos)
+}
+
+#[inline(always)]
+pub fn float_atan(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    map_float(state, process, pointer, f64::atan)
+}
+
+#[inline(always)]
+pub fn float_atan2(
+    state: &RcState,
+    process: &RcProcess,
+    y_ptr: ObjectPointer,
+    x_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let y = y_ptr.float_value()?;
+    let x = x_ptr.float_value()?;
+
+    Ok(process.allocate_f64(y.atan2(x), state.float_prototype))
+}
+
+/// Computes `(pointer * mul_ptr) + add_ptr` with only a single rounding
+/// error.
+#[inline(always)]
+pub fn float_fma(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+    mul_ptr: ObjectPointer,
+    add_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let float = pointer.float_value()?;
+    let mul = mul_ptr.float_value()?;
+    let add = add_ptr.float_value()?;
+
+    Ok(process.allocate_f64(float.mul_add(mul, add), state.float_prototype))
+}
+
+/// Applies a function to a float, and allocates the result.
+#[inline(always)]
+fn map_float<F: Fn(f64) -> f64>(
+    state: &RcState,
+    process: &RcProcess,
+    pointer: ObjectPointer,
+    function: F,
+) -> Result<ObjectPointer, String> {
+    let float = function(pointer.float_value()?);
+
+    Ok(process.allocate_f64(float, state.float_prototype))
+}
@@ -3,7 +3,15 @@ use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::process::RcProcess;
 use crate::vm::state::RcState;
-use num_traits::ToPrimitive;
+use num_bigint::BigInt;
+use num_integer::{Integer, Roots};
+use num_traits::{Pow, Signed, ToPrimitive};
+
+/// The maximum number of bits of the result of raising an integer to a power.
+///
+/// Without a limit, raising a large integer to a large power could take a very
+/// long time or exhaust all available memory.
+const MAX_POW_BITS: usize = 8 * 1024 * 1024;
 
 #[inline(always)]
 pub fn integer_to_float(
@@ -23,7 +31,7 @@ pub fn integer_to_float(
         integer.integer_value()? as f64
     };
 
-    Ok(process.allocate(object_value::float(result), state.float_prototype))
+    Ok(process.allocate_f64(result, state.float_prototype))
 }
 
 #[inline(always)]
@@ -44,3 +52,145 @@ pub fn integer_to_string(
 
     Ok(process.allocate(object_value::string(result), state.string_prototype))
 }
+
+#[inline(always)]
+pub fn integer_pow(
+    state: &RcState,
+    process: &RcProcess,
+    integer: ObjectPointer,
+    exponent_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let exponent = exponent_ptr.integer_value()?;
+
+    if exponent < 0 || exponent > i64::from(u32::MAX) {
+        return Err(format!(
+            "The exponent {} is not in the range 0..{}",
+            exponent,
+            u32::MAX
+        ));
+    }
+
+    let exponent = exponent as u32;
+
+    let pointer = if integer.is_integer() {
+        let base = integer.integer_value()?;
+
+        if let Some(result) = base.checked_pow(exponent) {
+            process.allocate_i64(result, state.integer_prototype)
+        } else {
+            // If the operation overflowed we need to retry it but using big
+            // integers.
+            let result = bigint_pow(&BigInt::from(base), exponent)?;
+
+            process
+                .allocate(object_value::bigint(result), state.integer_prototype)
+        }
+    } else if integer.is_bigint() {
+        let result = bigint_pow(integer.bigint_value()?, exponent)?;
+
+        process.allocate(object_value::bigint(result), state.integer_prototype)
+    } else {
+        return Err(
+            "Integer instructions can only be performed using integers"
+                .to_string(),
+        );
+    };
+
+    Ok(pointer)
+}
+
+#[inline(always)]
+pub fn integer_gcd(
+    state: &RcState,
+    process: &RcProcess,
+    integer: ObjectPointer,
+    other: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    if integer.is_integer() && other.is_integer() {
+        // The greatest common divisor of i64::MIN and 0 doesn't fit in an
+        // i64, so we compute it using wider integers.
+        let left = i128::from(integer.integer_value()?);
+        let right = i128::from(other.integer_value()?);
+
+        return Ok(process
+            .allocate_u64(left.gcd(&right) as u64, state.integer_prototype));
+    }
+
+    let left = to_bigint(integer)?;
+    let right = to_bigint(other)?;
+
+    Ok(process.allocate(
+        object_value::bigint(left.gcd(&right)),
+        state.integer_prototype,
+    ))
+}
+
+#[inline(always)]
+pub fn integer_sqrt(
+    state: &RcState,
+    process: &RcProcess,
+    integer: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let pointer = if integer.is_integer() {
+        let value = integer.integer_value()?;
+
+        if value < 0 {
+            return Err(format!(
+                "Can not calculate the square root of the negative Integer {}",
+                integer.integer_to_string()?
+            ));
+        }
+
+        process.allocate_i64(value.sqrt(), state.integer_prototype)
+    } else if integer.is_bigint() {
+        let value = integer.bigint_value()?;
+
+        if value.is_negative() {
+            return Err(format!(
+                "Can not calculate the square root of the negative Integer {}",
+                integer.integer_to_string()?
+            ));
+        }
+
+        process.allocate(
+            object_value::bigint(value.sqrt()),
+            state.integer_prototype,
+        )
+    } else {
+        return Err(
+            "Only integers are supported for this operation".to_string()
+        );
+    };
+
+    Ok(pointer)
+}
+
+fn to_bigint(integer: ObjectPointer) -> Result<BigInt, String> {
+    if integer.is_bigint() {
+        Ok(integer.bigint_value()?.clone())
+    } else if integer.is_integer() {
+        Ok(BigInt::from(integer.integer_value()?))
+    } else {
+        Err("Integer instructions can only be performed using integers"
+            .to_string())
+    }
+}
+
+/// Raises a big integer to a power, unless the result would be too large.
+fn bigint_pow(base: &BigInt, exponent: u32) -> Result<BigInt, String> {
+    // The result of |base| ^ exponent has at least (bits - 1) * exponent bits,
+    // which is exact enough to reject results that are far too large.
+    let bits = base
+        .bits()
+        .saturating_sub(1)
+        .saturating_mul(exponent as usize);
+
+    if bits > MAX_POW_BITS {
+        return Err(format!(
+            "The exponent {} produces a result larger than {} bits",
+            exponent, MAX_POW_BITS
+        ));
+    }
+
+    Ok(base.pow(exponent))
+}
@@ -5,6 +5,7 @@ use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
+use crate::sandbox::Capability;
 use crate::vm::state::RcState;
 use num_traits::ToPrimitive;
 use std::fs;
@@ -119,8 +120,20 @@ pub fn file_open(
     mode_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, RuntimeError> {
     let mode = mode_ptr.integer_value()?;
+
+    if mode != READ {
+        check_path(state, Capability::FileWrite, path_ptr)?;
+    }
+
     let file = File::open(path_ptr, mode)?;
-    let proto_ptr = match mode {
+    let proto_ptr = file_prototype(state, mode);
+
+    Ok(process.allocate(object_value::file(file), proto_ptr))
+}
+
+/// Returns the prototype to use for a file opened using the given mode.
+pub fn file_prototype(state: &RcState, mode: i64) -> ObjectPointer {
+    match mode {
         READ => state.read_only_file_prototype,
         WRITE => state.write_only_file_prototype,
         APPEND => state.write_only_file_prototype,
@@ -130,9 +143,7 @@ pub fn file_open(
         _ => unreachable!(
             "File::open() doesn't handle unknown open modes, this is a bug"
         ),
-    };
-
-    Ok(process.allocate(object_value::file(file), proto_ptr))
+    }
 }
 
 #[inline(always)]
@@ -186,7 +197,12 @@ pub fn file_seek(
 }
 
 #[inline(always)]
-pub fn file_remove(path_ptr: ObjectPointer) -> Result<(), RuntimeError> {
+pub fn file_remove(
+    state: &RcState,
+    path_ptr: ObjectPointer,
+) -> Result<(), RuntimeError> {
+    check_path(state, Capability::FileWrite, path_ptr)?;
+
     let path_str = path_ptr.string_value()?;
 
     fs::remove_file(path_str)?;
@@ -200,6 +216,8 @@ pub fn file_copy(
     src_ptr: ObjectPointer,
     dst_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, RuntimeError> {
+    check_path(state, Capability::FileWrite, dst_ptr)?;
+
     let src = src_ptr.string_value()?;
     let dst = dst_ptr.string_value()?;
     let bytes_copied = fs::copy(src, dst)?;
@@ -227,8 +245,7 @@ pub fn file_time(
     let path = path_ptr.string_value()?;
     let kind = kind_ptr.integer_value()?;
     let dt = filesystem::date_time_for_path(path, kind)?;
-    let timestamp = process
-        .allocate(object_value::float(dt.timestamp()), state.float_prototype);
+    let timestamp = process.allocate_f64(dt.timestamp(), state.float_prototype);
 
     let offset = ObjectPointer::integer(dt.utc_offset());
     let tuple = process.allocate(
@@ -250,6 +267,8 @@ pub fn directory_create(
     path_ptr: ObjectPointer,
     recursive_ptr: ObjectPointer,
 ) -> Result<(), RuntimeError> {
+    check_path(state, Capability::Directories, path_ptr)?;
+
     let path = path_ptr.string_value()?;
 
     if is_false!(state, recursive_ptr) {
@@ -267,6 +286,8 @@ pub fn directory_remove(
     path_ptr: ObjectPointer,
     recursive_ptr: ObjectPointer,
 ) -> Result<(), RuntimeError> {
+    check_path(state, Capability::Directories, path_ptr)?;
+
     let path = path_ptr.string_value()?;
 
     if is_false!(state, recursive_ptr) {
@@ -284,12 +305,29 @@ pub fn directory_list(
     process: &RcProcess,
     path_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, RuntimeError> {
+    check_path(state, Capability::Directories, path_ptr)?;
+
     let path = path_ptr.string_value()?;
     let files = filesystem::list_directory_as_pointers(&state, process, path)?;
 
     Ok(files)
 }
 
+/// Throws an error if the sandbox doesn't allow the operation for the path.
+fn check_path(
+    state: &RcState,
+    capability: Capability,
+    path_ptr: ObjectPointer,
+) -> Result<(), RuntimeError> {
+    let path = path_ptr.string_value()?;
+
+    state
+        .config
+        .sandbox
+        .check_path(capability, path.as_slice())
+        .map_err(RuntimeError::Exception)
+}
+
 /// Reads a number of bytes from a stream into a byte array.
 fn io_read(
     state: &RcState,
@@ -1,6 +1,7 @@
 pub mod array;
 pub mod block;
 pub mod byte_array;
+pub mod decimal;
 pub mod env;
 pub mod ffi;
 pub mod float;
@@ -12,6 +13,7 @@ pub mod module;
 pub mod object;
 pub mod process;
 pub mod random;
+pub mod regex;
 pub mod socket;
 pub mod string;
 pub mod time;
@@ -1,5 +1,6 @@
 //! VM functions for working with Inko objects.
 use crate::immix::copy_object::CopyObject;
+use crate::inline_cache::InlineCache;
 use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::process::RcProcess;
@@ -61,6 +62,8 @@ pub fn get_builtin_prototype(
         17 => state.write_only_file_prototype,
         18 => state.read_write_file_prototype,
         19 => state.hasher_prototype,
+        20 => state.decimal_prototype,
+        21 => state.regex_prototype,
         _ => return Err(format!("Invalid prototype identifier: {}", id_int)),
     };
 
@@ -70,14 +73,77 @@ pub fn get_builtin_prototype(
 #[inline(always)]
 pub fn get_attribute(
     state: &RcState,
+    cache: Option<&InlineCache>,
     rec_ptr: ObjectPointer,
     name_ptr: ObjectPointer,
 ) -> ObjectPointer {
     let name = state.intern_pointer(name_ptr).unwrap_or_else(|_| name_ptr);
+    let cache = if let Some(cache) = cache {
+        cache
+    } else {
+        return rec_ptr
+            .lookup_attribute(&state, name)
+            .unwrap_or(state.nil_object);
+    };
 
-    rec_ptr
-        .lookup_attribute(&state, name)
-        .unwrap_or(state.nil_object)
+    let prototype = if rec_ptr.is_tagged_integer() {
+        state.integer_prototype
+    } else if rec_ptr.is_tagged_float() {
+        state.float_prototype
+    } else {
+        let object = rec_ptr.get();
+
+        // Attributes defined on the receiver are never cached, as they take
+        // precedence over the attributes of its prototypes.
+        if let Some(value) = object.lookup_attribute_in_self(name) {
+            return value;
+        }
+
+        object.prototype
+    };
+
+    if prototype.is_null() {
+        return state.nil_object;
+    }
+
+    let epoch = state.inline_cache_epoch.current();
+
+    if let Some(value) = cache.lookup(prototype, name, epoch) {
+        return value;
+    }
+
+    if let Some((value, permanent)) = lookup_in_prototypes(prototype, name) {
+        if permanent {
+            cache.store(prototype, name, value, epoch);
+        }
+
+        value
+    } else {
+        state.nil_object
+    }
+}
+
+/// Looks up an attribute in a prototype and its prototypes.
+///
+/// The returned boolean is true if the prototypes walked are all permanent.
+fn lookup_in_prototypes(
+    prototype: ObjectPointer,
+    name: ObjectPointer,
+) -> Option<(ObjectPointer, bool)> {
+    let mut current = prototype;
+    let mut permanent = true;
+
+    loop {
+        let object = current.get();
+
+        permanent = permanent && current.is_permanent();
+
+        if let Some(value) = object.lookup_attribute_in_self(name) {
+            return Some((value, permanent));
+        }
+
+        current = object.prototype()?;
+    }
 }
 
 #[inline(always)]
@@ -114,6 +180,10 @@ pub fn set_attribute(
 
     target_ptr.add_attribute(&process, name, value);
 
+    if target_ptr.is_permanent() {
+        state.inline_cache_epoch.increment();
+    }
+
     Ok(value)
 }
 
@@ -186,10 +256,20 @@ pub fn copy_blocks(
             object.add_attribute(*key, block);
         }
     }
+
+    if target_ptr.is_permanent() {
+        state.inline_cache_epoch.increment();
+    }
 }
 
 #[inline(always)]
 pub fn close(pointer: ObjectPointer) {
+    // Tagged integers and floats don't point to any memory, and there's
+    // nothing to close for them.
+    if pointer.is_tagged() {
+        return;
+    }
+
     pointer.get_mut().value.close();
 }
 
@@ -198,6 +278,7 @@ mod tests {
     use super::*;
     use crate::config::Config;
     use crate::vm::state::State;
+    use crate::vm::test::setup;
 
     #[test]
     fn test_get_builtin_prototype() {
@@ -217,4 +298,95 @@ mod tests {
             get_builtin_prototype(&state, ObjectPointer::integer(-1)).is_err()
         );
     }
+
+    #[test]
+    fn test_get_attribute_with_inline_cache() {
+        let (machine, _block, process) = setup();
+        let state = &machine.state;
+        let proto = state.permanent_allocator.lock().allocate_empty();
+        let object = process.allocate(object_value::none(), proto);
+        let name = state.intern_string("foo".to_string());
+        let cache = InlineCache::new();
+
+        proto
+            .get_mut()
+            .add_attribute(name, ObjectPointer::integer(1));
+
+        let epoch = state.inline_cache_epoch.current();
+        let value = get_attribute(state, Some(&cache), object, name);
+
+        assert!(value == ObjectPointer::integer(1));
+        assert!(cache.lookup(proto, name, epoch) == Some(value));
+
+        set_attribute(state, &process, proto, name, ObjectPointer::integer(2))
+            .unwrap();
+
+        assert!(
+            get_attribute(state, Some(&cache), object, name)
+                == ObjectPointer::integer(2)
+        );
+    }
+
+    #[test]
+    fn test_get_attribute_defined_in_receiver() {
+        let (machine, _block, process) = setup();
+        let state = &machine.state;
+        let proto = state.permanent_allocator.lock().allocate_empty();
+        let object = process.allocate(object_value::none(), proto);
+        let name = state.intern_string("foo".to_string());
+        let cache = InlineCache::new();
+
+        proto
+            .get_mut()
+            .add_attribute(name, ObjectPointer::integer(1));
+        object
+            .get_mut()
+            .add_attribute(name, ObjectPointer::integer(2));
+
+        let epoch = state.inline_cache_epoch.current();
+
+        assert!(
+            get_attribute(state, Some(&cache), object, name)
+                == ObjectPointer::integer(2)
+        );
+
+        assert!(cache.lookup(proto, name, epoch).is_none());
+    }
+
+    #[test]
+    fn test_get_attribute_without_permanent_prototype() {
+        let (machine, _block, process) = setup();
+        let state = &machine.state;
+        let proto = process.allocate_empty();
+        let object = process.allocate(object_value::none(), proto);
+        let name = state.intern_string("foo".to_string());
+        let cache = InlineCache::new();
+
+        proto
+            .get_mut()
+            .add_attribute(name, ObjectPointer::integer(1));
+
+        let epoch = state.inline_cache_epoch.current();
+
+        assert!(
+            get_attribute(state, Some(&cache), object, name)
+                == ObjectPointer::integer(1)
+        );
+
+        assert!(cache.lookup(proto, name, epoch).is_none());
+    }
+
+    #[test]
+    fn test_get_attribute_without_attribute() {
+        let (machine, _block, process) = setup();
+        let state = &machine.state;
+        let object = process.allocate_empty();
+        let name = state.intern_string("foo".to_string());
+        let cache = InlineCache::new();
+
+        assert!(
+            get_attribute(state, Some(&cache), object, name)
+                == state.nil_object
+        );
+    }
 }
@@ -5,12 +5,24 @@ use crate::execution_context::ExecutionContext;
 use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::process::{Process, RcProcess, RescheduleRights};
+use crate::runtime_error::RuntimeError;
+use crate::scheduler::priority::Priority;
+use crate::scheduler::process_scheduler::ProcessScheduler;
 use crate::scheduler::process_worker::ProcessWorker;
+use crate::scheduler::timeout_worker::TimeoutWorker;
 use crate::vm::state::RcState;
+use std::time::Duration;
 
 #[inline(always)]
 pub fn process_allocate(state: &RcState, block: &Block) -> RcProcess {
-    Process::from_block(block, state.global_allocator.clone(), &state.config)
+    let process = Process::from_block(
+        block,
+        state.global_allocator.clone(),
+        &state.config,
+    );
+
+    state.processes.add(process.clone());
+    process
 }
 
 #[inline(always)]
@@ -103,10 +115,11 @@ pub fn wait_for_message(
         process.suspend_without_timeout();
     }
 
-    if process.has_messages() {
-        // We may have received messages before marking the process as
-        // suspended. If this happens we have to reschedule ourselves, otherwise
-        // our process may be suspended until it is sent another message.
+    if should_wake_up(process) {
+        // We may have received messages (or a request to terminate) before
+        // marking the process as suspended. If this happens we have to
+        // reschedule ourselves, otherwise our process may be suspended until
+        // it is sent another message.
         attempt_to_reschedule_process(state, process);
     }
 
@@ -123,6 +136,10 @@ pub fn process_suspend_current(
 
     if let Some(duration) = wait_for {
         state.timeout_worker.suspend(process.clone(), duration);
+
+        if process.is_termination_requested() {
+            attempt_to_reschedule_process(state, process);
+        }
     } else {
         state.scheduler.schedule(process.clone());
     }
@@ -152,6 +169,212 @@ pub fn process_set_blocking(
     }
 }
 
+#[inline(always)]
+pub fn process_set_priority(
+    process_ptr: ObjectPointer,
+    priority_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let process = process_ptr.process_value()?;
+    let value = priority_ptr.integer_value()?;
+    let priority = if value >= 0 && value <= i64::from(u8::MAX) {
+        Priority::from_u8(value as u8)
+    } else {
+        None
+    }
+    .ok_or_else(|| format!("{} is not a valid process priority", value))?;
+
+    process.set_priority(priority);
+
+    Ok(priority_ptr)
+}
+
+#[inline(always)]
+pub fn process_priority(
+    process_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let process = process_ptr.process_value()?;
+
+    Ok(ObjectPointer::integer(process.priority() as i64))
+}
+
+#[inline(always)]
+pub fn process_terminate(
+    state: &RcState,
+    current_process: &RcProcess,
+    process_ptr: ObjectPointer,
+    reason_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let process = process_ptr.process_value()?;
+    let reason = if reason_ptr == state.nil_object {
+        ObjectPointer::null()
+    } else {
+        reason_ptr
+    };
+
+    if !process.request_termination(reason) {
+        return Ok(state.false_object);
+    }
+
+    // The current process starts unwinding right away, while other processes
+    // have to be woken up if they are suspended. Running processes will
+    // observe the request once they return from a method, or the next time
+    // they are scheduled.
+    if process != current_process {
+        if process.acquire_io_rescheduling_rights() {
+            state.scheduler.schedule(process.clone());
+        } else {
+            attempt_to_reschedule_process(state, process);
+        }
+    }
+
+    Ok(state.true_object)
+}
+
+#[inline(always)]
+pub fn process_termination_reason(
+    state: &RcState,
+    process: &RcProcess,
+) -> ObjectPointer {
+    process.termination_reason().unwrap_or(state.nil_object)
+}
+
+#[inline(always)]
+pub fn process_list(state: &RcState, process: &RcProcess) -> ObjectPointer {
+    let processes = state
+        .processes
+        .processes()
+        .into_iter()
+        .map(|proc| {
+            process
+                .allocate(object_value::process(proc), state.process_prototype)
+        })
+        .collect();
+
+    process.allocate(object_value::array(processes), state.array_prototype)
+}
+
+#[inline(always)]
+pub fn process_info(
+    state: &RcState,
+    current_process: &RcProcess,
+    process_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let info = process_ptr.process_value()?.info();
+    let int_proto = state.integer_prototype;
+    let optional_usize = |value: Option<usize>| {
+        value.map_or(state.nil_object, |number| {
+            current_process.allocate_usize(number, int_proto)
+        })
+    };
+
+    let (file, line) = if let Some((file, line)) = info.location {
+        (file, ObjectPointer::integer(i64::from(line)))
+    } else {
+        (state.nil_object, state.nil_object)
+    };
+
+    let values = vec![
+        ObjectPointer::integer(info.state as i64),
+        current_process.allocate_usize(info.mailbox_length, int_proto),
+        optional_usize(info.young_blocks),
+        optional_usize(info.mature_blocks),
+        file,
+        line,
+        current_process.allocate_u64(info.reductions, int_proto),
+        current_process.allocate_f64(
+            duration::to_f64(Some(info.cpu_time)),
+            state.float_prototype,
+        ),
+    ];
+
+    Ok(current_process
+        .allocate(object_value::array(values), state.array_prototype))
+}
+
+#[inline(always)]
+pub fn process_set_reduction_budget(
+    state: &RcState,
+    process_ptr: ObjectPointer,
+    budget_ptr: ObjectPointer,
+    watcher_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let process = process_ptr.process_value()?;
+
+    let budget = if budget_ptr == state.nil_object {
+        None
+    } else {
+        let value = budget_ptr.integer_value()?;
+
+        if value < 0 {
+            return Err(format!("{} is not a valid reduction budget", value));
+        }
+
+        Some(value as u64)
+    };
+
+    let watcher = if watcher_ptr == state.nil_object {
+        None
+    } else {
+        Some(watcher_ptr.process_value()?.clone())
+    };
+
+    process.set_reduction_budget(budget, watcher);
+
+    Ok(state.nil_object)
+}
+
+#[inline(always)]
+pub fn timer_start(
+    state: &RcState,
+    current_process: &RcProcess,
+    process_ptr: ObjectPointer,
+    message: ObjectPointer,
+    delay_ptr: ObjectPointer,
+    interval_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let receiver = process_ptr.process_value()?;
+    let delay = duration::from_f64(delay_ptr.float_value()?)?
+        .unwrap_or_else(|| Duration::from_secs(0));
+
+    let interval = if interval_ptr == state.nil_object {
+        None
+    } else {
+        let interval = duration::from_f64(interval_ptr.float_value()?)?;
+
+        if interval.is_none() {
+            return Err(
+                "The interval of a periodic timer must be greater than zero"
+                    .to_string(),
+            );
+        }
+
+        interval
+    };
+
+    let id = state.timeout_worker.start_timer(
+        receiver.clone(),
+        message,
+        delay,
+        interval,
+    );
+
+    Ok(current_process.allocate_usize(id, state.integer_prototype))
+}
+
+#[inline(always)]
+pub fn timer_cancel(
+    state: &RcState,
+    id_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let id = id_ptr.usize_value()?;
+
+    if state.timeout_worker.cancel_timer(id) {
+        Ok(state.true_object)
+    } else {
+        Ok(state.false_object)
+    }
+}
+
 #[inline(always)]
 pub fn stacktrace(
     state: &RcState,
@@ -238,8 +461,43 @@ pub fn process_unwind_until_defining_scope(process: &RcProcess) {
     }
 }
 
+#[inline(always)]
+pub fn signal_subscribe(
+    state: &RcState,
+    process: &RcProcess,
+    signal_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    state
+        .signals
+        .subscribe(process, signal_ptr.integer_value()?)?;
+
+    Ok(state.nil_object)
+}
+
+#[inline(always)]
+pub fn signal_unsubscribe(
+    state: &RcState,
+    process: &RcProcess,
+    signal_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    state
+        .signals
+        .unsubscribe(process, signal_ptr.integer_value()?)?;
+
+    Ok(state.nil_object)
+}
+
 /// Attempts to reschedule the given process after it was sent a message.
-fn attempt_to_reschedule_process(state: &RcState, process: &RcProcess) {
+pub fn attempt_to_reschedule_process(state: &RcState, process: &RcProcess) {
+    reschedule_for_message(&state.scheduler, &state.timeout_worker, process);
+}
+
+/// Reschedules a process after sending it a message, if necessary.
+pub fn reschedule_for_message(
+    scheduler: &ProcessScheduler,
+    timeout_worker: &TimeoutWorker,
+    process: &RcProcess,
+) {
     // The logic below is necessary as a process' state may change between
     // sending it a message and attempting to reschedule it. Imagine we have two
     // processes: A, and B. A sends B a message, and B waits for a message twice
@@ -267,15 +525,18 @@ fn attempt_to_reschedule_process(state: &RcState, process: &RcProcess) {
     // The logic below ensures that we only wake up a process when actually
     // necessary, and suspend it again if it didn't receive any messages (taking
     // into account messages it may have received while doing so).
+    //
+    // Processes that have to terminate are also woken up, so they can run
+    // their deferred blocks and terminate.
     let reschedule = match process.acquire_rescheduling_rights() {
         RescheduleRights::Failed => false,
         RescheduleRights::Acquired => {
-            if process.has_messages() {
+            if should_wake_up(process) {
                 true
             } else {
                 process.suspend_without_timeout();
 
-                if process.has_messages() {
+                if should_wake_up(process) {
                     process.acquire_rescheduling_rights().are_acquired()
                 } else {
                     false
@@ -283,15 +544,15 @@ fn attempt_to_reschedule_process(state: &RcState, process: &RcProcess) {
             }
         }
         RescheduleRights::AcquiredWithTimeout(timeout) => {
-            if process.has_messages() {
-                state.timeout_worker.increase_expired_timeouts();
+            if should_wake_up(process) {
+                timeout_worker.increase_expired_timeouts();
                 true
             } else {
                 process.suspend_with_timeout(timeout);
 
-                if process.has_messages() {
+                if should_wake_up(process) {
                     if process.acquire_rescheduling_rights().are_acquired() {
-                        state.timeout_worker.increase_expired_timeouts();
+                        timeout_worker.increase_expired_timeouts();
 
                         true
                     } else {
@@ -305,10 +566,15 @@ fn attempt_to_reschedule_process(state: &RcState, process: &RcProcess) {
     };
 
     if reschedule {
-        state.scheduler.schedule(process.clone());
+        scheduler.schedule(process.clone());
     }
 }
 
+/// Returns true if a suspended process should be woken up.
+fn should_wake_up(process: &RcProcess) -> bool {
+    process.is_termination_requested() || process.has_messages()
+}
+
 /// Produces a stacktrace containing up to N stack frames.
 fn allocate_stacktrace(
     process: &RcProcess,
@@ -35,10 +35,10 @@ pub fn random_number(
             .allocate_i64(worker.random_number(), state.integer_prototype)),
         INCREMENTAL_INTEGER => Ok(process
             .allocate_u64(worker.random_number(), state.integer_prototype)),
-        FLOAT => Ok(process.allocate(
-            object_value::float(worker.random_number()),
-            state.float_prototype,
-        )),
+        FLOAT => {
+            Ok(process
+                .allocate_f64(worker.random_number(), state.float_prototype))
+        }
         _ => Err(format!(
             "{} is not a valid type to generate a random value for",
             kind
@@ -80,8 +80,8 @@ pub fn random_range(
 
         verify_min_max!(min, max);
 
-        Ok(process.allocate(
-            object_value::float(worker.random_number_between(min, max)),
+        Ok(process.allocate_f64(
+            worker.random_number_between(min, max),
             state.float_prototype,
         ))
     } else {
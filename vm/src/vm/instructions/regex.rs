@@ -0,0 +1,107 @@
+//! VM functions for working with regular expressions.
+use crate::object_pointer::ObjectPointer;
+use crate::object_value;
+use crate::process::RcProcess;
+use crate::runtime_error::RuntimeError;
+use crate::vm::instructions::string::allocate_string;
+use crate::vm::state::RcState;
+use regex::{Captures, Regex};
+
+#[inline(always)]
+pub fn regex_compile(
+    state: &RcState,
+    process: &RcProcess,
+    pattern_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let regex = Regex::new(pattern_ptr.string_value()?.as_slice())
+        .map_err(|error| RuntimeError::Exception(error.to_string()))?;
+
+    Ok(process.allocate(object_value::regex(regex), state.regex_prototype))
+}
+
+#[inline(always)]
+pub fn regex_match(
+    state: &RcState,
+    regex_ptr: ObjectPointer,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let regex = regex_ptr.regex_value()?;
+    let string = str_ptr.string_value()?;
+
+    if regex.is_match(string.as_slice()) {
+        Ok(state.true_object)
+    } else {
+        Ok(state.false_object)
+    }
+}
+
+#[inline(always)]
+pub fn regex_find_all(
+    state: &RcState,
+    process: &RcProcess,
+    regex_ptr: ObjectPointer,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let regex = regex_ptr.regex_value()?;
+    let string = str_ptr.string_value()?;
+    let matches = regex
+        .captures_iter(string.as_slice())
+        .map(|captures| allocate_captures(state, process, &captures))
+        .collect();
+
+    Ok(process.allocate(object_value::array(matches), state.array_prototype))
+}
+
+#[inline(always)]
+pub fn regex_split(
+    state: &RcState,
+    process: &RcProcess,
+    regex_ptr: ObjectPointer,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let regex = regex_ptr.regex_value()?;
+    let string = str_ptr.string_value()?;
+    let segments = regex
+        .split(string.as_slice())
+        .map(|segment| allocate_string(state, process, segment))
+        .collect();
+
+    Ok(process.allocate(object_value::array(segments), state.array_prototype))
+}
+
+#[inline(always)]
+pub fn regex_replace(
+    state: &RcState,
+    process: &RcProcess,
+    regex_ptr: ObjectPointer,
+    str_ptr: ObjectPointer,
+    replacement_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let regex = regex_ptr.regex_value()?;
+    let string = str_ptr.string_value()?;
+    let replacement = replacement_ptr.string_value()?;
+    let replaced = regex.replace_all(string.as_slice(), replacement.as_slice());
+
+    Ok(allocate_string(state, process, &replaced))
+}
+
+/// Allocates an Array containing the capture groups of a single match.
+///
+/// The first value is the entire match. Groups that did not participate in the
+/// match are stored as Nil.
+fn allocate_captures(
+    state: &RcState,
+    process: &RcProcess,
+    captures: &Captures,
+) -> ObjectPointer {
+    let groups = captures
+        .iter()
+        .map(|group| {
+            group.map_or(state.nil_object, |group| {
+                allocate_string(state, process, group.as_str())
+            })
+        })
+        .collect();
+
+    process.allocate(object_value::array(groups), state.array_prototype)
+}
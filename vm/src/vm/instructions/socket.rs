@@ -3,9 +3,18 @@ use crate::object_pointer::ObjectPointer;
 use crate::object_value;
 use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
+use crate::sandbox::Capability;
 use crate::socket::Socket;
 use crate::vm::instructions::io;
 use crate::vm::state::RcState;
+use std::io::Seek;
+
+#[cfg(unix)]
+use {
+    crate::file::File,
+    crate::socket::ReceivedHandle,
+    std::os::unix::io::{AsRawFd, RawFd},
+};
 
 const TTL: i64 = 0;
 const ONLY_V6: i64 = 1;
@@ -29,7 +38,31 @@ macro_rules! ret {
     ($result:expr, $state:expr, $proc:expr, $sock:expr, $interest:expr) => {{
         if let Err(ref err) = $result {
             if err.should_poll() {
-                $sock.register($proc, &$state.network_poller, $interest)?;
+                // The process must be suspended before registering the socket,
+                // as the poller may reschedule the process right away.
+                $proc.suspend_for_io();
+
+                if let Err(error) =
+                    $sock.register($proc, &$state.network_poller, $interest)
+                {
+                    // If we can't reacquire the rights to run the process,
+                    // another thread already rescheduled it and we must stop
+                    // running it.
+                    return if $proc.acquire_io_rescheduling_rights() {
+                        Err(error)
+                    } else {
+                        Err(RuntimeError::WouldBlock)
+                    };
+                }
+
+                // A request to terminate the process may have come in before
+                // we suspended it, in which case we have to reschedule it
+                // ourselves.
+                if $proc.is_termination_requested()
+                    && $proc.acquire_io_rescheduling_rights()
+                {
+                    $state.scheduler.schedule($proc.clone());
+                }
             }
         }
 
@@ -44,6 +77,12 @@ pub fn socket_create(
     domain_ptr: ObjectPointer,
     kind_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, RuntimeError> {
+    state
+        .config
+        .sandbox
+        .check_socket()
+        .map_err(RuntimeError::Exception)?;
+
     let domain = domain_ptr.u8_value()?;
     let kind = kind_ptr.u8_value()?;
     let socket = Socket::new(domain, kind)?;
@@ -67,6 +106,137 @@ pub fn socket_write(
     ret!(res, state, process, sock, Interest::Write)
 }
 
+#[inline(always)]
+pub fn socket_write_vectored(
+    state: &RcState,
+    process: &RcProcess,
+    socket_ptr: ObjectPointer,
+    buffers_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let sock = socket_ptr.socket_value_mut()?;
+    let buffers = buffers_ptr
+        .array_value()?
+        .iter()
+        .map(|ptr| io::buffer_to_write(ptr))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let result = sock.write_vectored(&buffers).map(|written| {
+        process.allocate_usize(written, state.integer_prototype)
+    });
+
+    ret!(result, state, process, sock, Interest::Write)
+}
+
+#[inline(always)]
+pub fn socket_send_file(
+    state: &RcState,
+    process: &RcProcess,
+    socket_ptr: ObjectPointer,
+    file_ptr: ObjectPointer,
+    amount_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let sock = socket_ptr.socket_value_mut()?;
+    let file = file_ptr.file_value_mut()?.get_mut();
+    let amount = if amount_ptr.is_integer() {
+        amount_ptr.usize_value()?
+    } else {
+        let size = file.metadata()?.len();
+        let position = file.stream_position()?;
+
+        size.saturating_sub(position) as usize
+    };
+
+    let result = sock
+        .send_file(file, amount)
+        .map(|sent| process.allocate_usize(sent, state.integer_prototype));
+
+    ret!(result, state, process, sock, Interest::Write)
+}
+
+#[inline(always)]
+pub fn socket_peer_credentials(
+    state: &RcState,
+    process: &RcProcess,
+    socket_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let sock = socket_ptr.socket_value()?;
+    let (pid, uid, gid) = sock.peer_credentials()?;
+    let values = vec![
+        ObjectPointer::integer(pid),
+        ObjectPointer::integer(uid),
+        ObjectPointer::integer(gid),
+    ];
+
+    Ok(process.allocate(object_value::array(values), state.array_prototype))
+}
+
+#[cfg(unix)]
+#[inline(always)]
+pub fn socket_send_handles(
+    state: &RcState,
+    process: &RcProcess,
+    socket_ptr: ObjectPointer,
+    handles_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let sock = socket_ptr.socket_value_mut()?;
+    let fds = handles_ptr
+        .array_value()?
+        .iter()
+        .map(|ptr| handle_file_descriptor(*ptr))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let result = sock
+        .send_handles(&fds)
+        .map(|sent| process.allocate_usize(sent, state.integer_prototype));
+
+    ret!(result, state, process, sock, Interest::Write)
+}
+
+#[cfg(not(unix))]
+#[inline(always)]
+pub fn socket_send_handles(
+    _state: &RcState,
+    _process: &RcProcess,
+    _socket_ptr: ObjectPointer,
+    _handles_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    Err(RuntimeError::Exception(
+        "Sending handles isn't supported on this platform".to_string(),
+    ))
+}
+
+#[cfg(unix)]
+#[inline(always)]
+pub fn socket_receive_handles(
+    state: &RcState,
+    process: &RcProcess,
+    socket_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    let sock = socket_ptr.socket_value_mut()?;
+    let result = sock.receive_handles().map(|handles| {
+        let values = handles
+            .into_iter()
+            .map(|handle| allocate_received_handle(state, process, handle))
+            .collect();
+
+        process.allocate(object_value::array(values), state.array_prototype)
+    });
+
+    ret!(result, state, process, sock, Interest::Read)
+}
+
+#[cfg(not(unix))]
+#[inline(always)]
+pub fn socket_receive_handles(
+    _state: &RcState,
+    _process: &RcProcess,
+    _socket_ptr: ObjectPointer,
+) -> Result<ObjectPointer, RuntimeError> {
+    Err(RuntimeError::Exception(
+        "Receiving handles isn't supported on this platform".to_string(),
+    ))
+}
+
 #[inline(always)]
 pub fn socket_read(
     state: &RcState,
@@ -92,12 +262,22 @@ pub fn socket_read(
 
 #[inline(always)]
 pub fn socket_listen(
+    state: &RcState,
     socket_ptr: ObjectPointer,
     backlog_ptr: ObjectPointer,
 ) -> Result<ObjectPointer, RuntimeError> {
     let sock = socket_ptr.socket_value()?;
     let backlog = backlog_ptr.i32_value()?;
 
+    // Listening on a socket that isn't bound binds it to a random port on all
+    // interfaces, without going through socket_bind(). To prevent this from
+    // being used to get around the sandbox, we check the local address first.
+    if state.config.sandbox.denied.contains(Capability::Sockets) {
+        let (address, port) = sock.local_address()?;
+
+        check_address(state, &address, port as u16)?;
+    }
+
     sock.listen(backlog)?;
 
     Ok(backlog_ptr)
@@ -114,6 +294,9 @@ pub fn socket_bind(
     let sock = socket_ptr.socket_value_mut()?;
     let addr = addr_ptr.string_value()?;
     let port = port_ptr.u16_value()?;
+
+    check_address(state, addr.as_slice(), port)?;
+
     let result = sock.bind(addr, port);
 
     ret!(result, state, process, sock, Interest::Read)
@@ -130,6 +313,9 @@ pub fn socket_connect(
     let sock = socket_ptr.socket_value_mut()?;
     let addr = addr_ptr.string_value()?;
     let port = port_ptr.u16_value()?;
+
+    check_address(state, addr.as_slice(), port)?;
+
     let result = sock.connect(addr, port);
 
     ret!(result, state, process, sock, Interest::Write)
@@ -182,6 +368,9 @@ pub fn socket_send_to(
     let sock = socket_pointer.socket_value_mut()?;
     let address = address_pointer.string_value()?;
     let port = port_pointer.u16_value()?;
+
+    check_address(state, address.as_slice(), port)?;
+
     let result = sock
         .send_to(buffer, address, port)
         .map(|bytes| process.allocate_usize(bytes, state.integer_prototype));
@@ -351,7 +540,7 @@ fn alloc_f64(
     process: &RcProcess,
     value: f64,
 ) -> ObjectPointer {
-    process.allocate(object_value::float(value), state.float_prototype)
+    process.allocate_f64(value, state.float_prototype)
 }
 
 fn alloc_bool(state: &RcState, value: bool) -> ObjectPointer {
@@ -362,6 +551,41 @@ fn alloc_bool(state: &RcState, value: bool) -> ObjectPointer {
     }
 }
 
+#[cfg(unix)]
+fn handle_file_descriptor(
+    pointer: ObjectPointer,
+) -> Result<RawFd, RuntimeError> {
+    if pointer.is_file() {
+        Ok(pointer.file_value_mut()?.get_mut().as_raw_fd())
+    } else {
+        Ok(pointer.socket_value()?.as_raw_fd())
+    }
+}
+
+#[cfg(unix)]
+fn allocate_received_handle(
+    state: &RcState,
+    process: &RcProcess,
+    handle: ReceivedHandle,
+) -> ObjectPointer {
+    match handle {
+        ReceivedHandle::Socket(socket) => {
+            let proto_ptr = socket_prototype(state, &socket);
+
+            process.allocate(object_value::socket(socket), proto_ptr)
+        }
+        ReceivedHandle::File(file, mode, path) => {
+            let path_ptr = process
+                .allocate(object_value::string(path), state.string_prototype);
+
+            process.allocate(
+                object_value::file(File::new(file, path_ptr)),
+                io::file_prototype(state, mode),
+            )
+        }
+    }
+}
+
 fn socket_prototype(state: &RcState, socket: &Socket) -> ObjectPointer {
     if socket.is_unix() {
         state.unix_socket_prototype
@@ -369,3 +593,74 @@ fn socket_prototype(state: &RcState, socket: &Socket) -> ObjectPointer {
         state.ip_socket_prototype
     }
 }
+
+/// Throws an error if the sandbox doesn't allow the use of the address.
+fn check_address(
+    state: &RcState,
+    address: &str,
+    port: u16,
+) -> Result<(), RuntimeError> {
+    state
+        .config
+        .sandbox
+        .check_address(address, port)
+        .map_err(RuntimeError::Exception)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::sandbox::{AddressAllowlist, Capabilities};
+    use crate::vm::state::State;
+    use crate::vm::test::setup;
+
+    fn sandboxed_state() -> RcState {
+        let mut config = Config::new();
+
+        config.sandbox.denied = "sockets".parse::<Capabilities>().unwrap();
+        config.sandbox.addresses =
+            "127.0.0.1".parse::<AddressAllowlist>().unwrap();
+
+        State::with_rc(config, &[])
+    }
+
+    #[test]
+    fn test_socket_listen_without_bind_in_sandbox() {
+        let (machine, _block, process) = setup();
+        let state = sandboxed_state();
+        let socket = process.allocate(
+            object_value::socket(Socket::new(0, 0).unwrap()),
+            machine.state.ip_socket_prototype,
+        );
+
+        let result = socket_listen(&state, socket, ObjectPointer::integer(1));
+
+        match result {
+            Err(RuntimeError::Exception(message)) => {
+                assert!(message.contains("0.0.0.0:0"));
+            }
+            _ => panic!("listening on an unbound socket should be denied"),
+        }
+    }
+
+    #[test]
+    fn test_socket_listen_after_bind_in_sandbox() {
+        let (machine, _block, process) = setup();
+        let state = sandboxed_state();
+        let socket = process.allocate(
+            object_value::socket(Socket::new(0, 0).unwrap()),
+            machine.state.ip_socket_prototype,
+        );
+
+        socket
+            .socket_value_mut()
+            .unwrap()
+            .bind("127.0.0.1", 0)
+            .unwrap();
+
+        let result = socket_listen(&state, socket, ObjectPointer::integer(1));
+
+        assert!(result.is_ok());
+    }
+}
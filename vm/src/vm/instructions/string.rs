@@ -5,7 +5,9 @@ use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
 use crate::slicing;
 use crate::vm::state::RcState;
+use memchr::memmem;
 use num_bigint::BigInt;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[inline(always)]
 pub fn string_to_lower(
@@ -200,8 +202,7 @@ pub fn string_to_float(
     let string = str_ptr.string_value()?;
 
     if let Ok(value) = string.parse::<f64>() {
-        let pointer =
-            process.allocate(object_value::float(value), state.float_prototype);
+        let pointer = process.allocate_f64(value, state.float_prototype);
 
         Ok(pointer)
     } else {
@@ -223,3 +224,219 @@ pub fn string_byte(
 
     Ok(ObjectPointer::integer(byte))
 }
+
+#[inline(always)]
+pub fn string_find(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+    needle_ptr: ObjectPointer,
+    start_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let haystack = str_ptr.string_value()?.as_bytes();
+    let needle = needle_ptr.string_value()?.as_bytes();
+    let start = slicing::slice_index_to_usize(start_ptr, haystack.len())?;
+
+    if start > haystack.len() {
+        return Ok(state.nil_object);
+    }
+
+    // Searching the raw bytes is fine, as a valid UTF-8 needle can only match
+    // at a character boundary.
+    let result = memmem::find(&haystack[start..], needle)
+        .map_or(state.nil_object, |index| {
+            process.allocate_usize(start + index, state.integer_prototype)
+        });
+
+    Ok(result)
+}
+
+#[inline(always)]
+pub fn string_find_last(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+    needle_ptr: ObjectPointer,
+    end_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let haystack = str_ptr.string_value()?.as_bytes();
+    let needle = needle_ptr.string_value()?.as_bytes();
+    let end = if end_ptr == state.nil_object {
+        haystack.len()
+    } else {
+        slicing::slice_index_to_usize(end_ptr, haystack.len())?
+            .min(haystack.len())
+    };
+
+    let result = memmem::rfind(&haystack[..end], needle)
+        .map_or(state.nil_object, |index| {
+            process.allocate_usize(index, state.integer_prototype)
+        });
+
+    Ok(result)
+}
+
+#[inline(always)]
+pub fn string_starts_with(
+    state: &RcState,
+    str_ptr: ObjectPointer,
+    prefix_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+    let prefix = prefix_ptr.string_value()?;
+
+    if string.starts_with(prefix.as_slice()) {
+        Ok(state.true_object)
+    } else {
+        Ok(state.false_object)
+    }
+}
+
+#[inline(always)]
+pub fn string_ends_with(
+    state: &RcState,
+    str_ptr: ObjectPointer,
+    suffix_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+    let suffix = suffix_ptr.string_value()?;
+
+    if string.ends_with(suffix.as_slice()) {
+        Ok(state.true_object)
+    } else {
+        Ok(state.false_object)
+    }
+}
+
+#[inline(always)]
+pub fn string_split(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+    separator_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+    let separator = separator_ptr.string_value()?;
+
+    let segments = if string.is_empty() {
+        Vec::new()
+    } else if separator.is_empty()
+        || separator.as_slice().chars().count()
+            >= string.as_slice().chars().count()
+    {
+        // A separator that is at least as long as the String (in characters)
+        // never splits it, even if both are the same.
+        vec![str_ptr]
+    } else {
+        // A trailing separator doesn't produce an empty segment, which is what
+        // split_terminator() gives us.
+        string
+            .split_terminator(separator.as_slice())
+            .map(|segment| allocate_string(state, process, segment))
+            .collect()
+    };
+
+    Ok(process.allocate(object_value::array(segments), state.array_prototype))
+}
+
+#[inline(always)]
+pub fn string_replace(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+    from_ptr: ObjectPointer,
+    to_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+    let from = from_ptr.string_value()?;
+
+    if from.is_empty() {
+        return Ok(str_ptr);
+    }
+
+    let replaced =
+        string.replace(from.as_slice(), to_ptr.string_value()?.as_slice());
+
+    Ok(
+        process
+            .allocate(object_value::string(replaced), state.string_prototype),
+    )
+}
+
+#[inline(always)]
+pub fn string_trim(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+
+    Ok(allocate_string(state, process, string.trim()))
+}
+
+#[inline(always)]
+pub fn string_trim_start(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+
+    Ok(allocate_string(state, process, string.trim_start()))
+}
+
+#[inline(always)]
+pub fn string_trim_end(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let string = str_ptr.string_value()?;
+
+    Ok(allocate_string(state, process, string.trim_end()))
+}
+
+#[inline(always)]
+pub fn string_characters(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let mut buffer = [0; 4];
+    let chars = str_ptr
+        .string_value()?
+        .chars()
+        .map(|character| {
+            allocate_string(state, process, character.encode_utf8(&mut buffer))
+        })
+        .collect();
+
+    Ok(process.allocate(object_value::array(chars), state.array_prototype))
+}
+
+#[inline(always)]
+pub fn string_graphemes(
+    state: &RcState,
+    process: &RcProcess,
+    str_ptr: ObjectPointer,
+) -> Result<ObjectPointer, String> {
+    let graphemes = str_ptr
+        .string_value()?
+        .graphemes(true)
+        .map(|grapheme| allocate_string(state, process, grapheme))
+        .collect();
+
+    Ok(process.allocate(object_value::array(graphemes), state.array_prototype))
+}
+
+/// Allocates a new String using the given string slice.
+pub fn allocate_string(
+    state: &RcState,
+    process: &RcProcess,
+    string: &str,
+) -> ObjectPointer {
+    process.allocate(
+        object_value::string(string.to_string()),
+        state.string_prototype,
+    )
+}
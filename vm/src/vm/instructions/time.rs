@@ -11,14 +11,13 @@ pub fn time_monotonic(state: &RcState, process: &RcProcess) -> ObjectPointer {
     let duration = state.start_time.elapsed();
     let seconds = duration::to_f64(Some(duration));
 
-    process.allocate(object_value::float(seconds), state.float_prototype)
+    process.allocate_f64(seconds, state.float_prototype)
 }
 
 #[inline(always)]
 pub fn time_system(state: &RcState, process: &RcProcess) -> ObjectPointer {
     let dt = DateTime::now();
-    let timestamp = process
-        .allocate(object_value::float(dt.timestamp()), state.float_prototype);
+    let timestamp = process.allocate_f64(dt.timestamp(), state.float_prototype);
 
     let offset = ObjectPointer::integer(dt.utc_offset());
 
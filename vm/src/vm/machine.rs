@@ -1,21 +1,32 @@
 //! Virtual Machine for running instructions
+use crate::crash_report;
+use crate::deadlock_detector::Worker as DeadlockDetectorWorker;
 use crate::execution_context::ExecutionContext;
 use crate::gc::collection::Collection;
 use crate::integer_operations;
+#[cfg(feature = "jit")]
+use crate::jit;
 use crate::network_poller::Worker as NetworkPollerWorker;
 use crate::numeric::division::{FlooredDiv, OverflowingFlooredDiv};
 use crate::numeric::modulo::{Modulo, OverflowingModulo};
 use crate::object_pointer::ObjectPointer;
 use crate::object_value;
+use crate::optimiser::{
+    COMPARE_EQUALS, COMPARE_GREATER, COMPARE_GREATER_OR_EQUAL, COMPARE_SMALLER,
+    COMPARE_SMALLER_OR_EQUAL,
+};
 use crate::process::RcProcess;
 use crate::runtime_error::RuntimeError;
 use crate::runtime_panic;
+use crate::sandbox::Capability;
 use crate::scheduler::join_list::JoinList;
 use crate::scheduler::process_worker::ProcessWorker;
+use crate::signals::Worker as SignalsWorker;
 use crate::vm::instruction::Opcode;
 use crate::vm::instructions::array;
 use crate::vm::instructions::block;
 use crate::vm::instructions::byte_array;
+use crate::vm::instructions::decimal;
 use crate::vm::instructions::env;
 use crate::vm::instructions::ffi;
 use crate::vm::instructions::float;
@@ -27,19 +38,26 @@ use crate::vm::instructions::module;
 use crate::vm::instructions::object;
 use crate::vm::instructions::process;
 use crate::vm::instructions::random;
+use crate::vm::instructions::regex;
 use crate::vm::instructions::socket;
 use crate::vm::instructions::string;
 use crate::vm::instructions::time;
 use crate::vm::state::RcState;
 use num_bigint::BigInt;
+use std::cmp::Ordering;
 use std::i32;
 use std::ops::{Add, Mul, Sub};
 use std::panic;
 use std::thread;
+use std::time::Instant;
 
 /// The name of the module that acts as the entry point in an Inko program.
 const MAIN_MODULE_NAME: &str = "main";
 
+/// The number of calls a panic handler can perform when the maximum call depth
+/// of a process has been exceeded.
+const PANIC_HANDLER_CALL_DEPTH: usize = 1000;
+
 macro_rules! reset_context {
     ($process:expr, $context:ident, $index:ident) => {{
         $context = $process.context_mut();
@@ -103,6 +121,10 @@ macro_rules! safepoint_and_reduce {
             return Ok(());
         }
 
+        if $process.reduce() {
+            $vm.reduction_budget_exceeded(&$process)?;
+        }
+
         if $reductions > 0 {
             $reductions -= 1;
         } else {
@@ -175,6 +197,8 @@ impl Machine {
         // cross-platform way of waking up the system poller, so we just don't
         // wait for it to finish when terminating.
         let poller_guard = self.start_network_poller_thread();
+        let signals_guard = self.start_signals_thread();
+        let deadlock_guard = self.start_deadlock_detector_thread();
 
         // Starting the primary threads will block this thread, as the main
         // worker will run directly onto the current thread. As such, we must
@@ -188,9 +212,32 @@ impl Machine {
             || gc_pool_guard.join().is_err()
             || timeout_guard.join().is_err()
             || poller_guard.join().is_err()
+            || signals_guard.join().is_err()
+            || deadlock_guard.join().is_err()
         {
             self.state.set_exit_status(1);
         }
+
+        self.write_coverage();
+    }
+
+    /// Writes the line coverage of the program, if coverage is recorded.
+    fn write_coverage(&self) {
+        let (coverage, path) = match (
+            self.state.coverage.as_ref(),
+            self.state.config.coverage_file.as_ref(),
+        ) {
+            (Some(coverage), Some(path)) => (coverage, path),
+            _ => return,
+        };
+
+        if let Err(error) = coverage.write(&self.state.modules.lock(), path) {
+            eprintln!(
+                "Failed to write the coverage to {}: {}",
+                path.display(),
+                error
+            );
+        }
     }
 
     fn start_primary_threads(&self) -> JoinList<()> {
@@ -227,6 +274,28 @@ impl Machine {
             .unwrap()
     }
 
+    fn start_signals_thread(&self) -> thread::JoinHandle<()> {
+        let state = self.state.clone();
+
+        thread::Builder::new()
+            .name("signals".to_string())
+            .spawn(move || {
+                SignalsWorker::new(state).run();
+            })
+            .unwrap()
+    }
+
+    fn start_deadlock_detector_thread(&self) -> thread::JoinHandle<()> {
+        let state = self.state.clone();
+
+        thread::Builder::new()
+            .name("deadlock detector".to_string())
+            .spawn(move || {
+                DeadlockDetectorWorker::new(state).run();
+            })
+            .unwrap()
+    }
+
     fn parse_image(&self, path: &str) {
         self.state.parse_image(path).unwrap();
     }
@@ -253,22 +322,29 @@ impl Machine {
         // We are using AssertUnwindSafe here so we can pass a &mut Worker to
         // run()/panic(). This might be risky if values captured are not unwind
         // safe, so take care when capturing new variables.
+        let start = Instant::now();
         let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
             if let Err(message) = self.run(worker, process) {
                 self.panic(worker, process, &message);
             }
         }));
 
+        process.add_cpu_time(start.elapsed());
+
         if let Err(error) = result {
-            if let Ok(message) = error.downcast::<String>() {
-                self.panic(worker, process, &message);
+            let message = if let Ok(message) = error.downcast::<String>() {
+                *message
             } else {
-                self.panic(
-                    worker,
-                    process,
-                    &"The VM panicked with an unknown error",
-                );
+                "The VM panicked with an unknown error".to_string()
             };
+
+            // If the panic isn't handled, the default panic handler writes the
+            // crash report.
+            if self.has_panic_handler(process) {
+                crash_report::write(&self.state, process, &message);
+            }
+
+            self.panic(worker, process, &message);
         }
     }
 
@@ -282,15 +358,46 @@ impl Machine {
         worker: &mut ProcessWorker,
         process: &RcProcess,
     ) -> Result<(), String> {
-        let mut reductions = self.state.config.reductions;
+        let mut reductions =
+            process.priority().reductions(self.state.config.reductions);
         let mut context;
         let mut index;
         let mut instruction;
 
         reset_context!(process, context, index);
 
+        if process.take_stack_dump_request() {
+            runtime_panic::display_stack(
+                process,
+                process.state(),
+                self.state.config.panic_format,
+            );
+        }
+
+        // A process may have been rescheduled because another process
+        // requested it to terminate.
+        if process.is_termination_requested() {
+            if self.unwind_for_termination(process)? {
+                self.terminate_process(worker, process);
+
+                return Ok(());
+            }
+
+            reset_context!(process, context, index);
+        }
+
         'exec_loop: loop {
+            #[cfg(feature = "jit")]
+            {
+                index = jit::enter(&self.state, context, index);
+            }
+
             instruction = unsafe { context.code.instruction(index) };
+
+            if let Some(coverage) = self.state.coverage.as_ref() {
+                coverage.hit(&context.code, index);
+            }
+
             index += 1;
 
             match instruction.opcode {
@@ -409,6 +516,15 @@ impl Machine {
                     }
 
                     reset_context!(process, context, index);
+
+                    if process.is_termination_requested() {
+                        if self.unwind_for_termination(process)? {
+                            break 'exec_loop;
+                        }
+
+                        reset_context!(process, context, index);
+                    }
+
                     safepoint_and_reduce!(self, process, reductions);
                 }
                 Opcode::GotoIfFalse => {
@@ -565,6 +681,196 @@ impl Machine {
                 Opcode::IntegerSmallerOrEqual => {
                     integer_bool_op!(self.state, context, instruction, <=);
                 }
+                Opcode::IntegerPow => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let exp = context.get_register(instruction.arg(2));
+                    let res =
+                        integer::integer_pow(&self.state, process, ptr, exp)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::IntegerGcd => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let other = context.get_register(instruction.arg(2));
+                    let res =
+                        integer::integer_gcd(&self.state, process, ptr, other)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::IntegerSqrt => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = integer::integer_sqrt(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalFromString => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res = try_runtime_error!(
+                        decimal::decimal_from_string(&self.state, process, val),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalToString => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res =
+                        decimal::decimal_to_string(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalAdd => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_add(
+                        &self.state,
+                        process,
+                        left,
+                        right,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalSub => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_sub(
+                        &self.state,
+                        process,
+                        left,
+                        right,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalMul => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_mul(
+                        &self.state,
+                        process,
+                        left,
+                        right,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalDiv => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let scale = context.get_register(instruction.arg(3));
+                    let mode = context.get_register(instruction.arg(4));
+                    let res = decimal::decimal_div(
+                        &self.state,
+                        process,
+                        left,
+                        right,
+                        scale,
+                        mode,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalRound => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let scale = context.get_register(instruction.arg(2));
+                    let mode = context.get_register(instruction.arg(3));
+                    let res = decimal::decimal_round(
+                        &self.state,
+                        process,
+                        val,
+                        scale,
+                        mode,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalScale => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res =
+                        decimal::decimal_scale(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalSmaller => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_compare(
+                        &self.state,
+                        left,
+                        right,
+                        |ordering| ordering == Ordering::Less,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalGreater => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_compare(
+                        &self.state,
+                        left,
+                        right,
+                        |ordering| ordering == Ordering::Greater,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalEquals => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_compare(
+                        &self.state,
+                        left,
+                        right,
+                        |ordering| ordering == Ordering::Equal,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalGreaterOrEqual => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_compare(
+                        &self.state,
+                        left,
+                        right,
+                        |ordering| ordering != Ordering::Less,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::DecimalSmallerOrEqual => {
+                    let reg = instruction.arg(0);
+                    let left = context.get_register(instruction.arg(1));
+                    let right = context.get_register(instruction.arg(2));
+                    let res = decimal::decimal_compare(
+                        &self.state,
+                        left,
+                        right,
+                        |ordering| ordering != Ordering::Greater,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
                 Opcode::FloatAdd => {
                     float_op!(self.state, process, context, instruction, +);
                 }
@@ -908,7 +1214,9 @@ impl Machine {
                     let reg = instruction.arg(0);
                     let rec = context.get_register(instruction.arg(1));
                     let name = context.get_register(instruction.arg(2));
-                    let res = object::get_attribute(&self.state, rec, name);
+                    let cache = context.code.inline_cache(instruction.arg(3));
+                    let res =
+                        object::get_attribute(&self.state, cache, rec, name);
 
                     context.set_register(reg, res);
                 }
@@ -1145,6 +1453,109 @@ impl Machine {
 
                     context.set_register(reg, res);
                 }
+                Opcode::FloatSqrt => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_sqrt(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatPow => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let exp = context.get_register(instruction.arg(2));
+                    let res = float::float_pow(&self.state, process, ptr, exp)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatExp => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_exp(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatLn => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_ln(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatLog2 => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_log2(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatLog10 => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_log10(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatSin => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_sin(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatCos => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_cos(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatTan => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_tan(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatAsin => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_asin(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatAcos => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_acos(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatAtan => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let res = float::float_atan(&self.state, process, ptr)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatAtan2 => {
+                    let reg = instruction.arg(0);
+                    let y = context.get_register(instruction.arg(1));
+                    let x = context.get_register(instruction.arg(2));
+                    let res = float::float_atan2(&self.state, process, y, x)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::FloatFma => {
+                    let reg = instruction.arg(0);
+                    let ptr = context.get_register(instruction.arg(1));
+                    let mul = context.get_register(instruction.arg(2));
+                    let add = context.get_register(instruction.arg(3));
+                    let res =
+                        float::float_fma(&self.state, process, ptr, mul, add)?;
+
+                    context.set_register(reg, res);
+                }
                 Opcode::Close => {
                     let ptr = context.get_register(instruction.arg(0));
 
@@ -1178,7 +1589,7 @@ impl Machine {
                     let path = context.get_register(instruction.arg(0));
 
                     try_runtime_error!(
-                        io::file_remove(path),
+                        io::file_remove(&self.state, path),
                         self,
                         process,
                         context,
@@ -1191,6 +1602,21 @@ impl Machine {
                     return Err(msg.string_value()?.to_owned_string());
                 }
                 Opcode::Exit => {
+                    // A denied exit shouldn't run any deferred blocks, as the
+                    // program isn't going to exit. Instead we throw an error,
+                    // allowing the program to handle this.
+                    try_runtime_error!(
+                        self.state
+                            .config
+                            .sandbox
+                            .check(Capability::Exit)
+                            .map_err(RuntimeError::Exception),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
                     // Any pending deferred blocks should be executed first.
                     if context
                         .schedule_deferred_blocks_of_all_parents(process)?
@@ -1492,24 +1918,199 @@ impl Machine {
 
                     context.set_register(reg, res);
                 }
-                Opcode::EnvGet => {
+                Opcode::ByteArraySlice => {
                     let reg = instruction.arg(0);
-                    let var = context.get_register(instruction.arg(1));
-                    let val = try_runtime_error!(
-                        env::env_get(&self.state, process, var),
+                    let ary = context.get_register(instruction.arg(1));
+                    let start = context.get_register(instruction.arg(2));
+                    let len = context.get_register(instruction.arg(3));
+                    let res = byte_array::byte_array_slice(
+                        &self.state,
+                        process,
+                        ary,
+                        start,
+                        len,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayCopyRange => {
+                    let reg = instruction.arg(0);
+                    let target = context.get_register(instruction.arg(1));
+                    let offset = context.get_register(instruction.arg(2));
+                    let source = context.get_register(instruction.arg(3));
+                    let start = context.get_register(instruction.arg(4));
+                    let len = context.get_register(instruction.arg(5));
+                    let res = try_runtime_error!(
+                        byte_array::byte_array_copy_range(
+                            &self.state,
+                            process,
+                            target,
+                            offset,
+                            source,
+                            start,
+                            len,
+                        ),
                         self,
                         process,
                         context,
                         index
                     );
 
-                    context.set_register(reg, val);
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayAppend => {
+                    let reg = instruction.arg(0);
+                    let target = context.get_register(instruction.arg(1));
+                    let source = context.get_register(instruction.arg(2));
+                    let res = byte_array::byte_array_append(target, source)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayFill => {
+                    let reg = instruction.arg(0);
+                    let ary = context.get_register(instruction.arg(1));
+                    let val = context.get_register(instruction.arg(2));
+                    let start = context.get_register(instruction.arg(3));
+                    let len = context.get_register(instruction.arg(4));
+                    let res = try_runtime_error!(
+                        byte_array::byte_array_fill(ary, val, start, len),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayFind => {
+                    let reg = instruction.arg(0);
+                    let ary = context.get_register(instruction.arg(1));
+                    let needle = context.get_register(instruction.arg(2));
+                    let start = context.get_register(instruction.arg(3));
+                    let res = byte_array::byte_array_find(
+                        &self.state,
+                        process,
+                        ary,
+                        needle,
+                        start,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayReadInteger => {
+                    let reg = instruction.arg(0);
+                    let ary = context.get_register(instruction.arg(1));
+                    let offset = context.get_register(instruction.arg(2));
+                    let size = context.get_register(instruction.arg(3));
+                    let signed = context.get_register(instruction.arg(4));
+                    let big_endian = context.get_register(instruction.arg(5));
+                    let res = try_runtime_error!(
+                        byte_array::byte_array_read_integer(
+                            &self.state,
+                            process,
+                            ary,
+                            offset,
+                            size,
+                            signed,
+                            big_endian,
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayWriteInteger => {
+                    let reg = instruction.arg(0);
+                    let ary = context.get_register(instruction.arg(1));
+                    let offset = context.get_register(instruction.arg(2));
+                    let val = context.get_register(instruction.arg(3));
+                    let size = context.get_register(instruction.arg(4));
+                    let big_endian = context.get_register(instruction.arg(5));
+                    let res = try_runtime_error!(
+                        byte_array::byte_array_write_integer(
+                            &self.state,
+                            ary,
+                            offset,
+                            val,
+                            size,
+                            big_endian,
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayReadFloat => {
+                    let reg = instruction.arg(0);
+                    let ary = context.get_register(instruction.arg(1));
+                    let offset = context.get_register(instruction.arg(2));
+                    let size = context.get_register(instruction.arg(3));
+                    let big_endian = context.get_register(instruction.arg(4));
+                    let res = try_runtime_error!(
+                        byte_array::byte_array_read_float(
+                            &self.state,
+                            process,
+                            ary,
+                            offset,
+                            size,
+                            big_endian,
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ByteArrayWriteFloat => {
+                    let reg = instruction.arg(0);
+                    let ary = context.get_register(instruction.arg(1));
+                    let offset = context.get_register(instruction.arg(2));
+                    let val = context.get_register(instruction.arg(3));
+                    let size = context.get_register(instruction.arg(4));
+                    let big_endian = context.get_register(instruction.arg(5));
+                    let res = try_runtime_error!(
+                        byte_array::byte_array_write_float(
+                            &self.state,
+                            ary,
+                            offset,
+                            val,
+                            size,
+                            big_endian,
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::EnvGet => {
+                    let reg = instruction.arg(0);
+                    let var = context.get_register(instruction.arg(1));
+                    let val = try_runtime_error!(
+                        env::env_get(&self.state, process, var),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, val);
                 }
                 Opcode::EnvSet => {
                     let reg = instruction.arg(0);
                     let var = context.get_register(instruction.arg(1));
                     let val = context.get_register(instruction.arg(2));
-                    let res = env::env_set(var, val)?;
+                    let res = env::env_set(&self.state, var, val)?;
 
                     context.set_register(reg, res);
                 }
@@ -1553,7 +2154,7 @@ impl Machine {
                     let reg = instruction.arg(0);
                     let dir = context.get_register(instruction.arg(1));
                     let res = try_runtime_error!(
-                        env::env_set_working_directory(dir),
+                        env::env_set_working_directory(&self.state, dir),
                         self,
                         process,
                         context,
@@ -1571,7 +2172,7 @@ impl Machine {
                 Opcode::EnvRemove => {
                     let var = context.get_register(instruction.arg(0));
 
-                    env::env_remove(var)?;
+                    env::env_remove(&self.state, var)?;
                 }
                 Opcode::BlockGetReceiver => {
                     let reg = instruction.arg(0);
@@ -1787,6 +2388,175 @@ impl Machine {
 
                     context.set_register(reg, res);
                 }
+                Opcode::StringFind => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let needle = context.get_register(instruction.arg(2));
+                    let start = context.get_register(instruction.arg(3));
+                    let res = string::string_find(
+                        &self.state,
+                        process,
+                        val,
+                        needle,
+                        start,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringFindLast => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let needle = context.get_register(instruction.arg(2));
+                    let end = context.get_register(instruction.arg(3));
+                    let res = string::string_find_last(
+                        &self.state,
+                        process,
+                        val,
+                        needle,
+                        end,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringStartsWith => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let prefix = context.get_register(instruction.arg(2));
+                    let res =
+                        string::string_starts_with(&self.state, val, prefix)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringEndsWith => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let suffix = context.get_register(instruction.arg(2));
+                    let res =
+                        string::string_ends_with(&self.state, val, suffix)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringSplit => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let sep = context.get_register(instruction.arg(2));
+                    let res =
+                        string::string_split(&self.state, process, val, sep)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringReplace => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let from = context.get_register(instruction.arg(2));
+                    let to = context.get_register(instruction.arg(3));
+                    let res = string::string_replace(
+                        &self.state,
+                        process,
+                        val,
+                        from,
+                        to,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringTrim => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res = string::string_trim(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringTrimStart => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res =
+                        string::string_trim_start(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringTrimEnd => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res =
+                        string::string_trim_end(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringCharacters => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res =
+                        string::string_characters(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::StringGraphemes => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res =
+                        string::string_graphemes(&self.state, process, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::RegexCompile => {
+                    let reg = instruction.arg(0);
+                    let val = context.get_register(instruction.arg(1));
+                    let res = try_runtime_error!(
+                        regex::regex_compile(&self.state, process, val),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::RegexMatch => {
+                    let reg = instruction.arg(0);
+                    let regex = context.get_register(instruction.arg(1));
+                    let val = context.get_register(instruction.arg(2));
+                    let res = regex::regex_match(&self.state, regex, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::RegexFindAll => {
+                    let reg = instruction.arg(0);
+                    let regex = context.get_register(instruction.arg(1));
+                    let val = context.get_register(instruction.arg(2));
+                    let res = regex::regex_find_all(
+                        &self.state,
+                        process,
+                        regex,
+                        val,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::RegexSplit => {
+                    let reg = instruction.arg(0);
+                    let regex = context.get_register(instruction.arg(1));
+                    let val = context.get_register(instruction.arg(2));
+                    let res =
+                        regex::regex_split(&self.state, process, regex, val)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::RegexReplace => {
+                    let reg = instruction.arg(0);
+                    let regex = context.get_register(instruction.arg(1));
+                    let val = context.get_register(instruction.arg(2));
+                    let with = context.get_register(instruction.arg(3));
+                    let res = regex::regex_replace(
+                        &self.state,
+                        process,
+                        regex,
+                        val,
+                        with,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
                 Opcode::FloatToBits => {
                     let reg = instruction.arg(0);
                     let val = context.get_register(instruction.arg(1));
@@ -1827,6 +2597,289 @@ impl Machine {
 
                     context.set_register(reg, res);
                 }
+                Opcode::SocketWriteVectored => {
+                    let reg = instruction.arg(0);
+                    let sock = context.get_register(instruction.arg(1));
+                    let buffers = context.get_register(instruction.arg(2));
+                    let res = try_runtime_error!(
+                        socket::socket_write_vectored(
+                            &self.state,
+                            process,
+                            sock,
+                            buffers
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::SocketSendFile => {
+                    let reg = instruction.arg(0);
+                    let sock = context.get_register(instruction.arg(1));
+                    let file = context.get_register(instruction.arg(2));
+                    let len = context.get_register(instruction.arg(3));
+                    let res = try_runtime_error!(
+                        socket::socket_send_file(
+                            &self.state,
+                            process,
+                            sock,
+                            file,
+                            len
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::SocketPeerCredentials => {
+                    let reg = instruction.arg(0);
+                    let sock = context.get_register(instruction.arg(1));
+                    let res = try_runtime_error!(
+                        socket::socket_peer_credentials(
+                            &self.state,
+                            process,
+                            sock
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::SocketSendHandles => {
+                    let reg = instruction.arg(0);
+                    let sock = context.get_register(instruction.arg(1));
+                    let handles = context.get_register(instruction.arg(2));
+                    let res = try_runtime_error!(
+                        socket::socket_send_handles(
+                            &self.state,
+                            process,
+                            sock,
+                            handles
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::SocketReceiveHandles => {
+                    let reg = instruction.arg(0);
+                    let sock = context.get_register(instruction.arg(1));
+                    let res = try_runtime_error!(
+                        socket::socket_receive_handles(
+                            &self.state,
+                            process,
+                            sock
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::SignalSubscribe => {
+                    let reg = instruction.arg(0);
+                    let signal = context.get_register(instruction.arg(1));
+                    let res = try_runtime_error!(
+                        process::signal_subscribe(&self.state, process, signal),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::SignalUnsubscribe => {
+                    let reg = instruction.arg(0);
+                    let signal = context.get_register(instruction.arg(1));
+                    let res = try_runtime_error!(
+                        process::signal_unsubscribe(
+                            &self.state,
+                            process,
+                            signal
+                        ),
+                        self,
+                        process,
+                        context,
+                        index
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ProcessSetPriority => {
+                    let reg = instruction.arg(0);
+                    let proc = context.get_register(instruction.arg(1));
+                    let priority = context.get_register(instruction.arg(2));
+                    let res = process::process_set_priority(proc, priority)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ProcessPriority => {
+                    let reg = instruction.arg(0);
+                    let proc = context.get_register(instruction.arg(1));
+                    let res = process::process_priority(proc)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::TimerStart => {
+                    let reg = instruction.arg(0);
+                    let proc = context.get_register(instruction.arg(1));
+                    let msg = context.get_register(instruction.arg(2));
+                    let delay = context.get_register(instruction.arg(3));
+                    let interval = context.get_register(instruction.arg(4));
+                    let res = process::timer_start(
+                        &self.state,
+                        process,
+                        proc,
+                        msg,
+                        delay,
+                        interval,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::TimerCancel => {
+                    let reg = instruction.arg(0);
+                    let id = context.get_register(instruction.arg(1));
+                    let res = process::timer_cancel(&self.state, id)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ProcessTerminate => {
+                    let reg = instruction.arg(0);
+                    let proc = context.get_register(instruction.arg(1));
+                    let reason = context.get_register(instruction.arg(2));
+                    let res = process::process_terminate(
+                        &self.state,
+                        process,
+                        proc,
+                        reason,
+                    )?;
+
+                    context.set_register(reg, res);
+
+                    // A process may request itself to terminate, in which case
+                    // we start unwinding right away.
+                    if process.is_termination_requested() {
+                        context.instruction_index = index;
+
+                        if self.unwind_for_termination(process)? {
+                            break 'exec_loop;
+                        }
+
+                        reset_context!(process, context, index);
+                    }
+                }
+                Opcode::ProcessTerminationReason => {
+                    let reg = instruction.arg(0);
+                    let res = process::process_termination_reason(
+                        &self.state,
+                        process,
+                    );
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ProcessList => {
+                    let reg = instruction.arg(0);
+                    let res = process::process_list(&self.state, process);
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ProcessInfo => {
+                    let reg = instruction.arg(0);
+                    let proc = context.get_register(instruction.arg(1));
+                    let res =
+                        process::process_info(&self.state, process, proc)?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::ProcessSetReductionBudget => {
+                    let reg = instruction.arg(0);
+                    let proc = context.get_register(instruction.arg(1));
+                    let budget = context.get_register(instruction.arg(2));
+                    let watcher = context.get_register(instruction.arg(3));
+                    let res = process::process_set_reduction_budget(
+                        &self.state,
+                        proc,
+                        budget,
+                        watcher,
+                    )?;
+
+                    context.set_register(reg, res);
+                }
+                Opcode::GetLocalAttribute => {
+                    let reg = instruction.arg(0);
+                    let rec = general::get_local(context, instruction.arg(4));
+
+                    context.set_register(instruction.arg(1), rec);
+
+                    let name = context.get_register(instruction.arg(2));
+                    let cache = context.code.inline_cache(instruction.arg(3));
+                    let res =
+                        object::get_attribute(&self.state, cache, rec, name);
+
+                    context.set_register(reg, res);
+                }
+                Opcode::IntegerCompareGoto => {
+                    match instruction.arg(4) {
+                        COMPARE_SMALLER => {
+                            integer_bool_op!(self.state, context, instruction, <);
+                        }
+                        COMPARE_GREATER => {
+                            integer_bool_op!(self.state, context, instruction, >);
+                        }
+                        COMPARE_EQUALS => {
+                            integer_bool_op!(self.state, context, instruction, ==);
+                        }
+                        COMPARE_GREATER_OR_EQUAL => {
+                            integer_bool_op!(self.state, context, instruction, >=);
+                        }
+                        COMPARE_SMALLER_OR_EQUAL => {
+                            integer_bool_op!(self.state, context, instruction, <=);
+                        }
+                        kind => {
+                            return Err(format!(
+                                "{} is not a valid integer comparison",
+                                kind
+                            ));
+                        }
+                    }
+
+                    let val = context.get_register(instruction.arg(0));
+                    let jump_if_true = instruction.arg(5) == 1;
+
+                    if is_false!(self.state, val) != jump_if_true {
+                        index = instruction.arg(3) as usize;
+                    }
+                }
+                Opcode::IntegerAddLiteral => {
+                    let lit = general::set_literal(context, instruction.arg(3));
+
+                    context.set_register(instruction.arg(2), lit);
+
+                    integer_overflow_op!(
+                        process,
+                        context,
+                        self.state.integer_prototype,
+                        instruction,
+                        add,
+                        overflowing_add
+                    );
+                }
                 Opcode::SocketRead => {
                     let reg = instruction.arg(0);
                     let sock = context.get_register(instruction.arg(1));
@@ -1982,7 +3035,7 @@ impl Machine {
                     let sock = context.get_register(instruction.arg(1));
                     let backlog = context.get_register(instruction.arg(2));
                     let res = try_runtime_error!(
-                        socket::socket_listen(sock, backlog),
+                        socket::socket_listen(&self.state, sock, backlog),
                         self,
                         process,
                         context,
@@ -2069,6 +3122,17 @@ impl Machine {
             };
         }
 
+        self.terminate_process(worker, process);
+
+        Ok(())
+    }
+
+    /// Terminates a process that finished running.
+    fn terminate_process(
+        &self,
+        worker: &mut ProcessWorker,
+        process: &RcProcess,
+    ) {
         if process.is_pinned() {
             // A pinned process can only run on the corresponding worker.
             // Because pinned workers won't run already unpinned processes, and
@@ -2078,13 +3142,59 @@ impl Machine {
         }
 
         process.terminate(&self.state);
+        self.state.processes.remove(process);
+        self.state.signals.unsubscribe_all(process);
 
         // Terminate once the main process has finished execution.
         if process.is_main() {
             self.state.terminate(0);
         }
+    }
 
-        Ok(())
+    /// Unwinds the stack of a process that was requested to terminate.
+    ///
+    /// The deferred blocks of all call frames are scheduled, starting with the
+    /// blocks of the most recent call frame. Once the last deferred block
+    /// returns, the process terminates.
+    ///
+    /// The OK value is `true` if there are no deferred blocks to run, in which
+    /// case the process should terminate right away.
+    fn unwind_for_termination(
+        &self,
+        process: &RcProcess,
+    ) -> Result<bool, String> {
+        process.start_unwinding_for_termination();
+
+        let mut frames = Vec::new();
+
+        loop {
+            let mut deferred = Vec::new();
+
+            process.context_mut().move_deferred_blocks_to(&mut deferred);
+            frames.push(deferred);
+
+            if process.pop_context() {
+                break;
+            }
+        }
+
+        let mut scheduled = false;
+
+        // Blocks that are scheduled last run first, so we schedule the blocks
+        // of the outer most frames first.
+        for pointer in frames.into_iter().rev().flatten() {
+            let mut context =
+                ExecutionContext::from_block(pointer.block_value()?);
+
+            if !scheduled {
+                context.terminate_upon_return();
+                scheduled = true;
+            }
+
+            process.push_context(context);
+        }
+
+        Ok(!scheduled)
     }
 
     /// Checks if a garbage collection run should be scheduled for the given
@@ -2103,6 +3213,44 @@ impl Machine {
         true
     }
 
+    /// Handles a process exceeding its reduction budget.
+    ///
+    /// If the budget has a watcher, the watcher is sent the process as a
+    /// message and the process keeps running. Otherwise an error is returned,
+    /// resulting in the process panicking.
+    fn reduction_budget_exceeded(
+        &self,
+        process: &RcProcess,
+    ) -> Result<(), String> {
+        let budget =
+            if let Some(budget) = process.take_exceeded_reduction_budget() {
+                budget
+            } else {
+                return Ok(());
+            };
+
+        if let Some(watcher) = budget.watcher {
+            let message = process.allocate(
+                object_value::process(process.clone()),
+                self.state.process_prototype,
+            );
+
+            if watcher == *process {
+                process.send_message_from_self(message);
+            } else {
+                watcher.send_message_from_external_process(message);
+                process::attempt_to_reschedule_process(&self.state, &watcher);
+            }
+
+            return Ok(());
+        }
+
+        Err(format!(
+            "The reduction budget was exceeded after performing {} reductions",
+            budget.reductions
+        ))
+    }
+
     fn throw(
         &self,
         process: &RcProcess,
@@ -2155,6 +3303,18 @@ impl Machine {
         process: &RcProcess,
         message: &str,
     ) {
+        if process.call_depth_exceeded() {
+            // If the panic handler itself exceeds the call depth, we can't run
+            // it again.
+            if process.max_call_depth() > self.state.config.max_call_depth {
+                self.run_default_panic_handler(process, message);
+                return;
+            }
+
+            // The panic handler needs some room to run blocks of its own.
+            process.increase_max_call_depth(PANIC_HANDLER_CALL_DEPTH);
+        }
+
         let handler_opt = process
             .panic_handler()
             .cloned()
@@ -2210,7 +3370,17 @@ impl Machine {
     ///
     /// This handler will _not_ execute any deferred blocks.
     fn run_default_panic_handler(&self, process: &RcProcess, message: &str) {
-        runtime_panic::display_panic(process, message);
+        runtime_panic::display_panic(
+            process,
+            message,
+            self.state.config.panic_format,
+        );
+        crash_report::write(&self.state, process, message);
         self.state.terminate(1);
     }
+
+    fn has_panic_handler(&self, process: &RcProcess) -> bool {
+        process.panic_handler().is_some()
+            || self.state.default_panic_handler().is_some()
+    }
 }
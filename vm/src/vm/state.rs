@@ -5,16 +5,22 @@
 //! etc.
 use crate::arc_without_weak::ArcWithoutWeak;
 use crate::config::Config;
+use crate::coverage::Coverage;
+use crate::deadlock_detector::DeadlockDetector;
 use crate::gc::coordinator::Pool as GcPool;
 use crate::immix::global_allocator::{GlobalAllocator, RcGlobalAllocator};
 use crate::immix::permanent_allocator::PermanentAllocator;
 use crate::immutable_string::ImmutableString;
+use crate::inline_cache::Epoch as InlineCacheEpoch;
 use crate::modules::Modules;
 use crate::network_poller::NetworkPoller;
 use crate::object_pointer::ObjectPointer;
 use crate::object_value;
+use crate::process::ProcessInfo;
+use crate::process_registry::ProcessRegistry;
 use crate::scheduler::process_scheduler::ProcessScheduler;
 use crate::scheduler::timeout_worker::TimeoutWorker;
+use crate::signals::Signals;
 use crate::string_pool::StringPool;
 use num_bigint::BigInt;
 use parking_lot::Mutex;
@@ -143,6 +149,12 @@ pub struct State {
     /// The prototype to use for hashers.
     pub hasher_prototype: ObjectPointer,
 
+    /// The prototype to use for decimals.
+    pub decimal_prototype: ObjectPointer,
+
+    /// The prototype to use for regular expressions.
+    pub regex_prototype: ObjectPointer,
+
     /// The commandline arguments passed to an Inko program.
     pub arguments: Vec<ObjectPointer>,
 
@@ -155,8 +167,23 @@ pub struct State {
     /// The system polling mechanism to use for polling non-blocking sockets.
     pub network_poller: NetworkPoller,
 
+    /// The processes subscribed to signals.
+    pub signals: Signals,
+
     /// All modules that are available to the current program.
     pub modules: Mutex<Modules>,
+
+    /// All processes that are alive.
+    pub processes: ProcessRegistry,
+
+    /// The type used for waiting between checks for deadlocks.
+    pub deadlock_detector: DeadlockDetector,
+
+    /// The instructions executed, if line coverage is recorded.
+    pub coverage: Option<Coverage>,
+
+    /// The epoch used for invalidating inline caches.
+    pub inline_cache_epoch: InlineCacheEpoch,
 }
 
 impl RefUnwindSafe for State {}
@@ -194,6 +221,8 @@ impl State {
         let write_only_file_prototype = perm_alloc.allocate_empty();
         let read_write_file_prototype = perm_alloc.allocate_empty();
         let hasher_prototype = perm_alloc.allocate_empty();
+        let decimal_prototype = perm_alloc.allocate_empty();
+        let regex_prototype = perm_alloc.allocate_empty();
 
         integer_proto.set_prototype(object_proto);
         float_proto.set_prototype(object_proto);
@@ -217,8 +246,16 @@ impl State {
         write_only_file_prototype.set_prototype(object_proto);
         read_write_file_prototype.set_prototype(object_proto);
         hasher_prototype.set_prototype(object_proto);
+        decimal_prototype.set_prototype(object_proto);
+        regex_prototype.set_prototype(object_proto);
 
         let gc_pool = GcPool::new(config.gc_threads);
+        let coverage = if config.coverage_file.is_some() {
+            Some(Coverage::new())
+        } else {
+            None
+        };
+
         let mut state = State {
             scheduler: ProcessScheduler::new(
                 config.primary_threads,
@@ -257,8 +294,15 @@ impl State {
             write_only_file_prototype,
             read_write_file_prototype,
             hasher_prototype,
+            decimal_prototype,
+            regex_prototype,
             network_poller: NetworkPoller::new(),
+            signals: Signals::new(),
             modules: Mutex::new(Modules::new()),
+            processes: ProcessRegistry::new(),
+            deadlock_detector: DeadlockDetector::new(),
+            coverage,
+            inline_cache_epoch: InlineCacheEpoch::new(),
         };
 
         for argument in arguments {
@@ -325,6 +369,8 @@ impl State {
         self.gc_pool.terminate();
         self.timeout_worker.terminate();
         self.network_poller.terminate();
+        self.signals.terminate();
+        self.deadlock_detector.terminate();
     }
 
     pub fn set_exit_status(&self, new_status: i32) {
@@ -348,12 +394,22 @@ impl State {
     pub fn parse_image(&self, path: &str) -> Result<(), String> {
         self.modules.lock().parse_image(&self, path)
     }
+
+    /// Returns information about all processes that are alive.
+    pub fn process_info(&self) -> Vec<ProcessInfo> {
+        self.processes
+            .processes()
+            .iter()
+            .map(|process| process.info())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
+    use crate::vm::test::setup;
 
     #[test]
     fn test_intern() {
@@ -396,4 +452,13 @@ mod tests {
 
         assert_eq!(float.float_value().unwrap(), 10.5);
     }
+
+    #[test]
+    fn test_process_info() {
+        let (machine, _block, process) = setup();
+        let info = machine.state.process_info();
+
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].identifier, process.identifier());
+    }
 }